@@ -0,0 +1,62 @@
+/* The C API for embedding Uiua
+ *
+ * Build the library with
+ * `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+ * Results and errors are owned by the interpreter and stay valid
+ * until the next call to `uiua_eval` or `uiua_free`.
+ */
+
+#ifndef UIUA_H
+#define UIUA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct UiuaInterpreter UiuaInterpreter;
+
+typedef enum {
+    /* The data is `double`s */
+    UIUA_NUM = 0,
+    /* The data is `uint8_t`s */
+    UIUA_BYTE = 1,
+    /* The data is `uint32_t` Unicode code points */
+    UIUA_CHAR = 2,
+    /* Boxes and functions, whose data is not exposed */
+    UIUA_FUNC = 3,
+} UiuaElementType;
+
+typedef struct {
+    UiuaElementType element_type;
+    size_t rank;
+    const size_t *shape;
+    /* The number of elements */
+    size_t len;
+    /* The elements in row-major order, or NULL for UIUA_FUNC */
+    const void *data;
+} UiuaArray;
+
+/* Create an interpreter with the standard IO backend */
+UiuaInterpreter *uiua_new(void);
+/* Free an interpreter */
+void uiua_free(UiuaInterpreter *interp);
+/* Evaluate a null-terminated UTF-8 string of code
+ * Returns 0 on success and 1 on failure */
+int uiua_eval(UiuaInterpreter *interp, const char *code);
+/* Get the error of the last evaluation, or NULL if it succeeded */
+const char *uiua_error(const UiuaInterpreter *interp);
+/* Get the number of results of the last evaluation */
+size_t uiua_result_count(const UiuaInterpreter *interp);
+/* Get a result of the last evaluation, with the top of the stack last
+ * Returns false if the index is out of range */
+bool uiua_result(const UiuaInterpreter *interp, size_t index, UiuaArray *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
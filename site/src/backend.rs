@@ -16,7 +16,10 @@ pub struct WebBackend {
     pub stderr: Mutex<String>,
     pub trace: Mutex<String>,
     pub files: Mutex<HashMap<String, Vec<u8>>>,
+    pub vars: Mutex<HashMap<String, String>>,
+    pub clipboard: Mutex<String>,
     next_thread_id: AtomicU64,
+    next_temp_id: AtomicU64,
     thread_results: Mutex<HashMap<Handle, UiuaResult<Vec<Value>>>>,
 }
 
@@ -27,7 +30,10 @@ impl Default for WebBackend {
             stderr: String::new().into(),
             trace: String::new().into(),
             files: HashMap::new().into(),
+            vars: HashMap::new().into(),
+            clipboard: String::new().into(),
             next_thread_id: 0.into(),
+            next_temp_id: 0.into(),
             thread_results: HashMap::new().into(),
         }
     }
@@ -108,6 +114,61 @@ impl SysBackend for WebBackend {
             .cloned()
             .ok_or_else(|| format!("File not found: {path}"))
     }
+    fn file_exists(&self, path: &str) -> bool {
+        self.files.lock().unwrap().contains_key(path)
+    }
+    fn file_size(&self, path: &str) -> Result<u64, String> {
+        self.files
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|contents| contents.len() as u64)
+            .ok_or_else(|| format!("File not found: {path}"))
+    }
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        self.files
+            .lock()
+            .unwrap()
+            .remove(path)
+            .map(drop)
+            .ok_or_else(|| format!("File not found: {path}"))
+    }
+    fn create_temp_file(&self) -> Result<String, String> {
+        let path = format!(
+            "tmp/uiua-{}",
+            self.next_temp_id.fetch_add(1, Ordering::Relaxed)
+        );
+        self.files.lock().unwrap().insert(path.clone(), Vec::new());
+        Ok(path)
+    }
+    fn create_temp_dir(&self) -> Result<String, String> {
+        Ok(format!(
+            "tmp/uiua-{}",
+            self.next_temp_id.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.vars.lock().unwrap().get(name).cloned()
+    }
+    fn set_var(&self, name: &str, value: &str) -> Result<(), String> {
+        self.vars
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+    fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<_> = self.vars.lock().unwrap().clone().into_iter().collect();
+        vars.sort();
+        vars
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        Ok(self.clipboard.lock().unwrap().clone())
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        *self.clipboard.lock().unwrap() = contents.into();
+        Ok(())
+    }
     fn play_audio(&self, wav_bytes: Vec<u8>) -> Result<(), String> {
         self.stdout
             .lock()
@@ -13,7 +13,10 @@ mod uiuisms;
 
 use leptos::*;
 use leptos_router::*;
-use uiua::primitive::{ConstantDef, PrimClass, Primitive};
+use uiua::{
+    lsp::PrimHighlight,
+    primitive::{ConstantDef, Primitive},
+};
 use wasm_bindgen::JsCast;
 use web_sys::HtmlAudioElement;
 
@@ -286,26 +289,16 @@ fn prim_class(prim: Primitive) -> &'static str {
 
     match prim {
         Primitive::Transpose => code_font!("monadic-function-button trans"),
-        prim if prim.class() == PrimClass::Stack && prim.modifier_args().is_none() => {
-            code_font!("stack-function-button")
-        }
-        prim => {
-            if let Some(m) = prim.modifier_args() {
-                if m == 1 {
-                    code_font!("modifier1-button")
-                } else {
-                    code_font!("modifier2-button")
-                }
-            } else {
-                match prim.args() {
-                    Some(0) => code_font!("noadic-function-button"),
-                    Some(1) => code_font!("monadic-function-button"),
-                    Some(2) => code_font!("dyadic-function-button"),
-                    Some(3) => code_font!("triadic-function-button"),
-                    _ => code_font!("variadic-function-button"),
-                }
-            }
-        }
+        prim => match PrimHighlight::new(prim) {
+            PrimHighlight::Stack => code_font!("stack-function-button"),
+            PrimHighlight::Noadic => code_font!("noadic-function-button"),
+            PrimHighlight::Monadic => code_font!("monadic-function-button"),
+            PrimHighlight::Dyadic => code_font!("dyadic-function-button"),
+            PrimHighlight::Triadic => code_font!("triadic-function-button"),
+            PrimHighlight::Variadic => code_font!("variadic-function-button"),
+            PrimHighlight::Modifier(1) => code_font!("modifier1-button"),
+            PrimHighlight::Modifier(_) => code_font!("modifier2-button"),
+        },
     }
 }
 
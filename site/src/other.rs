@@ -128,6 +128,7 @@ pub fn Install() -> impl IntoView {
         <p>"Use "<code>"uiua run"</code>" to format and run a file without watching it."</p>
         <p>"Use "<code>"uiua fmt"</code>" to format a file without running it."</p>
         <p>"Use "<code>"uiua test"</code>" to run tests."</p>
+        <p>"Running "<code>"uiua <PATH>"</code>" runs a file without formatting it. This means a file that starts with a line like "<code>"#!/usr/bin/env uiua"</code>" can be made executable and run as a script. Errors are printed to stderr, and the exit code is 1 if there was an error."</p>
 
         <h2>"Font"</h2>
         <p>"The Uiua glyphs were chosen to be compatible specifically with "<a href="https://dejavu-fonts.github.io/Download.html">"DejaVu Sans Mono"</a>". It is recommended that you install this font to get the best experience with Uiua."</p>
@@ -430,6 +430,8 @@ fn TutorialTypes() -> impl IntoView {
         <Editor example="[@u @i @u @a]"/>
         <p>"Characters like newline or null need to be escaped with "<code>"\\"</code>", but a space does not."</p>
         <Editor example="[@\\r @\\0 @ ]"/>
+        <p>"Any Unicode codepoint can be written with "<code>"\\u{...}"</code>" and its hexadecimal value."</p>
+        <Editor example="[@\\u{41} @\\u{263A}]"/>
         <p>"String literals, delimited by "<code>"\""</code>"s, create rank 1 character arrays."</p>
         <Editor example="△.\"Hello, World!\""/>
         <p>"You can make strings span multiple lines with a "<code>"$"</code>" followed by a space on each line."</p>
@@ -438,6 +440,8 @@ fn TutorialTypes() -> impl IntoView {
         <Editor example="&p $ Hello, \n   $ World!"/>
         <p>"This style of string is also useful when your string contains a lot of quotes that you don't want to escape."</p>
         <Editor example="$ An then she was like, \"No way!\"\n$ And I was like, \"Way...\""/>
+        <p>"Lines that start with "<code>"$$"</code>" instead are raw, so escapes are not processed. This is useful for things like file paths and regular expressions."</p>
+        <Editor example="&p $$ C:\\Users\\Me\\file.txt"/>
         <br/>
 
         <h2 id="character-arithmetic">"Character Arithmetic"</h2>
@@ -505,6 +509,8 @@ fn TutorialBindings() -> impl IntoView {
         <p>"If the code on the right side of the "<code>"←"</code>" looks like a function, then instead of evaluating its right side immediately, the right side will be bound as a function."</p>
         <p>"This is how you make named functions in Uiua."</p>
         <Editor example="TimesThree ← ×3\nTimesThree 7"/>
+        <p>"A right side that takes no arguments is evaluated immediately. To bind it as a function instead, so that it runs each time it is used, give it a signature."</p>
+        <Editor example="Roll ← |0.1 ⁅×6⚂\n[Roll Roll Roll]"/>
         <Editor example="👋 ← ⊂\"Hello, \"\n👋 \"World!\""/>
         <Editor example="⍨ ← ∶\n⍪ ← ⊂\n⍳ ← ⇡\n⍪⍨⍳3⍳5 # Truly an abomination"/>
         <p>"The "<A href="/docs/functions">"next section"</A>" discusses functions in more detail."</p>
@@ -547,10 +553,22 @@ X ← (
   ↥⇌.
 )
 X 5"/>
-
-        <h2 id="local-bindings">"A Note on Local Bindings"</h2>
-        <p>"Bindings in Uiua can "<em>"only"</em>" be global. There is no way to give a name to a value within an inline function. A "<code>"←"</code>" inside "<code>"()"</code>"s is a syntax error."</p>
-        <p>"This is a deliberate design decision. It forces you to write tacit code, a.k.a. code with functions that do not mention their arguments. Uiua is designed to make writing tacit code as workable as possible."</p>
+        <p>"This is how to write a long binding or modifier function across lines. Otherwise, a line always ends the code on it. A modifier whose line ends before its functions gets a warning. A "<code>"("</code>", "<code>"["</code>", or "<code>"{"</code>" that is never closed is an error at the place it was opened."</p>
+
+        <h2 id="placeholders">"Placeholders"</h2>
+        <p>"In an inline function, "<code>"^1"</code>", "<code>"^2"</code>", and so on refer to the function's arguments by position. "<code>"^1"</code>" is the value that was on top of the stack when the function was called. The arguments are taken off the stack when the function starts, and a function with placeholders takes as many arguments as its largest placeholder."</p>
+        <p>"This makes it easy to use an argument more than once, even in places that are far apart."</p>
+        <Editor example="F ← (+^1 ×^2 ^1)\nF 2 3"/>
+        <p>"Inner inline functions have their own placeholders, so a "<code>"^1"</code>" inside one refers to that function's first argument."</p>
+
+        <h2 id="local-bindings">"Local Bindings"</h2>
+        <p>"Bindings at the top level of a program are global. Inside a multiline function, a line can start with "<code>"name ←"</code>" to bind the top value of the stack to a local name. The rest of the line runs first, so the name is bound to its result."</p>
+        <Editor example="Hyp ← (|2\n  a ← ×.\n  b ← ×.\n  √+a b\n)\nHyp 3 4"/>
+        <p>"A local name can be used in the rest of the function, and it shadows any global binding with the same name. If there is nothing after the "<code>"←"</code>", the name is bound to one of the function's arguments."</p>
+        <Editor example="Mean ← (|1\n  xs ←\n  ÷⧻xs /+xs\n)\nMean [1 2 3 4]"/>
+        <p>"Local bindings belong to the function they are made in. Functions nested inside it cannot see them, and using one there is an error."</p>
+        <Editor example="F ← (|1\n  x ←\n  ∵(+x) [1 2 3]\n)\nF 10"/> // Should fail
+        <p>"Tacit code, a.k.a. code with functions that do not mention their arguments, is still the most natural way to write Uiua. Local bindings are for the cases where juggling values on the stack would make a function harder to read."</p>
 
         <h2 id="format-strings">"Format Strings"</h2>
         <p>"Prefixing a string with "<code>"$"</code>", creates a format string. A format string is a function that is called immediately. It takes an argument for each "<code>"_"</code>" in the string and replaces it with the stringified version."</p>
@@ -596,7 +614,7 @@ fn TutorialAdvancedStack() -> impl IntoView {
     use Primitive::*;
     view! {
         <h1>"Advanced Stack Manipulation"</h1>
-        <p>"Uiua does not have local variables. With only "<Prim prim=Dup/>", "<Prim prim=Flip/>", and "<Prim prim=Over/>", how do you work with more than 2 values at a time?"</p>
+        <p>"Outside of "<A href="/docs/functions#local-bindings">"local bindings"</A>" in multiline functions, Uiua does not have local variables. With only "<Prim prim=Dup/>", "<Prim prim=Flip/>", and "<Prim prim=Over/>", how do you work with more than 2 values at a time?"</p>
 
         <h2 id="fork"><Prim prim=Fork/></h2>
         <p><Prim prim=Fork/>" is a dyadic modifier that takes 2 functions and calls them both on the same set of arguments. The number of arguments used is the maximum of the two functions."</p>
@@ -459,6 +459,9 @@ impl<T: ArrayValue> Array<T> {
 impl Value {
     pub fn reshape(&mut self, shape: &Self, env: &Uiua) -> UiuaResult {
         if let Ok(n) = shape.as_nat(env, "") {
+            let mut new_shape = Shape::from(self.shape());
+            new_shape.insert(0, n);
+            env.validate_element_count(&new_shape, "Reshape")?;
             match self {
                 Value::Num(a) => a.reshape_scalar(n),
                 Value::Byte(a) => a.reshape_scalar(n),
@@ -569,6 +572,7 @@ impl<T: ArrayValue> Array<T> {
                 return Err(env.error(format!("Cannot reshape array with {n} negative dimensions")))
             }
         };
+        env.validate_element_count(&shape, "Reshape")?;
         let target_len: usize = shape.iter().product();
         self.shape = shape;
         if self.data.len() < target_len {
@@ -1580,6 +1584,7 @@ impl<T: ArrayValue> Array<T> {
         );
         new_shape.extend_from_slice(size_spec);
         new_shape.extend_from_slice(&self.shape[size_spec.len()..]);
+        env.validate_element_count(&new_shape, "Windows")?;
         // Check if the window size is too large
         for (size, sh) in size_spec.iter().zip(&self.shape) {
             if *size > *sh {
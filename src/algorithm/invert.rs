@@ -7,6 +7,7 @@ use crate::{
     function::{Function, Instr, TempKind},
     primitive::Primitive,
     value::Value,
+    SysOp,
 };
 
 impl Function {
@@ -26,6 +27,7 @@ impl Function {
 }
 
 pub(crate) fn invert_instrs(instrs: &[Instr]) -> Option<Vec<Instr>> {
+    let instrs = &Instr::unfuse(instrs);
     if instrs.is_empty() {
         return Some(Vec::new());
     }
@@ -128,6 +130,7 @@ fn invert_instr_fragment(mut instrs: &[Instr]) -> Option<Vec<Instr>> {
 type Under = (Vec<Instr>, Vec<Instr>);
 
 pub(crate) fn under_instrs(instrs: &[Instr]) -> Option<Under> {
+    let instrs = &Instr::unfuse(instrs);
     if instrs.is_empty() {
         return Some((Vec::new(), Vec::new()));
     }
@@ -255,6 +258,16 @@ fn under_instrs_impl(instrs: &[Instr]) -> Option<(Vec<Instr>, Vec<Instr>)> {
             [Now.i(), PushTempN(1).i()],
             [PopTempN(1).i(), Now.i(), Flip.i(), Sub.i()],
         ),
+        &(
+            [Sys(SysOp::Monotonic)],
+            [Sys(SysOp::Monotonic).i(), PushTempN(1).i()],
+            [
+                PopTempN(1).i(),
+                Sys(SysOp::Monotonic).i(),
+                Flip.i(),
+                Sub.i(),
+            ],
+        ),
     ];
 
     let mut befores = Vec::new();
@@ -353,21 +353,24 @@ pub fn each(env: &mut Uiua) -> UiuaResult {
 }
 
 fn each1_1(f: Value, xs: Value, env: &mut Uiua) -> UiuaResult {
-    let mut new_values = Vec::with_capacity(xs.flat_len());
+    // Results are appended to the builder as they are produced so that
+    // each iteration's value can be freed before the next one is created.
+    // The values themselves still come from the allocator.
+    let mut new_values = Value::builder(xs.flat_len());
     let mut new_shape = Shape::from(xs.shape());
     let mut old_values = xs.into_flat_values();
     for val in old_values.by_ref() {
         env.push(val);
         let broke = env.call_catch_break(f.clone())?;
-        new_values.push(env.pop("each's function result")?);
+        new_values.add_row(env.pop("each's function result")?, &env)?;
         if broke {
             for row in old_values {
-                new_values.push(row);
+                new_values.add_row(row, &env)?;
             }
             break;
         }
     }
-    let mut eached = Value::from_row_values(new_values, env)?;
+    let mut eached = new_values.finish();
     new_shape.extend_from_slice(&eached.shape()[1..]);
     *eached.shape_mut() = new_shape;
     env.push(eached);
@@ -725,6 +728,9 @@ pub fn table(env: &mut Uiua) -> UiuaResult {
     let f = env.pop(FunctionArg(1))?;
     let xs = env.pop(ArrayArg(1))?;
     let ys = env.pop(ArrayArg(2))?;
+    let mut new_shape = Shape::from(xs.shape());
+    new_shape.extend_from_slice(ys.shape());
+    env.validate_element_count(&new_shape, "Table")?;
     match (f.as_flipped_primitive(), xs, ys) {
         (Some((prim, flipped)), Value::Num(xs), Value::Num(ys)) => {
             if let Err((xs, ys)) = table_nums(prim, flipped, xs, ys, env) {
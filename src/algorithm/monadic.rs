@@ -30,6 +30,96 @@ impl Value {
     }
 }
 
+/// A text encoding used by [`Primitive::Encode`](crate::primitive::Primitive::Encode)
+/// and [`Primitive::Decode`](crate::primitive::Primitive::Decode)
+#[derive(Debug, Clone, Copy)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+        }
+    }
+}
+
+/// Parse an encoding name, optionally followed by `lossy`
+fn parse_encoding(spec: &str, env: &Uiua) -> UiuaResult<(Encoding, bool)> {
+    let mut encoding = None;
+    let mut lossy = false;
+    for word in spec.split_whitespace() {
+        match word.to_lowercase().replace(['-', '_'], "").as_str() {
+            "utf8" => encoding = Some(Encoding::Utf8),
+            "utf16le" => encoding = Some(Encoding::Utf16Le),
+            "utf16" | "utf16be" => encoding = Some(Encoding::Utf16Be),
+            "lossy" => lossy = true,
+            _ => {
+                return Err(env.error(format!(
+                    "Unknown encoding {word:?}. Supported encodings are \
+                    utf-8, utf-16, utf-16le, and utf-16be"
+                )))
+            }
+        }
+    }
+    let encoding = encoding
+        .ok_or_else(|| env.error(format!("Encoding {spec:?} does not name an encoding")))?;
+    Ok((encoding, lossy))
+}
+
+impl Value {
+    pub fn encode(&self, encoding: &str, env: &Uiua) -> UiuaResult<Array<u8>> {
+        let (encoding, _) = parse_encoding(encoding, env)?;
+        let s = self.as_string(env, "Encoded array must be a string")?;
+        Ok(match encoding {
+            Encoding::Utf8 => s.into_bytes().into(),
+            Encoding::Utf16Le => s.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+            Encoding::Utf16Be => s.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        })
+    }
+    pub fn decode(self, encoding: &str, env: &Uiua) -> UiuaResult<Self> {
+        let (encoding, lossy) = parse_encoding(encoding, env)?;
+        let bytes = self.into_bytes(env, "Decoded array must be a list of bytes")?;
+        let invalid = |e: &dyn std::fmt::Display| {
+            env.error(format!("Bytes are not valid {}: {e}", encoding.name()))
+        };
+        let s = match encoding {
+            Encoding::Utf8 if lossy => String::from_utf8_lossy(&bytes).into_owned(),
+            Encoding::Utf8 => String::from_utf8(bytes).map_err(|e| invalid(&e))?,
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                let chunks = bytes.chunks_exact(2);
+                let odd = !chunks.remainder().is_empty();
+                let units = chunks.map(|pair| match encoding {
+                    Encoding::Utf16Le => u16::from_le_bytes([pair[0], pair[1]]),
+                    _ => u16::from_be_bytes([pair[0], pair[1]]),
+                });
+                let mut s = String::with_capacity(bytes.len() / 2);
+                for c in char::decode_utf16(units) {
+                    match c {
+                        Ok(c) => s.push(c),
+                        Err(_) if lossy => s.push(char::REPLACEMENT_CHARACTER),
+                        Err(e) => return Err(invalid(&e)),
+                    }
+                }
+                if odd {
+                    if lossy {
+                        s.push(char::REPLACEMENT_CHARACTER);
+                    } else {
+                        return Err(invalid(&"odd number of bytes"));
+                    }
+                }
+                s
+            }
+        };
+        Ok(s.into())
+    }
+}
+
 impl<T: ArrayValue> Array<T> {
     pub fn deshape(&mut self) {
         self.shape = tiny_vec![self.flat_len()];
@@ -59,20 +149,12 @@ fn range(shape: &[usize], env: &Uiua) -> UiuaResult<Vec<f64>> {
     if shape.contains(&0) {
         return Ok(Vec::new());
     }
-    let mut len = shape.len();
-    for &item in shape {
-        let (new, overflow) = len.overflowing_mul(item);
-        if overflow {
-            let len = shape.len() as f64 * shape.iter().map(|d| *d as f64).product::<f64>();
-            return Err(env.error(format!(
-                "Attempting to make a range from shape {} would \
-                create an array with {} elements, which is too large",
-                FormatShape(shape),
-                len
-            )));
-        }
-        len = new;
+    let mut full_shape = Shape::from(shape);
+    if shape.len() > 1 {
+        full_shape.push(shape.len());
     }
+    env.validate_element_count(&full_shape, "Range")?;
+    let len = shape.len() * shape.iter().product::<usize>();
     let mut data: Vec<f64> = Vec::with_capacity(len);
     let mut curr = vec![0; shape.len()];
     loop {
@@ -268,6 +268,9 @@ impl<T: ArrayValue + ArrayCmp<U>, U: ArrayValue> PartialEq<Array<U>> for Array<T
         if self.shape() != other.shape() {
             return false;
         }
+        if self.data.ptr_eq(&other.data) {
+            return true;
+        }
         self.data
             .iter()
             .zip(&other.data)
@@ -15,6 +15,51 @@ pub enum Item {
     ExtraNewlines(CodeSpan),
 }
 
+impl Item {
+    /// Get the span of the code the item was parsed from
+    ///
+    /// Scoped items do not include their delimiters.
+    pub fn span(&self) -> Option<CodeSpan> {
+        match self {
+            Item::Scoped { items, .. } => {
+                items.iter().filter_map(Item::span).reduce(CodeSpan::merge)
+            }
+            Item::Words(words) => words_span(words),
+            Item::Binding(binding) => Some(binding.span()),
+            Item::ExtraNewlines(span) => Some(span.clone()),
+        }
+    }
+    /// Get the text of an item that is only a comment
+    ///
+    /// A `#!` line at the start of a file is not counted, so it does not become documentation.
+    pub fn comment(&self) -> Option<&str> {
+        match self {
+            Item::Words(words) => match words.as_slice() {
+                [Sp {
+                    value: Word::Comment(comment),
+                    span,
+                }] if !(span.start.byte_pos == 0 && comment.starts_with('!')) => Some(comment),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Add a line of a comment to the documentation of the binding that follows it
+///
+/// Lines are joined with spaces, and blank lines separate paragraphs.
+pub fn push_doc_line(doc: &mut String, line: &str) {
+    if !doc.is_empty() {
+        if line.trim().is_empty() {
+            doc.push_str("\n\n");
+        } else if !doc.ends_with('\n') {
+            doc.push(' ');
+        }
+    }
+    doc.push_str(line.trim());
+}
+
 #[derive(Debug, Clone)]
 pub struct Binding {
     pub name: Sp<Ident>,
@@ -22,6 +67,26 @@ pub struct Binding {
     pub words: Vec<Sp<Word>>,
 }
 
+impl Binding {
+    /// Get the span of the whole binding
+    pub fn span(&self) -> CodeSpan {
+        let mut span = self.name.span.clone();
+        if let Some(sig) = &self.signature {
+            span = span.merge(sig.span.clone());
+        }
+        if let Some(words) = words_span(&self.words) {
+            span = span.merge(words);
+        }
+        span
+    }
+}
+
+/// Get the span that covers some words
+pub fn words_span(words: &[Sp<Word>]) -> Option<CodeSpan> {
+    let first = words.first()?.span.clone();
+    Some(first.merge(words.last()?.span.clone()))
+}
+
 #[derive(Clone)]
 pub enum Word {
     Number(String, f64),
@@ -30,6 +95,8 @@ pub enum Word {
     FormatString(Vec<String>),
     MultilineString(Vec<Sp<Vec<String>>>),
     Ident(Ident),
+    BindLocal(Ident),
+    Placeholder(usize),
     Strand(Vec<Sp<Word>>),
     Array(Arr),
     Func(Func),
@@ -66,6 +133,8 @@ impl fmt::Debug for Word {
                 Ok(())
             }
             Word::Ident(ident) => write!(f, "ident({ident})"),
+            Word::BindLocal(ident) => write!(f, "bind({ident})"),
+            Word::Placeholder(n) => write!(f, "^{n}"),
             Word::Array(arr) => arr.fmt(f),
             Word::Strand(items) => write!(f, "strand({items:?})"),
             Word::Func(func) => func.fmt(f),
@@ -122,6 +191,16 @@ pub struct Modified {
     pub terminated: bool,
 }
 
+impl Modified {
+    /// Get the span of the modifier and its operands
+    pub fn span(&self) -> CodeSpan {
+        match words_span(&self.operands) {
+            Some(operands) => self.modifier.span.clone().merge(operands),
+            None => self.modifier.span.clone(),
+        }
+    }
+}
+
 impl fmt::Debug for Modified {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self.modifier.value)?;
@@ -134,3 +213,129 @@ impl fmt::Debug for Modified {
         Ok(())
     }
 }
+
+/// A way to walk a syntax tree
+///
+/// Each method's default implementation visits the node's children,
+/// so implementors only need to override the methods for the nodes they care about.
+/// An overriding method can call the matching `walk_*` function to keep walking.
+pub trait Visit {
+    fn visit_item(&mut self, item: &Item) {
+        walk_item(self, item)
+    }
+    fn visit_binding(&mut self, binding: &Binding) {
+        walk_binding(self, binding)
+    }
+    fn visit_word(&mut self, word: &Sp<Word>) {
+        walk_word(self, word)
+    }
+}
+
+/// Visit the children of an item
+pub fn walk_item<V: Visit + ?Sized>(visitor: &mut V, item: &Item) {
+    match item {
+        Item::Scoped { items, .. } => {
+            for item in items {
+                visitor.visit_item(item);
+            }
+        }
+        Item::Words(words) => {
+            for word in words {
+                visitor.visit_word(word);
+            }
+        }
+        Item::Binding(binding) => visitor.visit_binding(binding),
+        Item::ExtraNewlines(_) => {}
+    }
+}
+
+/// Visit the words of a binding
+pub fn walk_binding<V: Visit + ?Sized>(visitor: &mut V, binding: &Binding) {
+    for word in &binding.words {
+        visitor.visit_word(word);
+    }
+}
+
+/// Visit the words inside a word
+pub fn walk_word<V: Visit + ?Sized>(visitor: &mut V, word: &Sp<Word>) {
+    match &word.value {
+        Word::Strand(items) => {
+            for item in items {
+                visitor.visit_word(item);
+            }
+        }
+        Word::Array(Arr { lines, .. }) | Word::Func(Func { lines, .. }) => {
+            for word in lines.iter().flatten() {
+                visitor.visit_word(word);
+            }
+        }
+        Word::Modified(modified) => {
+            for operand in &modified.operands {
+                visitor.visit_word(operand);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// A way to walk and change a syntax tree
+///
+/// This is like [`Visit`], but nodes can be changed as they are visited.
+pub trait VisitMut {
+    fn visit_item_mut(&mut self, item: &mut Item) {
+        walk_item_mut(self, item)
+    }
+    fn visit_binding_mut(&mut self, binding: &mut Binding) {
+        walk_binding_mut(self, binding)
+    }
+    fn visit_word_mut(&mut self, word: &mut Sp<Word>) {
+        walk_word_mut(self, word)
+    }
+}
+
+/// Visit the children of an item mutably
+pub fn walk_item_mut<V: VisitMut + ?Sized>(visitor: &mut V, item: &mut Item) {
+    match item {
+        Item::Scoped { items, .. } => {
+            for item in items {
+                visitor.visit_item_mut(item);
+            }
+        }
+        Item::Words(words) => {
+            for word in words {
+                visitor.visit_word_mut(word);
+            }
+        }
+        Item::Binding(binding) => visitor.visit_binding_mut(binding),
+        Item::ExtraNewlines(_) => {}
+    }
+}
+
+/// Visit the words of a binding mutably
+pub fn walk_binding_mut<V: VisitMut + ?Sized>(visitor: &mut V, binding: &mut Binding) {
+    for word in &mut binding.words {
+        visitor.visit_word_mut(word);
+    }
+}
+
+/// Visit the words inside a word mutably
+pub fn walk_word_mut<V: VisitMut + ?Sized>(visitor: &mut V, word: &mut Sp<Word>) {
+    match &mut word.value {
+        Word::Strand(items) => {
+            for item in items {
+                visitor.visit_word_mut(item);
+            }
+        }
+        Word::Array(Arr { lines, .. }) | Word::Func(Func { lines, .. }) => {
+            for word in lines.iter_mut().flatten() {
+                visitor.visit_word_mut(word);
+            }
+        }
+        Word::Modified(modified) => {
+            for operand in &mut modified.operands {
+                visitor.visit_word_mut(operand);
+            }
+        }
+        _ => {}
+    }
+}
@@ -31,6 +31,25 @@ pub(crate) fn instrs_signature(instrs: &[Instr]) -> Result<Signature, String> {
     Ok(Signature { args, outputs })
 }
 
+/// Check that a modifier's function has a signature that the modifier can work with
+///
+/// Only signatures that would always cause an error at runtime are reported.
+pub(crate) fn check_modifier_function(prim: Primitive, sig: Signature) -> Result<(), String> {
+    use Primitive::*;
+    match prim {
+        Each | Rows if sig.outputs > 1 => Err(format!(
+            "{prim:?}'s function must return 0 or 1 values, but its signature is {sig}"
+        )),
+        Table | Cross | Scan | Distribute if sig.outputs != 1 => Err(format!(
+            "{prim:?}'s function must return 1 value, but its signature is {sig}"
+        )),
+        Distribute if sig.args == 0 => Err(format!(
+            "{prim:?}'s function must take at least 1 argument, but its signature is {sig}"
+        )),
+        _ => Ok(()),
+    }
+}
+
 /// An environment that emulates the runtime but only keeps track of the stack.
 struct VirtualEnv<'a> {
     stack: Vec<BasicValue<'a>>,
@@ -118,8 +137,20 @@ impl<'a> VirtualEnv<'a> {
             Instr::PopTemp { count, .. } | Instr::CopyTemp { count, .. } => {
                 self.handle_args_outputs(0, *count)?
             }
+            Instr::CallGlobal { sig, .. } => self.handle_sig(*sig)?,
             Instr::Dynamic(f) => self.handle_sig(f.signature)?,
+            Instr::BindLocal { .. } => self.handle_args_outputs(1, 0)?,
+            Instr::PushLocal { .. } => self.handle_args_outputs(0, 1)?,
             Instr::DropTemp { .. } => {}
+            Instr::Super { first, prim, .. } => {
+                self.instr(first)?;
+                let args = prim
+                    .args()
+                    .ok_or("superinstruction with indeterminate args")?;
+                let outputs =
+                    (prim.outputs()).ok_or("superinstruction with indeterminate outputs")?;
+                self.handle_args_outputs(args as usize, outputs as usize)?;
+            }
             Instr::Prim(prim, _) => match prim {
                 Reduce | Scan => {
                     let f = self.pop()?;
@@ -1,6 +1,8 @@
 use std::{
-    collections::hash_map::DefaultHasher,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
     hash::{Hash, Hasher},
+    iter::once,
+    mem::replace,
     sync::Arc,
 };
 
@@ -8,10 +10,10 @@ use crate::{
     algorithm::invert::under_instrs,
     array::Array,
     ast::*,
-    check::instrs_signature,
+    check::{check_modifier_function, instrs_signature},
     function::*,
     lex::{CodeSpan, Sp, Span},
-    primitive::Primitive,
+    primitive::{Primitive, CONSTANTS},
     run::RunMode,
     value::Value,
     Diagnostic, DiagnosticKind, Ident, SysOp, UiuaError, UiuaResult,
@@ -19,13 +21,90 @@ use crate::{
 
 use crate::Uiua;
 
+/// A binding whose body is currently being compiled
+#[derive(Clone)]
+pub(crate) struct CurrentBinding {
+    name: Ident,
+    /// The global slot reserved for the binding
+    global_index: usize,
+    signature: Option<Signature>,
+    /// Whether the body refers to the binding
+    referenced: bool,
+}
+
+/// A constant in a runtime's constant pool
+///
+/// Unlike with [`Value`]'s own equality, numbers and bytes are never equal and numbers
+/// must have identical bits, so a constant is only ever replaced by an indistinguishable one.
+#[derive(Clone)]
+pub(crate) struct PooledConstant(Value);
+
+impl PartialEq for PooledConstant {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Value::Num(a), Value::Num(b)) => {
+                a.shape == b.shape
+                    && (a.data.iter().zip(&b.data)).all(|(a, b)| a.to_bits() == b.to_bits())
+            }
+            (Value::Byte(a), Value::Byte(b)) => a == b,
+            (Value::Char(a), Value::Char(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for PooledConstant {}
+
+impl Hash for PooledConstant {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
 impl Uiua {
+    /// Compile and run items
+    ///
+    /// Errors are collected in `load_errors` rather than returned.
+    /// After an error, the remaining items are only compiled so that any other compile errors are found.
+    ///
+    /// Comments directly above a binding become its documentation.
     pub(crate) fn items(&mut self, items: Vec<Item>, in_test: bool) -> UiuaResult {
+        let mut doc: Option<String> = None;
         for item in items {
-            self.item(item, in_test)?;
+            match &item {
+                Item::Words(_) if item.comment().is_some() => {
+                    let comment = item.comment().unwrap();
+                    push_doc_line(doc.get_or_insert_with(String::new), comment);
+                    continue;
+                }
+                Item::ExtraNewlines(_) => continue,
+                _ => {}
+            }
+            let doc = doc.take();
+            let name = match &item {
+                Item::Binding(binding) => Some(binding.name.value.clone()),
+                _ => None,
+            };
+            if let Err(e) = self.restore_on_error(|env| env.item(item, in_test)) {
+                self.load_errors.push(e);
+                self.compile_only = true;
+            }
+            if let Some((name, doc)) = name.zip(doc) {
+                if let Some(&index) = self.scope.names.get(&name) {
+                    self.binding_docs.insert(index, doc);
+                }
+            }
         }
         Ok(())
     }
+    /// Record a compile error
+    ///
+    /// The remaining items are only compiled, not run.
+    fn compile_error(&mut self, error: UiuaError) -> UiuaResult {
+        self.load_errors.push(error);
+        self.compile_only = true;
+        Ok(())
+    }
     fn item(&mut self, item: Item, in_test: bool) -> UiuaResult {
         fn words_have_import(words: &[Sp<Word>]) -> bool {
             words
@@ -67,8 +146,37 @@ impl Uiua {
                     RunMode::All => true,
                 };
                 if can_run || words_have_import(&words) || words_are_export(&words) {
-                    let instrs = self.compile_words(words, true)?;
-                    self.exec_global_instrs(instrs)?;
+                    let instrs = match self.compile_words(words, true) {
+                        Ok(instrs) => instrs,
+                        Err(e) => return self.compile_error(e),
+                    };
+                    if self.print_instrs {
+                        let sig = instrs_signature(&instrs).unwrap_or(Signature::new(0, 0));
+                        let f = Function::new(FunctionId::Main, instrs.clone(), sig);
+                        let listing = format!("{}\n", self.disassemble(&f));
+                        _ = self.backend.print_str_stderr(&listing);
+                    }
+                    if self.compile_only {
+                        return Ok(());
+                    }
+                    let stack_height = self.stack.len();
+                    let testing = in_test && self.mode == RunMode::Test;
+                    if testing {
+                        self.passed_asserts = Some(HashSet::new());
+                    }
+                    let res = self.restore_on_error(|env| env.exec_global_instrs(instrs));
+                    // Each assertion is counted once, even if it passed many times
+                    if let Some(passed) = self.passed_asserts.take() {
+                        self.test_results.passed += passed.len();
+                    }
+                    match res {
+                        // Failures in test scopes are recorded so that the rest of the tests can run
+                        Err(e) if testing && !e.is_uncatchable() => {
+                            self.stack.truncate(stack_height);
+                            self.test_results.failures.push(e);
+                        }
+                        res => res?,
+                    }
                 }
             }
             Item::Binding(binding) => {
@@ -84,6 +192,16 @@ impl Uiua {
         }
         Ok(())
     }
+    /// Get the span of the first instruction whose result varies each time it is run
+    fn varying_span(&self, instrs: &[Instr]) -> Option<Span> {
+        instrs.iter().find_map(|instr| match instr {
+            Instr::Prim(
+                Primitive::Rand | Primitive::Now | Primitive::Sys(SysOp::Monotonic),
+                span,
+            ) => Some(self.spans.lock()[*span].clone()),
+            _ => None,
+        })
+    }
     fn add_span(&mut self, span: impl Into<Span>) -> usize {
         let mut spans = self.spans.lock();
         let idx = spans.len();
@@ -91,7 +209,31 @@ impl Uiua {
         idx
     }
     fn binding(&mut self, binding: Binding) -> UiuaResult {
-        let instrs = self.compile_words(binding.words, true)?;
+        // Reserve a global slot so that the body can refer to the binding
+        let global_index = {
+            let mut globals = self.globals.lock();
+            globals.push(Value::default());
+            globals.len() - 1
+        };
+        let outer_binding = self.current_binding.replace(CurrentBinding {
+            name: binding.name.value.clone(),
+            global_index,
+            signature: binding.signature.as_ref().map(|sig| sig.value),
+            referenced: false,
+        });
+        let instrs = self.compile_words(binding.words, true);
+        let current = replace(&mut self.current_binding, outer_binding).unwrap();
+        // If the binding fails to compile, its name is bound to a placeholder
+        // so that later uses of it do not cause more errors
+        let name = binding.name.value.clone();
+        let compile_error = |env: &mut Self, error: UiuaError| {
+            env.scope.names.entry(name.clone()).or_insert(global_index);
+            env.compile_error(error)
+        };
+        let instrs = match instrs {
+            Ok(instrs) => instrs,
+            Err(e) => return compile_error(self, e),
+        };
         let make_fn = |instrs: Vec<Instr>, sig: Signature| {
             let func = Function::new(FunctionId::Named(binding.name.value.clone()), instrs, sig);
             Value::from(func)
@@ -102,17 +244,43 @@ impl Uiua {
                     if declared_sig.value == sig {
                         sig = declared_sig.value;
                     } else {
-                        return Err(UiuaError::Run(Span::Code(declared_sig.span.clone()).sp(
-                            format!(
+                        let error =
+                            UiuaError::Run(Span::Code(declared_sig.span.clone()).sp(format!(
                                 "Function signature mismatch: \
                                  declared {} but inferred {}",
                                 declared_sig.value, sig
-                            ),
-                        )));
+                            )));
+                        return compile_error(self, error);
                     }
                 }
 
-                if sig.args == 0 {
+                // A binding with no arguments is evaluated immediately
+                // unless it has an explicit signature
+                if sig.args == 0 && binding.signature.is_none() {
+                    if let Some(span) = self.varying_span(&instrs) {
+                        self.diagnostics.insert(Diagnostic::new(
+                            format!(
+                                "`{name}` takes no arguments, so it is evaluated once now \
+                                rather than each time it is used. To bind it as a function, \
+                                give it a signature like `{name} ← |0.{} ...`",
+                                sig.outputs
+                            ),
+                            span,
+                            DiagnosticKind::Advice,
+                        ));
+                    }
+                    if current.referenced {
+                        let error =
+                            UiuaError::Run(Span::Code(binding.name.span.clone()).sp(format!(
+                                "`{}` refers to itself, so it must take at least one argument",
+                                binding.name.value
+                            )));
+                        return compile_error(self, error);
+                    }
+                    if self.compile_only {
+                        self.scope.names.insert(binding.name.value, global_index);
+                        return Ok(());
+                    }
                     self.exec_global_instrs(instrs)?;
                     if let Some(value) = self.stack.pop() {
                         match value {
@@ -142,26 +310,36 @@ impl Uiua {
                 if let Some(sig) = binding.signature {
                     make_fn(instrs, sig.value)
                 } else {
-                    return Err(UiuaError::Run(
+                    let error = UiuaError::Run(
                         Span::Code(binding.name.span.clone())
                             .sp(format!("Cannot infer function signature: {e}")),
-                    ));
+                    );
+                    return compile_error(self, error);
                 }
             }
         };
         val.compress();
-        let mut globals = self.globals.lock();
-        let idx = globals.len();
-        globals.push(val);
-        self.scope.names.insert(binding.name.value, idx);
+        if self.print_instrs {
+            if let Some(f) = val.as_function() {
+                let listing = format!("{}\n", self.disassemble(f));
+                _ = self.backend.print_str_stderr(&listing);
+            }
+        }
+        self.globals.lock()[global_index] = val;
+        self.scope.names.insert(binding.name.value, global_index);
         Ok(())
     }
     fn compile_words(&mut self, words: Vec<Sp<Word>>, call: bool) -> UiuaResult<Vec<Instr>> {
+        let height = self.new_functions.len();
         self.new_functions.push(Vec::new());
-        self.words(words, call)?;
+        if let Err(e) = self.words(words, call) {
+            self.new_functions.truncate(height);
+            return Err(e);
+        }
         if self.print_diagnostics {
             for diagnostic in self.take_diagnostics() {
-                eprintln!("{}", diagnostic.show(true));
+                let diagnostic = format!("{}\n", diagnostic.show(self.pretty_stderr()));
+                _ = self.backend.print_str_stderr(&diagnostic);
             }
         }
         let instrs = self.new_functions.pop().unwrap();
@@ -177,7 +355,7 @@ impl Uiua {
         if let [Instr::Push(val)] = instrs.as_slice() {
             if let Some(f) = val.as_function() {
                 sig = Some(f.signature());
-                instrs = f.instrs.clone();
+                instrs = Instr::unfuse(&f.instrs);
             }
         }
         let sig = if let Some(sig) = sig {
@@ -193,12 +371,39 @@ impl Uiua {
         }
         Ok(())
     }
+    /// Get a constant that shares its data with an identical constant compiled earlier
+    ///
+    /// Functions are never pooled.
+    fn pool_constant(&mut self, val: Value) -> Value {
+        if let Value::Func(_) = val {
+            return val;
+        }
+        let constant = PooledConstant(val);
+        if let Some(pooled) = self.constants.get(&constant) {
+            return pooled.0.clone();
+        }
+        let val = constant.0.clone();
+        self.constants.insert(constant);
+        val
+    }
     /// Push an instruction to the current function being compiled
     ///
     /// Also performs some optimizations if the instruction and the previous
     /// instruction form some known pattern
     fn push_instr(&mut self, instr: Instr) {
         use Primitive::*;
+        let instr = match instr {
+            Instr::Prim(prim, span) => match self.fold_constant(prim) {
+                Some(val) => Instr::push(val),
+                None => Instr::Prim(prim, span),
+            },
+            instr => instr,
+        };
+        let instr = match instr {
+            Instr::Push(val) => Instr::push(self.pool_constant(*val)),
+            Instr::Prim(Each, span) if self.fuse_each(span) => return,
+            instr => instr,
+        };
         let instrs = self.new_functions.last_mut().unwrap();
         // Optimizations
         match (instrs.as_mut_slice(), instr) {
@@ -218,11 +423,87 @@ impl Uiua {
                     DiagnosticKind::Style,
                 ));
             }
+            // Pushing a value and immediately popping it does nothing
+            ([.., Instr::Push(_)], Instr::Prim(Pop, _)) => {
+                instrs.pop();
+            }
             // First reverse = last
             ([.., Instr::Prim(top @ Reverse, _)], Instr::Prim(First, _)) => *top = Last,
             (_, instr) => instrs.push(instr),
         }
     }
+    /// Fuse `each G each F` into `each (F G)` so that no intermediate array is created
+    ///
+    /// This is only done when `F` is known to return scalars.
+    /// Otherwise, `G` would be called on the elements of `F`'s results rather than on the results themselves.
+    ///
+    /// Returns whether the instructions were fused.
+    /// The fused function calls `F` and `G` rather than inlining them so that
+    /// [`recur`](Primitive::Recur) and error traces are unaffected.
+    fn fuse_each(&mut self, g_span: usize) -> bool {
+        let instrs = self.new_functions.last().unwrap();
+        let [.., Instr::Push(f), Instr::Prim(Primitive::Each, f_span), Instr::Push(g)] =
+            instrs.as_slice()
+        else {
+            return false;
+        };
+        let (Some(f), Some(g)) = (f.as_function(), g.as_function()) else {
+            return false;
+        };
+        let unary = Signature::new(1, 1);
+        if f.signature() != unary || g.signature() != unary || !returns_scalars(f) {
+            return false;
+        }
+        let Span::Code(span) = self.merge_spans(*f_span, g_span) else {
+            return false;
+        };
+        let (f, g, f_span) = (f.clone(), g.clone(), *f_span);
+        let instrs = vec![
+            Instr::push(f),
+            Instr::Call(f_span),
+            Instr::push(g),
+            Instr::Call(g_span),
+        ];
+        let fused = Function::new(FunctionId::Anonymous(span), instrs, unary);
+        let instrs = self.new_functions.last_mut().unwrap();
+        instrs.truncate(instrs.len() - 3);
+        instrs.push(Instr::push(fused));
+        instrs.push(Instr::Prim(Primitive::Each, g_span));
+        true
+    }
+    /// Evaluate a pure primitive at compile time if all of its arguments are constants
+    ///
+    /// The argument instructions are removed if evaluation succeeds.
+    /// If evaluation fails, nothing is changed so that the error is reported at runtime.
+    fn fold_constant(&mut self, prim: Primitive) -> Option<Value> {
+        use Primitive::*;
+        if !(prim.class().is_pervasive() || matches!(prim, Range | Len | Shape | Deshape)) {
+            return None;
+        }
+        let args = prim.args()? as usize;
+        let instrs = self.new_functions.last().unwrap();
+        if args == 0 || prim.outputs() != Some(1) || instrs.len() < args {
+            return None;
+        }
+        let arg_instrs = &instrs[instrs.len() - args..];
+        let mut values = Vec::with_capacity(args);
+        for instr in arg_instrs {
+            match instr {
+                Instr::Push(val) if !matches!(**val, Value::Func(_)) => {
+                    values.push((**val).clone())
+                }
+                _ => return None,
+            }
+        }
+        let stack_height = self.stack.len();
+        self.stack.extend(values);
+        let res = prim.run(self).and_then(|_| self.pop("folded constant"));
+        self.stack.truncate(stack_height);
+        let val = res.ok()?;
+        let instrs = self.new_functions.last_mut().unwrap();
+        instrs.truncate(instrs.len() - args);
+        Some(val)
+    }
     fn word(&mut self, word: Sp<Word>, call: bool) -> UiuaResult {
         match word.value {
             Word::Number(_, n) => {
@@ -277,7 +558,7 @@ impl Uiua {
                             let mut i = 0;
                             for (j, line) in lines.iter().enumerate() {
                                 if j > 0 {
-                                    formatted.push_str("\r\n");
+                                    formatted.push('\n');
                                 }
                                 for (k, frag) in line.value.iter().enumerate() {
                                     if k > 0 {
@@ -302,6 +583,42 @@ impl Uiua {
                 }
             }
             Word::Ident(ident) => self.ident(ident, word.span, call)?,
+            Word::Placeholder(0) => {
+                return Err(word
+                    .span
+                    .sp("Placeholders start at `^1`, so `^0` is not valid".into())
+                    .into());
+            }
+            Word::Placeholder(n) => {
+                let name = placeholder_name(n);
+                let index = (self.local_names.last())
+                    .and_then(|names| names.iter().position(|local| *local == name));
+                let Some(index) = index else {
+                    return Err(word
+                        .span
+                        .sp("Placeholders can only be used in functions".into())
+                        .into());
+                };
+                let span = self.add_span(word.span);
+                self.push_instr(Instr::PushLocal { index, span });
+            }
+            Word::BindLocal(name) => {
+                let Some(names) = self.local_names.last_mut() else {
+                    return Err(word
+                        .span
+                        .sp("Local bindings can only be made in functions".into())
+                        .into());
+                };
+                let index = match names.iter().position(|n| *n == name) {
+                    Some(index) => index,
+                    None => {
+                        names.push(name);
+                        names.len() - 1
+                    }
+                };
+                let span = self.add_span(word.span);
+                self.push_instr(Instr::BindLocal { index, span });
+            }
             Word::Strand(items) => {
                 self.push_instr(Instr::BeginArray);
                 let inner = self.compile_words(items, false)?;
@@ -381,6 +698,22 @@ impl Uiua {
         Ok(())
     }
     fn ident(&mut self, ident: Ident, span: CodeSpan, call: bool) -> UiuaResult {
+        // Local bindings shadow global ones
+        if let Some(names) = self.local_names.last() {
+            if let Some(index) = names.iter().position(|n| *n == ident) {
+                let span = self.add_span(span);
+                self.push_instr(Instr::PushLocal { index, span });
+                return Ok(());
+            }
+        }
+        if (self.local_names.iter().rev().skip(1)).any(|names| names.contains(&ident)) {
+            return Err(span
+                .sp(format!(
+                    "`{ident}` is a local binding of an enclosing function, \
+                    so it cannot be used here"
+                ))
+                .into());
+        }
         if let Some(idx) = self.scope.names.get(&ident).or_else(|| {
             self.higher_scopes
                 .last()
@@ -391,21 +724,117 @@ impl Uiua {
             // Name exists in scope
             let value = self.globals.lock()[*idx].clone();
             let should_call = matches!(&value, Value::Func(f) if f.shape.is_empty());
-            self.push_instr(Instr::push(value));
             if should_call && call {
-                let span = self.add_span(span);
-                self.push_instr(Instr::Call(span));
+                let f = value.into_function().unwrap();
+                if !self.is_profiling() && can_inline(&f) {
+                    for instr in Instr::unfuse(&f.instrs) {
+                        self.push_instr(instr);
+                    }
+                } else {
+                    self.push_instr(Instr::push(f));
+                    let span = self.add_span(span);
+                    self.push_instr(Instr::Call(span));
+                }
+            } else {
+                self.push_instr(Instr::push(value));
             }
+        } else if let Some(binding) = (self.current_binding.as_mut()).filter(|b| b.name == ident) {
+            // A binding's body can call the binding itself, which is looked up when it is called
+            let Some(sig) = binding.signature else {
+                return Err(span
+                    .sp(format!(
+                        "Recursive function `{ident}` must declare its signature"
+                    ))
+                    .into());
+            };
+            if !call {
+                return Err(span
+                    .sp(format!(
+                        "`{ident}` can only be called directly in its own definition"
+                    ))
+                    .into());
+            }
+            binding.referenced = true;
+            let index = binding.global_index;
+            let span = self.add_span(span);
+            self.push_instr(Instr::CallGlobal { index, span, sig });
         } else {
-            return Err(span.sp(format!("Unknown identifier `{ident}`")).into());
+            let mut message = format!("Unknown identifier `{ident}`");
+            let suggestions = self.similar_names(&ident);
+            if !suggestions.is_empty() {
+                message.push_str(". Did you mean ");
+                for (i, name) in suggestions.iter().enumerate() {
+                    if i > 0 {
+                        message.push_str(if i == suggestions.len() - 1 {
+                            " or "
+                        } else {
+                            ", "
+                        });
+                    }
+                    message.push_str(name);
+                }
+                message.push('?');
+            }
+            return Err(span.sp(message).into());
         }
         Ok(())
     }
+    /// Find the names of bindings and primitives that might have been meant
+    /// by an unknown identifier
+    ///
+    /// Closer names come first, and bindings come before primitives.
+    fn similar_names(&self, ident: &str) -> Vec<String> {
+        let max_distance = (ident.chars().count() + 1) / 3;
+        let mut candidates = Vec::new();
+        let scopes =
+            once(&self.scope).chain(self.higher_scopes.last().filter(|_| self.scope.local));
+        let bindings = (scopes.flat_map(|scope| scope.names.keys()))
+            .chain(self.local_names.last().into_iter().flatten());
+        for name in bindings {
+            let distance = edit_distance(ident, name);
+            if distance <= max_distance {
+                candidates.push((distance, false, format!("`{name}`")));
+            }
+        }
+        let lowercase = ident.to_lowercase();
+        for prim in Primitive::all() {
+            let Some(name) = prim.name() else {
+                continue;
+            };
+            let distance = edit_distance(&lowercase, name);
+            if distance <= max_distance {
+                candidates.push(match prim.glyph() {
+                    Some(glyph) => (distance, true, format!("`{name}` {glyph}")),
+                    None => (distance, true, format!("`{name}`")),
+                });
+            }
+        }
+        candidates.sort();
+        candidates.dedup();
+        candidates.truncate(3);
+        candidates.into_iter().map(|(.., name)| name).collect()
+    }
     fn func(&mut self, func: Func, span: CodeSpan) -> UiuaResult {
         let mut instrs = Vec::new();
-        for line in func.lines {
-            instrs.extend(self.compile_words(line, true)?);
+        // Placeholders are bound to locals when the function is called
+        let placeholders = (func.lines.iter().flatten())
+            .map(max_placeholder)
+            .max()
+            .unwrap_or(0);
+        self.local_names
+            .push((1..=placeholders).map(placeholder_name).collect());
+        if placeholders > 0 {
+            let span = self.add_span(span.clone());
+            instrs.extend((0..placeholders).map(|index| Instr::BindLocal { index, span }));
         }
+        let res: UiuaResult = (|| {
+            for line in func.lines {
+                instrs.extend(self.compile_words(line, true)?);
+            }
+            Ok(())
+        })();
+        self.local_names.pop();
+        res?;
 
         // Validate signature
         let sig = match instrs_signature(&instrs) {
@@ -652,11 +1081,13 @@ impl Uiua {
 
         if call {
             self.words(modified.operands, false)?;
+            self.check_modifier_operand(&modified.modifier)?;
             let span = self.add_span(modified.modifier.span);
             self.push_instr(Instr::Prim(modified.modifier.value, span));
         } else {
             self.new_functions.push(Vec::new());
             self.words(modified.operands, false)?;
+            self.check_modifier_operand(&modified.modifier)?;
             self.primitive(
                 modified.modifier.value,
                 modified.modifier.span.clone(),
@@ -679,6 +1110,19 @@ impl Uiua {
         }
         Ok(())
     }
+    /// Check the signature of a modifier's function operand before the modifier is run
+    fn check_modifier_operand(&self, modifier: &Sp<Primitive>) -> UiuaResult {
+        let instrs = self.new_functions.last().unwrap();
+        let Some(f) = instrs
+            .last()
+            .and_then(Instr::as_push)
+            .and_then(Value::as_function)
+        else {
+            return Ok(());
+        };
+        check_modifier_function(modifier.value, f.signature())
+            .map_err(|e| UiuaError::Run(Span::Code(modifier.span.clone()).sp(e)))
+    }
     fn handle_primitive_deprecation(&mut self, prim: Primitive, span: &CodeSpan) {
         if let Some(suggestion) = prim.deprecation_suggestion() {
             let suggestion = if suggestion.is_empty() {
@@ -701,6 +1145,18 @@ impl Uiua {
     fn primitive(&mut self, prim: Primitive, span: CodeSpan, call: bool) -> UiuaResult {
         self.handle_primitive_deprecation(prim, &span);
         let span_i = self.add_span(span.clone());
+        if call && prim == Primitive::Pop {
+            let instrs = self.new_functions.last().unwrap();
+            if let Some(Instr::Push(val)) = instrs.last() {
+                if !matches!(**val, Value::Func(_)) {
+                    self.diagnostics.insert(Diagnostic::new(
+                        "This value is pushed and then immediately popped",
+                        span.clone(),
+                        DiagnosticKind::Warning,
+                    ));
+                }
+            }
+        }
         if call || prim.as_constant().is_some() {
             self.push_instr(Instr::Prim(prim, span_i));
         } else {
@@ -719,6 +1175,39 @@ impl Uiua {
     }
 }
 
+/// The maximum number of instructions a function can have to be inlined
+const INLINE_LIMIT: usize = 10;
+
+/// Whether a function's instructions can be inlined at its call sites
+///
+/// Inlining removes a call frame, which would change what [`recur`](Primitive::Recur)
+/// refers to and remove the function from error traces. Because of this, only functions
+/// that do not call other functions or pass functions to modifiers are inlined.
+fn can_inline(f: &Function) -> bool {
+    f.instrs.len() <= INLINE_LIMIT
+        && f.instrs.iter().all(|instr| match instr {
+            Instr::Call(_)
+            | Instr::CallGlobal { .. }
+            | Instr::BindLocal { .. }
+            | Instr::PushLocal { .. }
+            | Instr::Prim(Primitive::Recur | Primitive::Call, _) => false,
+            Instr::Push(val) => !matches!(**val, Value::Func(_)),
+            _ => true,
+        })
+        && instrs_signature(&f.instrs).is_ok_and(|sig| sig == f.signature())
+}
+
+/// Whether a function is known to return scalars when called on scalars
+///
+/// This is true of functions that only push scalar constants and call pervasive primitives.
+fn returns_scalars(f: &Function) -> bool {
+    f.instrs.iter().all(|instr| match instr {
+        Instr::Push(val) => val.rank() == 0 && !matches!(**val, Value::Func(_)),
+        Instr::Prim(prim, _) => prim.class().is_pervasive(),
+        _ => false,
+    })
+}
+
 fn words_look_pervasive(words: &[Sp<Word>]) -> bool {
     use Primitive::*;
     words.iter().all(|word| match &word.value {
@@ -730,3 +1219,150 @@ fn words_look_pervasive(words: &[Sp<Word>]) -> bool {
         _ => false,
     })
 }
+
+/// The name of the local that a placeholder like `^1` is bound to
+///
+/// It cannot conflict with the name of a local binding, because those can't contain `^`.
+fn placeholder_name(n: usize) -> Ident {
+    format!("^{n}").into()
+}
+
+/// Get the largest placeholder used in a word, not counting ones in inner functions
+fn max_placeholder(word: &Sp<Word>) -> usize {
+    let max = |words: &mut dyn Iterator<Item = &Sp<Word>>| words.map(max_placeholder).max();
+    match &word.value {
+        Word::Placeholder(n) => *n,
+        Word::Strand(items) => max(&mut items.iter()).unwrap_or(0),
+        Word::Array(arr) => max(&mut arr.lines.iter().flatten()).unwrap_or(0),
+        Word::Modified(m) => max(&mut m.operands.iter()).unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// The number of single character insertions, deletions, and substitutions
+/// needed to turn one string into another
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + (a != *b) as usize;
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Find bindings that are never used or that shadow other names
+///
+/// This works on the syntax tree, so bindings used only in test scopes count as used.
+pub(crate) fn binding_diagnostics(items: &[Item]) -> Vec<Diagnostic> {
+    struct BindingUse {
+        span: CodeSpan,
+        /// The depth of the scope the binding is in
+        depth: usize,
+        used: bool,
+    }
+    #[derive(Default)]
+    struct Walker {
+        /// The bindings visible for each name, innermost last
+        bindings: HashMap<Ident, Vec<BindingUse>>,
+        diagnostics: Vec<Diagnostic>,
+    }
+    impl Walker {
+        fn report_unused(&mut self, name: &Ident, binding: BindingUse) {
+            if !binding.used {
+                self.diagnostics.push(Diagnostic::new(
+                    format!("`{name}` is never used"),
+                    binding.span,
+                    DiagnosticKind::Warning,
+                ));
+            }
+        }
+        fn words(&mut self, words: &[Sp<Word>]) {
+            for word in words {
+                match &word.value {
+                    Word::Ident(name) => {
+                        if let Some(binding) =
+                            self.bindings.get_mut(name).and_then(|b| b.last_mut())
+                        {
+                            binding.used = true;
+                        }
+                    }
+                    Word::Strand(items) => self.words(items),
+                    Word::Array(arr) => arr.lines.iter().for_each(|line| self.words(line)),
+                    Word::Func(func) => func.lines.iter().for_each(|line| self.words(line)),
+                    Word::Modified(m) => self.words(&m.operands),
+                    _ => {}
+                }
+            }
+        }
+        fn binding(&mut self, binding: &Binding, depth: usize) {
+            self.words(&binding.words);
+            let name = &binding.name.value;
+            let span = binding.name.span.clone();
+            let shadows = if CONSTANTS.iter().any(|def| *def.name == **name) {
+                Some("a built-in constant")
+            } else {
+                match self.bindings.get(name).and_then(|b| b.last()) {
+                    Some(old) if old.depth < depth => Some("a binding from an outer scope"),
+                    Some(old) if old.used => Some("an earlier binding"),
+                    _ => None,
+                }
+            };
+            if let Some(shadowed) = shadows {
+                self.diagnostics.push(Diagnostic::new(
+                    format!("`{name}` shadows {shadowed}"),
+                    span.clone(),
+                    DiagnosticKind::Warning,
+                ));
+            }
+            let new = BindingUse {
+                span,
+                depth,
+                used: false,
+            };
+            let stack = self.bindings.entry(name.clone()).or_default();
+            if stack.last().is_some_and(|old| old.depth == depth) {
+                let old = stack.pop().unwrap();
+                stack.push(new);
+                self.report_unused(name, old);
+            } else {
+                stack.push(new);
+            }
+        }
+        fn items(&mut self, items: &[Item], depth: usize) {
+            for item in items {
+                match item {
+                    Item::Words(words) => self.words(words),
+                    Item::Binding(binding) => self.binding(binding, depth),
+                    Item::Scoped { items, .. } => {
+                        self.items(items, depth + 1);
+                        // Names bound in a scope are not visible after it ends
+                        self.end_scope(depth + 1);
+                    }
+                    Item::ExtraNewlines(_) => {}
+                }
+            }
+        }
+        fn end_scope(&mut self, depth: usize) {
+            let mut ended = Vec::new();
+            for (name, stack) in &mut self.bindings {
+                if stack.last().is_some_and(|b| b.depth >= depth) {
+                    ended.push((name.clone(), stack.pop().unwrap()));
+                }
+            }
+            ended.sort_by_key(|(_, binding)| binding.span.start.byte_pos);
+            for (name, binding) in ended {
+                self.report_unused(&name, binding);
+            }
+        }
+    }
+    let mut walker = Walker::default();
+    walker.items(items, 0);
+    walker.end_scope(0);
+    walker.diagnostics
+}
@@ -32,6 +32,10 @@ impl<T> CowSlice<T> {
     pub fn truncate(&mut self, len: usize) {
         self.end = (self.start + len as u32).min(self.end);
     }
+    /// Check if two slices view the same elements of the same allocation
+    pub fn ptr_eq<U>(&self, other: &CowSlice<U>) -> bool {
+        self.len() == other.len() && self.as_ptr() as *const () == other.as_ptr() as *const ()
+    }
 }
 
 impl<T: Clone> CowSlice<T> {
@@ -110,6 +114,18 @@ impl<T: Clone> Clone for CowSlice<T> {
     }
 }
 
+#[test]
+fn cow_slice_clone_shares() {
+    let slice = CowSlice::from([1, 2, 3, 4]);
+    let mut copy = slice.clone();
+    assert_eq!(slice.data.as_ptr(), copy.data.as_ptr());
+
+    copy.modify(|vec| vec.push(5));
+    assert_ne!(slice.data.as_ptr(), copy.data.as_ptr());
+    assert_eq!(slice, [1, 2, 3, 4]);
+    assert_eq!(copy, [1, 2, 3, 4, 5]);
+}
+
 impl<T> Deref for CowSlice<T> {
     type Target = [T];
     fn deref(&self) -> &Self::Target {
@@ -11,7 +11,7 @@ use ariadne::{Color, Config, Label, Report, ReportKind, Source};
 
 use crate::{
     example_ua,
-    function::FunctionId,
+    function::{FunctionId, Instr},
     lex::{CodeSpan, Sp, Span},
     parse::ParseError,
     value::Value,
@@ -30,7 +30,10 @@ pub enum UiuaError {
     Throw(Box<Value>, Span),
     Break(usize, Span),
     Timeout(Span),
+    Interrupted(Span),
+    Exit(i32, Span),
     Fill(Box<Self>),
+    Multi(Vec<Self>),
 }
 
 pub type UiuaResult<T = ()> = Result<T, UiuaError>;
@@ -51,6 +54,8 @@ impl From<Sp<String>> for UiuaError {
 pub struct TraceFrame {
     pub id: FunctionId,
     pub span: Span,
+    /// The instruction the function was running
+    pub instr: Option<Instr>,
 }
 
 impl fmt::Display for UiuaError {
@@ -76,7 +81,18 @@ impl fmt::Display for UiuaError {
             UiuaError::Throw(value, span) => write!(f, "{span}: {value}"),
             UiuaError::Break(_, span) => write!(f, "{span}: break outside of loop"),
             UiuaError::Timeout(_) => write!(f, "Maximum execution time exceeded"),
+            UiuaError::Interrupted(_) => write!(f, "Program interrupted"),
+            UiuaError::Exit(code, _) => write!(f, "Program exited with code {code}"),
             UiuaError::Fill(error) => error.fmt(f),
+            UiuaError::Multi(errors) => {
+                for (i, error) in errors.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    error.fmt(f)?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -95,6 +111,30 @@ impl UiuaError {
             error => error.message().into(),
         }
     }
+    /// Get the span of the code that caused the error
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            UiuaError::Load(..) | UiuaError::Format(..) => None,
+            UiuaError::Parse(errors) => errors.first().map(|error| error.span.clone().into()),
+            UiuaError::Run(error) => Some(error.span.clone()),
+            UiuaError::Traced { error, .. } | UiuaError::Fill(error) => error.span(),
+            UiuaError::Throw(_, span)
+            | UiuaError::Break(_, span)
+            | UiuaError::Timeout(span)
+            | UiuaError::Interrupted(span)
+            | UiuaError::Exit(_, span) => Some(span.clone()),
+            UiuaError::Multi(errors) => errors.first().and_then(UiuaError::span),
+        }
+    }
+    /// Get the instruction that caused a runtime error
+    pub fn instr(&self) -> Option<&Instr> {
+        match self {
+            UiuaError::Traced { trace, .. } => trace.iter().find_map(|frame| frame.instr.as_ref()),
+            UiuaError::Fill(error) => error.instr(),
+            UiuaError::Multi(errors) => errors.first().and_then(UiuaError::instr),
+            _ => None,
+        }
+    }
     pub fn break_data(self) -> Result<(usize, Span), Self> {
         match self {
             UiuaError::Traced { error, trace } => {
@@ -115,6 +155,28 @@ impl UiuaError {
             _ => false,
         }
     }
+    /// Check if the error was caused by an interruption
+    pub(crate) fn is_interrupted(&self) -> bool {
+        match self {
+            UiuaError::Traced { error, .. } => error.is_interrupted(),
+            UiuaError::Interrupted(_) => true,
+            _ => false,
+        }
+    }
+    /// Get the exit code if the error was caused by [`SysOp::Exit`](crate::SysOp::Exit)
+    ///
+    /// Hosts should end the program with this code rather than reporting an error.
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            UiuaError::Traced { error, .. } | UiuaError::Fill(error) => error.exit_code(),
+            UiuaError::Exit(code, _) => Some(*code),
+            _ => None,
+        }
+    }
+    /// Check if the error should stop the program without being caught
+    pub(crate) fn is_uncatchable(&self) -> bool {
+        self.is_interrupted() || self.exit_code().is_some()
+    }
     /// Mark the error as fill-related
     pub(crate) fn fill(self) -> Self {
         UiuaError::Fill(Box::new(self))
@@ -125,6 +187,7 @@ fn format_trace<F: fmt::Write>(f: &mut F, trace: &[TraceFrame]) -> fmt::Result {
     let last = TraceFrame {
         id: FunctionId::Named("".into()),
         span: Span::Builtin,
+        instr: None,
     };
     let mut last = &last;
     let mut repetitions = 1;
@@ -213,7 +276,15 @@ impl UiuaError {
                 kind,
                 color,
             ),
+            UiuaError::Interrupted(span) => {
+                report([("Program interrupted", span.clone())], kind, color)
+            }
+            UiuaError::Exit(..) => self.to_string(),
             UiuaError::Fill(error) => error.show(color),
+            UiuaError::Multi(errors) => {
+                let shown: Vec<_> = errors.iter().map(|error| error.show(color)).collect();
+                shown.join("\n")
+            }
             UiuaError::Load(..) | UiuaError::Format(..) => self.to_string(),
         }
     }
@@ -0,0 +1,174 @@
+//! A C API for embedding Uiua, enabled by the `ffi` feature
+//!
+//! Build it as a shared library with
+//! `cargo rustc --lib --release --features ffi --crate-type cdylib`.
+//! The declarations are in `include/uiua.h`.
+//!
+//! An interpreter keeps its bindings between evaluations.
+//! The results and error of an evaluation are owned by the interpreter and stay valid
+//! until the next evaluation or until the interpreter is freed.
+
+use std::{
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::{value::Value, Uiua};
+
+/// A Uiua interpreter and the results of its last evaluation
+pub struct UiuaInterpreter {
+    env: Uiua,
+    results: Vec<Value>,
+    error: Option<CString>,
+}
+
+/// The element type of a [`UiuaArray`]
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiuaElementType {
+    /// The data is `double`s
+    Num = 0,
+    /// The data is `uint8_t`s
+    Byte = 1,
+    /// The data is `uint32_t` Unicode code points
+    Char = 2,
+    /// Boxes and functions, whose data is not exposed
+    Func = 3,
+}
+
+/// A view of an array in an interpreter's results
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UiuaArray {
+    pub element_type: UiuaElementType,
+    pub rank: usize,
+    pub shape: *const usize,
+    /// The number of elements
+    pub len: usize,
+    /// The elements in row-major order, or null for [`UiuaElementType::Func`]
+    pub data: *const c_void,
+}
+
+/// Create an interpreter with the standard IO backend
+///
+/// It must be freed with [`uiua_free`].
+#[no_mangle]
+pub extern "C" fn uiua_new() -> *mut UiuaInterpreter {
+    Box::into_raw(Box::new(UiuaInterpreter {
+        env: Uiua::with_native_sys(),
+        results: Vec::new(),
+        error: None,
+    }))
+}
+
+/// Free an interpreter
+///
+/// # Safety
+/// `interp` must be null or come from [`uiua_new`], and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_free(interp: *mut UiuaInterpreter) {
+    if !interp.is_null() {
+        drop(Box::from_raw(interp));
+    }
+}
+
+/// Evaluate a null-terminated UTF-8 string of code
+///
+/// Returns 0 on success, in which case the stack becomes the results.
+/// Otherwise returns 1, and the error can be read with [`uiua_error`].
+///
+/// # Safety
+/// `interp` must be a valid interpreter, and `code` must be null or a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_eval(interp: *mut UiuaInterpreter, code: *const c_char) -> c_int {
+    let interp = &mut *interp;
+    interp.results.clear();
+    interp.error = None;
+    let res = if code.is_null() {
+        Err("Code is null".into())
+    } else {
+        match CStr::from_ptr(code).to_str() {
+            Ok(code) => match catch_unwind(AssertUnwindSafe(|| interp.env.run(code))) {
+                Ok(Ok(results)) => Ok(results),
+                Ok(Err(e)) => Err(e.show(false)),
+                Err(_) => Err("The interpreter panicked".into()),
+            },
+            Err(e) => Err(format!("Code is not valid UTF-8: {e}")),
+        }
+    };
+    match res {
+        Ok(results) => {
+            interp.results = results;
+            0
+        }
+        Err(e) => {
+            interp.error = Some(CString::new(e.replace('\0', "")).unwrap());
+            1
+        }
+    }
+}
+
+/// Get the error of the last evaluation as a null-terminated string, or null if it succeeded
+///
+/// # Safety
+/// `interp` must be a valid interpreter.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_error(interp: *const UiuaInterpreter) -> *const c_char {
+    (*interp).error.as_ref().map_or(ptr::null(), |e| e.as_ptr())
+}
+
+/// Get the number of results of the last evaluation
+///
+/// # Safety
+/// `interp` must be a valid interpreter.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_result_count(interp: *const UiuaInterpreter) -> usize {
+    (*interp).results.len()
+}
+
+/// Get one of the results of the last evaluation
+///
+/// Results are in the order they were pushed, so the top of the stack is last.
+/// Returns false and leaves `out` unchanged if `index` is out of range.
+///
+/// # Safety
+/// `interp` must be a valid interpreter, and `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn uiua_result(
+    interp: *const UiuaInterpreter,
+    index: usize,
+    out: *mut UiuaArray,
+) -> bool {
+    let interp = &*interp;
+    let Some(value) = interp.results.get(index) else {
+        return false;
+    };
+    let (element_type, len, data) = match value {
+        Value::Num(array) => (
+            UiuaElementType::Num,
+            array.data.len(),
+            array.data.as_ptr() as *const c_void,
+        ),
+        Value::Byte(array) => (
+            UiuaElementType::Byte,
+            array.data.len(),
+            array.data.as_ptr() as *const c_void,
+        ),
+        // `char` has the same layout as `u32`
+        Value::Char(array) => (
+            UiuaElementType::Char,
+            array.data.len(),
+            array.data.as_ptr() as *const c_void,
+        ),
+        Value::Func(array) => (UiuaElementType::Func, array.data.len(), ptr::null()),
+    };
+    *out = UiuaArray {
+        element_type,
+        rank: value.rank(),
+        shape: value.shape().as_ptr(),
+        len,
+        data,
+    };
+    true
+}
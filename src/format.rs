@@ -6,6 +6,7 @@ use std::{
     env,
     fmt::Display,
     fs,
+    mem::{replace, take},
     path::{Path, PathBuf},
 };
 
@@ -17,6 +18,7 @@ use crate::{
     grid_fmt::GridFmt,
     lex::{CodeSpan, Loc, Sp},
     parse::parse,
+    primitive::Primitive,
     value::Value,
     SysBackend, Uiua, UiuaError, UiuaResult,
 };
@@ -112,8 +114,9 @@ macro_rules! create_config {
         impl PartialFormatConfig {
             paste! {
                 fn from_file(file_path: PathBuf) -> UiuaResult<Self> {
-                    let mut env = Uiua::with_backend(FormatConfigBackend)
-                        .print_diagnostics(true);
+                    // Diagnostics are not printed because the config bindings
+                    // would all be reported as unused
+                    let mut env = Uiua::with_backend(FormatConfigBackend);
                     env.load_file(file_path)?;
                     let mut bindings = env.all_bindings_in_scope();
 
@@ -200,6 +203,14 @@ create_config!(
     (multiline_compact_threshold, usize, 10),
     /// Whether to align consecutive end-of-line comments
     (align_comments, bool, true),
+    /// Whether to remove spaces after primitive glyphs where they are not needed to separate tokens
+    (remove_glyph_spaces, bool, true),
+    /// Whether to write primitives as glyphs. If false, primitives are written as their names.
+    (glyphs, bool, true),
+    /// Whether to align the arrows of bindings on consecutive lines
+    (align_bindings, bool, false),
+    /// The maximum width of a line. Arrays of literals that would go past it are wrapped onto multiple lines. 0 means there is no maximum.
+    (max_line_width, usize, 0),
 );
 
 /// The source from which to populate the formatter configuration.
@@ -319,6 +330,7 @@ pub fn format_items(items: &[Item], config: &FormatConfig) -> FormatOutput {
         output: String::new(),
         glyph_map: BTreeMap::new(),
         end_of_line_comments: Vec::new(),
+        binding_lines: Vec::new(),
     };
     formatter.format_items(items);
     let mut output = formatter.output;
@@ -368,14 +380,59 @@ struct Formatter<'a> {
     output: String,
     glyph_map: BTreeMap<CodeSpan, Loc>,
     end_of_line_comments: Vec<(usize, String)>,
+    /// The line numbers of single-line bindings and the widths of their names
+    binding_lines: Vec<(usize, usize)>,
 }
 
 impl<'a> Formatter<'a> {
     fn format_items(&mut self, items: &[Item]) {
         for item in items {
+            let start = self.output.len();
             self.format_item(item);
+            if let Item::Binding(binding) = item {
+                if !self.output[start..].contains('\n') {
+                    let line_number = self.output.split('\n').count();
+                    let width = binding.name.value.chars().count();
+                    self.binding_lines.push((line_number, width));
+                }
+            }
             self.output.push('\n');
         }
+        // Align binding arrows
+        if self.config.align_bindings && !self.binding_lines.is_empty() {
+            let mut lines: Vec<String> = self.output.split('\n').map(Into::into).collect();
+            let mut groups: Vec<Vec<(usize, usize)>> = Vec::new();
+            for (line_number, width) in self.binding_lines.drain(..) {
+                match groups.last_mut() {
+                    Some(group) if line_number - group.last().unwrap().0 == 1 => {
+                        group.push((line_number, width))
+                    }
+                    _ => groups.push(vec![(line_number, width)]),
+                }
+            }
+            // The number of spaces added to each padded line, by 0-based line number
+            let mut padding = BTreeMap::new();
+            for group in groups {
+                let max = group.iter().map(|(_, width)| *width).max().unwrap();
+                for (line_number, width) in group {
+                    let line = &mut lines[line_number - 1];
+                    let name_end = line
+                        .char_indices()
+                        .nth(width)
+                        .map_or(line.len(), |(i, _)| i);
+                    line.insert_str(name_end, &" ".repeat(max - width));
+                    padding.insert(line_number - 1, max - width);
+                }
+            }
+            // Move glyph locations after the padding
+            for loc in self.glyph_map.values_mut() {
+                let added: usize = padding.range(..=loc.line).map(|(_, pad)| pad).sum();
+                loc.char_pos += added;
+                loc.byte_pos += added;
+                loc.col += padding.get(&loc.line).copied().unwrap_or(0);
+            }
+            self.output = lines.join("\n");
+        }
         // Align end-of-line comments
         if self.config.align_comments && !self.end_of_line_comments.is_empty() {
             // Group comments by consecutive lines
@@ -456,30 +513,48 @@ impl<'a> Formatter<'a> {
         }
     }
     fn format_words(&mut self, words: &[Sp<Word>], trim_end: bool, depth: usize) {
-        for word in trim_spaces(words, trim_end) {
+        let words = trim_spaces(words, trim_end);
+        for (i, word) in words.iter().enumerate() {
+            if self.config.remove_glyph_spaces
+                && self.config.glyphs
+                && i > 0
+                && matches!(word.value, Word::Spaces)
+                && matches!(&words[i - 1].value, Word::Primitive(prim)
+                    if glyph_needs_no_space(*prim, words.get(i + 1).map(|w| &w.value)))
+            {
+                continue;
+            }
             self.format_word(word, depth);
         }
     }
     fn format_word(&mut self, word: &Sp<Word>, depth: usize) {
+        // Names of primitives must be separated from the words after them
+        if !self.config.glyphs
+            && self.output.ends_with(is_name_char)
+            && word.span.as_str().starts_with(is_name_char)
+        {
+            self.output.push(' ');
+        }
         match &word.value {
             Word::Number(s, n) => {
                 let grid_str = n.grid_string();
-                if grid_str.len() < s.len() {
+                let unsigned = s.trim_start_matches(['`', '¯']);
+                if let Some(radix @ ('x' | 'X' | 'b' | 'B')) = unsigned.chars().nth(1) {
+                    // Hexadecimal and binary literals are kept in their base
+                    if s.len() > unsigned.len() {
+                        self.output.push('¯');
+                    }
+                    self.output.push('0');
+                    self.output.push(radix.to_ascii_lowercase());
+                    self.output.push_str(&unsigned[2..].to_ascii_uppercase());
+                } else if grid_str.len() < s.len() {
                     self.output.push_str(&grid_str);
                 } else {
                     self.output.push_str(&s.replace('`', "¯"));
                 }
             }
-            Word::Char(c) => {
-                let formatted = match *c {
-                    char::MAX => "'\\_'".to_string(),
-                    _ => format!("{c:?}"),
-                };
-                let formatted = &formatted[1..formatted.len() - 1];
-                self.output.push('@');
-                self.output.push_str(formatted);
-            }
-            Word::String(s) => self.output.push_str(&format!("{:?}", s)),
+            // Character and string literals are kept as written so that escapes are preserved
+            Word::Char(_) | Word::String(_) => self.output.push_str(word.span.as_str()),
             Word::FormatString(_) => self.output.push_str(word.span.as_str()),
             Word::MultilineString(lines) => {
                 if lines.len() == 1 {
@@ -507,6 +582,14 @@ impl<'a> Formatter<'a> {
                 }
             }
             Word::Ident(ident) => self.output.push_str(ident),
+            Word::BindLocal(ident) => {
+                self.output.push_str(ident);
+                self.output.push_str(" ←");
+            }
+            Word::Placeholder(n) => {
+                self.output.push('^');
+                self.output.push_str(&n.to_string());
+            }
             Word::Strand(items) => {
                 for (i, item) in items.iter().enumerate() {
                     if i > 0 {
@@ -518,6 +601,7 @@ impl<'a> Formatter<'a> {
                     self.output.push('_');
                 }
             }
+            Word::Array(arr) if self.should_wrap(arr) => self.format_wrapped_array(arr),
             Word::Array(arr) => {
                 if arr.constant {
                     self.output.push('{');
@@ -540,13 +624,13 @@ impl<'a> Formatter<'a> {
                 self.output.push(')');
             }
             Word::Primitive(prim) => {
-                self.push(&word.span, &prim.to_string());
+                self.push_prim(&word.span, *prim);
                 if prim.is_modifier() {
                     self.output.push('|');
                 }
             }
             Word::Modified(m) => {
-                self.push(&m.modifier.span, &m.modifier.value.to_string());
+                self.push_prim(&m.modifier.span, m.modifier.value);
                 self.format_words(&m.operands, true, depth);
                 if m.terminated {
                     self.output.push('|');
@@ -639,6 +723,68 @@ impl<'a> Formatter<'a> {
             }
         }
     }
+    /// Check if an array of literals would go past the maximum line width
+    fn should_wrap(&self, arr: &Arr) -> bool {
+        if self.config.max_line_width == 0
+            || arr.lines.len() != 1
+            || !arr.lines[0].iter().all(|word| word_is_literal(&word.value))
+        {
+            return false;
+        }
+        let line = self.output.rsplit('\n').next().unwrap_or_default();
+        let arr_width: usize = (arr.lines[0].iter())
+            .map(|word| word.span.as_str().chars().count())
+            .sum();
+        line.chars().count() + arr_width + 2 > self.config.max_line_width
+    }
+    /// Format an array of literals with as many items on each line as fit
+    fn format_wrapped_array(&mut self, arr: &Arr) {
+        let (open, close) = if arr.constant { ('{', '}') } else { ('[', ']') };
+        self.output.push(open);
+        let indent = self
+            .output
+            .rsplit('\n')
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .count();
+        let mut col = indent;
+        let items = arr.lines[0]
+            .iter()
+            .filter(|word| !matches!(word.value, Word::Spaces));
+        for (i, item) in items.enumerate() {
+            // Literals never add to the glyph map, so they can be formatted separately
+            let outer = take(&mut self.output);
+            self.format_word(item, 0);
+            let text = replace(&mut self.output, outer);
+            let width = text.chars().count();
+            if i > 0 {
+                if col + 1 + width + 1 > self.config.max_line_width {
+                    self.output.push('\n');
+                    self.output.push_str(&" ".repeat(indent));
+                    col = indent;
+                } else {
+                    self.output.push(' ');
+                    col += 1;
+                }
+            }
+            self.output.push_str(&text);
+            col += width;
+        }
+        self.output.push(close);
+    }
+    fn push_prim(&mut self, span: &CodeSpan, prim: Primitive) {
+        match prim.name().filter(|_| !self.config.glyphs) {
+            Some(name) => {
+                // Names must be separated from the words around them
+                if self.output.ends_with(is_name_char) {
+                    self.output.push(' ');
+                }
+                self.push(span, name);
+            }
+            None => self.push(span, &prim.to_string()),
+        }
+    }
     fn push(&mut self, span: &CodeSpan, formatted: &str) {
         self.output.push_str(formatted);
         if span.as_str() != formatted {
@@ -648,6 +794,37 @@ impl<'a> Formatter<'a> {
     }
 }
 
+/// Whether a primitive's glyph can be followed by a word without a space
+///
+/// Non-ASCII glyphs only combine with a following number in the case of
+/// [`negate`](Primitive::Neg). ASCII glyphs may combine with other glyphs, like `<` and `=`.
+fn glyph_needs_no_space(prim: Primitive, next: Option<&Word>) -> bool {
+    let Some(glyph) = prim.glyph() else {
+        return false;
+    };
+    match next {
+        None | Some(Word::Comment(_) | Word::Spaces) => false,
+        _ if prim.is_modifier() || prim == Primitive::Neg => false,
+        Some(Word::Primitive(_) | Word::Modified(_)) => !glyph.is_ascii(),
+        Some(_) => true,
+    }
+}
+
+fn word_is_literal(word: &Word) -> bool {
+    match word {
+        Word::Number(..) | Word::Char(_) | Word::String(_) | Word::Spaces => true,
+        Word::Strand(items) => items.iter().all(|item| word_is_literal(&item.value)),
+        Word::Array(arr) => {
+            arr.lines.len() == 1 && arr.lines[0].iter().all(|word| word_is_literal(&word.value))
+        }
+        _ => false,
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '&'
+}
+
 fn trim_spaces(words: &[Sp<Word>], trim_end: bool) -> &[Sp<Word>] {
     let mut start = 0;
     for word in words {
@@ -680,7 +857,7 @@ fn word_is_multiline(word: &Word) -> bool {
         Word::String(_) => false,
         Word::FormatString(_) => false,
         Word::MultilineString(_) => true,
-        Word::Ident(_) => false,
+        Word::Ident(_) | Word::BindLocal(_) | Word::Placeholder(_) => false,
         Word::Strand(_) => false,
         Word::Array(arr) => {
             arr.lines.len() > 1
@@ -22,7 +22,20 @@ pub enum Instr {
     },
     Prim(Primitive, usize),
     Call(usize),
+    CallGlobal {
+        index: usize,
+        span: usize,
+        sig: Signature,
+    },
     Dynamic(DynamicFunction),
+    BindLocal {
+        index: usize,
+        span: usize,
+    },
+    PushLocal {
+        index: usize,
+        span: usize,
+    },
     PushTemp {
         count: usize,
         span: usize,
@@ -44,6 +57,13 @@ pub enum Instr {
         span: usize,
         kind: TempKind,
     },
+    /// A pervasive primitive fused with the push, [`dup`](Primitive::Dup), or
+    /// [`flip`](Primitive::Flip) instruction before it so that the pair is dispatched once
+    Super {
+        first: Box<Instr>,
+        prim: Primitive,
+        span: usize,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -60,6 +80,9 @@ impl PartialEq for Instr {
             (Self::EndArray { .. }, Self::EndArray { .. }) => true,
             (Self::Prim(a, s_span), Self::Prim(b, b_span)) => a == b && s_span == b_span,
             (Self::Call(a), Self::Call(b)) => a == b,
+            (Self::CallGlobal { index: a, .. }, Self::CallGlobal { index: b, .. }) => a == b,
+            (Self::BindLocal { index: a, .. }, Self::BindLocal { index: b, .. }) => a == b,
+            (Self::PushLocal { index: a, .. }, Self::PushLocal { index: b, .. }) => a == b,
             (Self::PushTemp { count: a, .. }, Self::PushTemp { count: b, .. }) => a == b,
             (Self::PopTemp { count: a, .. }, Self::PopTemp { count: b, .. }) => a == b,
             (
@@ -75,6 +98,18 @@ impl PartialEq for Instr {
                 },
             ) => ao == bo && ac == bc,
             (Self::DropTemp { count: a, .. }, Self::DropTemp { count: b, .. }) => a == b,
+            (
+                Self::Super {
+                    first: af,
+                    prim: ap,
+                    span: a_span,
+                },
+                Self::Super {
+                    first: bf,
+                    prim: bp,
+                    span: b_span,
+                },
+            ) => af == bf && ap == bp && a_span == b_span,
             _ => false,
         }
     }
@@ -115,7 +150,9 @@ impl Hash for Instr {
             Instr::EndArray { .. } => 2u8.hash(state),
             Instr::Prim(p, _) => p.hash(state),
             Instr::Call(_) => {}
+            Instr::CallGlobal { index, .. } => index.hash(state),
             Instr::Dynamic(f) => f.id.hash(state),
+            Instr::BindLocal { index, .. } | Instr::PushLocal { index, .. } => index.hash(state),
             Instr::PushTemp { count, .. } => count.hash(state),
             Instr::PopTemp { count, .. } => count.hash(state),
             Instr::CopyTemp { offset, count, .. } => {
@@ -123,6 +160,10 @@ impl Hash for Instr {
                 count.hash(state);
             }
             Instr::DropTemp { count, .. } => count.hash(state),
+            Instr::Super { first, prim, .. } => {
+                first.hash(state);
+                prim.hash(state);
+            }
         }
     }
 }
@@ -137,6 +178,74 @@ impl Instr {
             _ => None,
         }
     }
+    /// Fuse common instruction pairs into superinstructions
+    fn fuse(instrs: Vec<Instr>) -> Vec<Instr> {
+        if instrs.len() < 2 {
+            return instrs;
+        }
+        let mut fused: Vec<Instr> = Vec::with_capacity(instrs.len());
+        for instr in instrs {
+            match (fused.last(), instr) {
+                (Some(Instr::Push(val)), Instr::Prim(prim, span))
+                    if prim.class().is_pervasive() && !matches!(**val, Value::Func(_)) =>
+                {
+                    let first = fused.pop().unwrap();
+                    fused.push(Instr::Super {
+                        first: first.into(),
+                        prim,
+                        span,
+                    });
+                }
+                (
+                    Some(Instr::Prim(Primitive::Dup | Primitive::Flip, _)),
+                    Instr::Prim(prim, span),
+                ) if prim.class().is_pervasive() => {
+                    let first = fused.pop().unwrap();
+                    fused.push(Instr::Super {
+                        first: first.into(),
+                        prim,
+                        span,
+                    });
+                }
+                (_, instr) => fused.push(instr),
+            }
+        }
+        fused
+    }
+    /// Split superinstructions back into the instructions they were fused from
+    pub(crate) fn unfuse(instrs: &[Instr]) -> Vec<Instr> {
+        let mut unfused = Vec::with_capacity(instrs.len());
+        for instr in instrs {
+            match instr {
+                Instr::Super { first, prim, span } => {
+                    unfused.push((**first).clone());
+                    unfused.push(Instr::Prim(*prim, *span));
+                }
+                instr => unfused.push(instr.clone()),
+            }
+        }
+        unfused
+    }
+    /// Get the index of the instruction's span
+    ///
+    /// Use [`Uiua::instr_span`] to get the span itself.
+    /// Every instruction that can fail has a span.
+    pub fn span(&self) -> Option<usize> {
+        match self {
+            Instr::Prim(_, span)
+            | Instr::Call(span)
+            | Instr::CallGlobal { span, .. }
+            | Instr::BindLocal { span, .. }
+            | Instr::PushLocal { span, .. }
+            | Instr::EndArray { span, .. }
+            | Instr::PushTemp { span, .. }
+            | Instr::PopTemp { span, .. }
+            | Instr::CopyTemp { span, .. }
+            | Instr::DropTemp { span, .. }
+            | Instr::Super { span, .. } => Some(*span),
+            Instr::Push(_) | Instr::BeginArray | Instr::Dynamic(_) => None,
+        }
+    }
     pub fn is_temp(&self) -> bool {
         matches!(
             self,
@@ -163,7 +272,10 @@ impl fmt::Display for Instr {
             Instr::Prim(prim @ Primitive::Over, _) => write!(f, "`{prim}`"),
             Instr::Prim(prim, _) => write!(f, "{prim}"),
             Instr::Call(_) => write!(f, "!"),
+            Instr::CallGlobal { index, .. } => write!(f, "<call global {index}>"),
             Instr::Dynamic(df) => write!(f, "{df:?}"),
+            Instr::BindLocal { index, .. } => write!(f, "<bind local {index}>"),
+            Instr::PushLocal { index, .. } => write!(f, "<local {index}>"),
             Instr::PushTemp { count, kind, .. } => write!(f, "<push {kind:?} {count}>"),
             Instr::PopTemp { count, kind, .. } => write!(f, "<pop {kind:?} {count}>"),
             Instr::CopyTemp {
@@ -173,6 +285,7 @@ impl fmt::Display for Instr {
                 ..
             } => write!(f, "<copy {kind:?} {offset}/{count}>"),
             Instr::DropTemp { count, kind, .. } => write!(f, "<drop {kind:?} {count}>"),
+            Instr::Super { first, prim, .. } => write!(f, "{prim}{first}"),
         }
     }
 }
@@ -320,7 +433,7 @@ impl fmt::Display for Function {
 
 impl Function {
     pub fn new(id: FunctionId, instrs: impl Into<Vec<Instr>>, signature: Signature) -> Self {
-        let instrs = instrs.into();
+        let instrs = Instr::fuse(instrs.into());
         Self {
             id,
             instrs,
@@ -330,6 +443,7 @@ impl Function {
     pub fn new_inferred(id: FunctionId, instrs: impl Into<Vec<Instr>>) -> Result<Self, String> {
         let instrs = instrs.into();
         let signature = instrs_signature(&instrs)?;
+        let instrs = Instr::fuse(instrs);
         Ok(Self {
             id,
             signature,
@@ -410,6 +524,11 @@ impl Function {
             _ => match self.instrs.as_slice() {
                 [Instr::Prim(prim, _)] => Some((*prim, false)),
                 [Instr::Prim(Primitive::Flip, _), Instr::Prim(prim, _)] => Some((*prim, true)),
+                [Instr::Super { first, prim, .. }]
+                    if matches!(**first, Instr::Prim(Primitive::Flip, _)) =>
+                {
+                    Some((*prim, true))
+                }
                 _ => None,
             },
         }
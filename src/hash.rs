@@ -0,0 +1,308 @@
+//! Digests and HMACs of byte arrays
+//!
+//! These are implemented here rather than with a crate so that they work on every target.
+
+/// A digest algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HashAlgorithm {
+    Sha256,
+    Sha1,
+    Md5,
+    Crc32,
+}
+
+impl HashAlgorithm {
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Ok(match name.to_lowercase().replace('-', "").as_str() {
+            "sha256" => HashAlgorithm::Sha256,
+            "sha1" => HashAlgorithm::Sha1,
+            "md5" => HashAlgorithm::Md5,
+            "crc32" => HashAlgorithm::Crc32,
+            _ => {
+                return Err(format!(
+                    "Unknown hash algorithm {name:?}. \
+                    Supported algorithms are sha256, sha1, md5, and crc32."
+                ))
+            }
+        })
+    }
+    /// The size of the blocks that HMAC pads its key to
+    fn block_size(self) -> Option<usize> {
+        match self {
+            HashAlgorithm::Sha256 | HashAlgorithm::Sha1 | HashAlgorithm::Md5 => Some(64),
+            HashAlgorithm::Crc32 => None,
+        }
+    }
+    pub fn digest(self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => sha256(data).to_vec(),
+            HashAlgorithm::Sha1 => sha1(data).to_vec(),
+            HashAlgorithm::Md5 => md5(data).to_vec(),
+            HashAlgorithm::Crc32 => crc32(data).to_be_bytes().to_vec(),
+        }
+    }
+    pub fn hmac(self, key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+        let block_size = self
+            .block_size()
+            .ok_or_else(|| format!("HMAC cannot be used with {self:?}"))?;
+        let mut key = if key.len() > block_size {
+            self.digest(key)
+        } else {
+            key.to_vec()
+        };
+        key.resize(block_size, 0);
+        let mut inner: Vec<u8> = key.iter().map(|b| b ^ 0x36).collect();
+        inner.extend_from_slice(data);
+        let mut outer: Vec<u8> = key.iter().map(|b| b ^ 0x5c).collect();
+        outer.extend(self.digest(&inner));
+        Ok(self.digest(&outer))
+    }
+}
+
+/// Pad a message to a multiple of 64 bytes with its bit length, as SHA-1, SHA-256, and MD5 do
+fn pad_message(data: &[u8], big_endian: bool) -> Vec<u8> {
+    let bit_len = (data.len() as u64).wrapping_mul(8);
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend(if big_endian {
+        bit_len.to_be_bytes()
+    } else {
+        bit_len.to_le_bytes()
+    });
+    message
+}
+
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4,
+        0xab1c5ed5, 0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe,
+        0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f,
+        0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7,
+        0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc,
+        0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b,
+        0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116,
+        0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+        0xc67178f2,
+    ];
+    let mut h: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab,
+        0x5be0cd19,
+    ];
+    for block in pad_message(data, true).chunks_exact(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16]
+                .wrapping_add(s0)
+                .wrapping_add(w[i - 7])
+                .wrapping_add(s1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = h;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = hh
+                .wrapping_add(s1)
+                .wrapping_add(ch)
+                .wrapping_add(K[i])
+                .wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            hh = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+    let mut digest = [0; 32];
+    for (chunk, h) in digest.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+    digest
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    for block in pad_message(data, true).chunks_exact(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &w) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(w);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+    let mut digest = [0; 20];
+    for (chunk, h) in digest.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+    digest
+}
+
+fn md5(data: &[u8]) -> [u8; 16] {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 5, 9, 14, 20, 5, 9, 14, 20, 5,
+        9, 14, 20, 5, 9, 14, 20, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 6, 10,
+        15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let k: Vec<u32> = (0..64)
+        .map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32)
+        .collect();
+    let mut h: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in pad_message(data, false).chunks_exact(64) {
+        let mut m = [0u32; 16];
+        for (i, word) in block.chunks_exact(4).enumerate() {
+            m[i] = u32::from_le_bytes(word.try_into().unwrap());
+        }
+        let [mut a, mut b, mut c, mut d] = h;
+        for i in 0..64 {
+            let (f, g) = match i {
+                0..=15 => ((b & c) | (!b & d), i),
+                16..=31 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                32..=47 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = f.wrapping_add(a).wrapping_add(k[i]).wrapping_add(m[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(S[i]));
+        }
+        for (h, v) in h.iter_mut().zip([a, b, c, d]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+    let mut digest = [0; 16];
+    for (chunk, h) in digest.chunks_exact_mut(4).zip(h) {
+        chunk.copy_from_slice(&h.to_le_bytes());
+    }
+    digest
+}
+
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{value::Value, Uiua};
+
+    #[test]
+    fn hashes() {
+        fn hex(bytes: &[u8]) -> String {
+            bytes.iter().map(|b| format!("{b:02x}")).collect()
+        }
+        let digest = |algorithm: &str, data: &str| {
+            let mut env = Uiua::with_native_sys();
+            env.load_str(&format!("&hash {algorithm:?} {data:?}"))
+                .unwrap();
+            match env.take_stack().as_slice() {
+                [Value::Byte(bytes)] => hex(&bytes.data),
+                stack => panic!("unexpected stack {stack:?}"),
+            }
+        };
+        let fox = "The quick brown fox jumps over the lazy dog";
+        let cases = [
+            (
+                "sha256",
+                "abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                "sha256",
+                "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                "sha256",
+                "",
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            ("sha1", "abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+            ("SHA-1", fox, "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12"),
+            ("md5", "abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("md5", fox, "9e107d9d372bb6826bd81d3542a419d6"),
+            ("crc32", "abc", "352441c2"),
+            ("crc32", fox, "414fa339"),
+        ];
+        for (algorithm, data, expected) in cases {
+            assert_eq!(digest(algorithm, data), expected, "{algorithm} of {data:?}");
+        }
+        let macs = [
+            (
+                "sha256",
+                "f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            ),
+            ("sha1", "de7c9b85b8b78aa6bc8a7a36f70a90701c9db4d9"),
+            ("md5", "80070713463e7749b90c2dc24911e275"),
+        ];
+        for (algorithm, expected) in macs {
+            let algorithm = HashAlgorithm::parse(algorithm).unwrap();
+            let mac = algorithm.hmac(b"key", fox.as_bytes()).unwrap();
+            assert_eq!(hex(&mac), expected, "HMAC {algorithm:?}");
+        }
+        let mut env = Uiua::with_native_sys();
+        env.load_str(&format!(
+            r#"≅ &hmac "sha256" "key" {fox:?} &hmac "sha256" "key" -@\0 {fox:?}"#
+        ))
+        .unwrap();
+        assert_eq!(env.take_stack(), [Value::from(1)]);
+        assert!(Uiua::with_native_sys()
+            .load_str(r#"&hash "sha3" "abc""#)
+            .is_err());
+        assert!(Uiua::with_native_sys()
+            .load_str(r#"&hmac "crc32" "k" "abc""#)
+            .is_err());
+    }
+}
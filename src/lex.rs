@@ -3,23 +3,36 @@ use std::{
     error::Error,
     fmt,
     hash::{Hash, Hasher},
+    ops::Range,
     path::Path,
     sync::Arc,
 };
 
+use unicode_normalization::{char::is_combining_mark, is_nfc, UnicodeNormalization};
+
 use crate::{primitive::Primitive, UiuaError};
 
 pub fn lex(input: &str, file: Option<&Path>) -> (Vec<Sp<Token>>, Vec<Sp<LexError>>) {
+    let input: Arc<str> = input.into();
+    let end = input.len();
+    lex_region(&input, file, Loc::default(), end)
+}
+
+/// Lex the part of the input between `start` and the byte position `end`
+///
+/// `start` must be at the beginning of a line
+pub(crate) fn lex_region(
+    input: &Arc<str>,
+    file: Option<&Path>,
+    start: Loc,
+    end: usize,
+) -> (Vec<Sp<Token>>, Vec<Sp<LexError>>) {
     Lexer {
-        input_chars: input.chars().collect(),
-        loc: Loc {
-            char_pos: 0,
-            byte_pos: 0,
-            line: 1,
-            col: 1,
-        },
+        input_chars: input[start.byte_pos..end].chars().collect(),
+        char_offset: start.char_pos,
+        loc: start,
         file: file.map(Into::into),
-        input: input.into(),
+        input: input.clone(),
         tokens: Vec::new(),
         errors: Vec::new(),
     }
@@ -29,8 +42,9 @@ pub fn lex(input: &str, file: Option<&Path>) -> (Vec<Sp<Token>>, Vec<Sp<LexError
 #[derive(Debug, Clone)]
 pub enum LexError {
     UnexpectedChar(char),
+    Lookalike(char, char),
     ExpectedCharacter(Option<char>),
-    InvalidEscape(char),
+    InvalidEscape(String),
     ExpectedNumber,
 }
 
@@ -38,9 +52,12 @@ impl fmt::Display for LexError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             LexError::UnexpectedChar(c) => write!(f, "Unexpected char {c:?}"),
+            LexError::Lookalike(c, expected) => {
+                write!(f, "Unexpected char {c:?}, did you mean {expected:?}?")
+            }
             LexError::ExpectedCharacter(Some(c)) => write!(f, "Expected {c:?}"),
             LexError::ExpectedCharacter(None) => write!(f, "Expected character"),
-            LexError::InvalidEscape(c) => write!(f, "Invalid escape character {c:?}"),
+            LexError::InvalidEscape(escape) => write!(f, "Invalid escape sequence `{escape}`"),
             LexError::ExpectedNumber => write!(f, "Expected number"),
         }
     }
@@ -164,8 +181,12 @@ impl CodeSpan {
             ..self
         }
     }
+    /// Get the byte range of the span in its input
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.byte_pos..self.end.byte_pos
+    }
     pub fn as_str(&self) -> &str {
-        &self.input[self.start.byte_pos..self.end.byte_pos]
+        &self.input[self.byte_range()]
     }
     pub fn contains_line_col(&self, line: usize, col: usize) -> bool {
         if self.start.line == self.end.line {
@@ -286,6 +307,7 @@ pub enum Token {
     MultilineString(Vec<String>),
     Simple(AsciiToken),
     Glyph(Primitive),
+    Placeholder(usize),
     LeftArrow,
     Newline,
     Spaces,
@@ -316,6 +338,12 @@ impl Token {
             _ => None,
         }
     }
+    pub fn as_placeholder(&self) -> Option<usize> {
+        match self {
+            Token::Placeholder(n) => Some(*n),
+            _ => None,
+        }
+    }
     pub fn as_glyph(&self) -> Option<Primitive> {
         match self {
             Token::Glyph(glyph) => Some(*glyph),
@@ -387,6 +415,8 @@ impl From<Primitive> for Token {
 
 struct Lexer {
     input_chars: Vec<char>,
+    /// The character position of the first of `input_chars`
+    char_offset: usize,
     loc: Loc,
     file: Option<Arc<Path>>,
     input: Arc<str>,
@@ -396,7 +426,12 @@ struct Lexer {
 
 impl Lexer {
     fn peek_char(&self) -> Option<char> {
-        self.input_chars.get(self.loc.char_pos).copied()
+        self.input_chars
+            .get(self.loc.char_pos - self.char_offset)
+            .copied()
+    }
+    fn chars_since(&self, start: Loc) -> &[char] {
+        &self.input_chars[start.char_pos - self.char_offset..self.loc.char_pos - self.char_offset]
     }
     fn update_loc(&mut self, c: char) {
         match c {
@@ -411,7 +446,7 @@ impl Lexer {
         self.loc.byte_pos += c.len_utf8();
     }
     fn next_char_if(&mut self, f: impl Fn(char) -> bool) -> Option<char> {
-        let c = *self.input_chars.get(self.loc.char_pos)?;
+        let c = self.peek_char()?;
         if !f(c) {
             return None;
         }
@@ -478,6 +513,25 @@ impl Lexer {
                 '↰' => self.end(Primitive::Spawn, start),
                 '↲' => self.end(Primitive::Wait, start),
 
+                // Lookalikes that are often pasted from other text
+                '−' | '‐' | '‑' | '–' => self.end(Primitive::Sub, start),
+                '✕' | '✖' | '⨯' => self.end(Primitive::Mul, start),
+                '∕' | '➗' => self.end(Primitive::Div, start),
+                '⩽' => self.end(Primitive::Le, start),
+                '⩾' => self.end(Primitive::Ge, start),
+                '‾' | 'ˉ' => {
+                    if self.peek_char().filter(char::is_ascii_digit).is_some() {
+                        self.number('-');
+                        self.end(Number, start)
+                    } else {
+                        self.end(Primitive::Neg, start)
+                    }
+                }
+                '“' | '”' | '„' => self
+                    .errors
+                    .push(self.end_span(start).sp(LexError::Lookalike(c, '"'))),
+                '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}' => continue,
+
                 '(' => self.end(OpenParen, start),
                 ')' => self.end(CloseParen, start),
                 '{' => self.end(OpenCurly, start),
@@ -500,7 +554,18 @@ impl Lexer {
                 }
                 '*' => self.end(Star, start),
                 '%' => self.end(Percent, start),
-                '^' => self.end(Caret, start),
+                '^' => {
+                    // Placeholders
+                    let mut n: Option<usize> = None;
+                    while let Some(c) = self.next_char_if(|c| c.is_ascii_digit()) {
+                        let digit = c.to_digit(10).unwrap() as usize;
+                        n = Some(n.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+                    }
+                    match n {
+                        Some(n) => self.end(Placeholder(n), start),
+                        None => self.end(Caret, start),
+                    }
+                }
                 '=' => self.end(Equal, start),
                 '<' if self.next_char_exact('=') => self.end(LessEqual, start),
                 '>' if self.next_char_exact('=') => self.end(GreaterEqual, start),
@@ -519,8 +584,7 @@ impl Lexer {
                 }
                 // Characters
                 '@' => {
-                    let mut escaped = false;
-                    let char = match self.character(&mut escaped, None) {
+                    let char = match self.character(None) {
                         Ok(Some(c)) => c,
                         Ok(None) => {
                             self.errors
@@ -528,8 +592,7 @@ impl Lexer {
                             continue;
                         }
                         Err(e) => {
-                            self.errors
-                                .push(self.end_span(start).sp(LexError::InvalidEscape(e)));
+                            self.errors.push(e);
                             continue;
                         }
                     };
@@ -538,12 +601,22 @@ impl Lexer {
                 // Strings
                 '"' | '$' => {
                     let format = c == '$';
-                    if format && self.next_char_exact(' ') {
+                    // Lines starting with `$$ ` are raw, so they have no escapes or format arguments
+                    let mut raw = format && self.next_chars_exact("$ ");
+                    if raw || (format && self.next_char_exact(' ')) {
                         // Multiline strings
                         let mut start = start;
                         loop {
-                            let inner = self.parse_string_contents(start, None);
-                            let string = parse_format_fragments(&inner);
+                            let string = if raw {
+                                let mut line = String::new();
+                                while let Some(c) = self.next_char_if(|c| !"\r\n".contains(c)) {
+                                    line.push(c);
+                                }
+                                vec![nfc(line)]
+                            } else {
+                                let inner = self.parse_string_contents(None);
+                                parse_format_fragments(&inner)
+                            };
                             self.end(MultilineString(string), start);
                             let checkpoint = self.loc;
                             while self.next_char_exact('\r') {}
@@ -553,7 +626,12 @@ impl Lexer {
                                     .is_some()
                                 {}
                                 start = self.loc;
+                                if self.next_chars_exact("$$ ") {
+                                    raw = true;
+                                    continue;
+                                }
                                 if self.next_chars_exact("$ ") {
+                                    raw = false;
                                     continue;
                                 }
                             }
@@ -569,7 +647,7 @@ impl Lexer {
                         );
                     }
                     // Single-line strings
-                    let inner = self.parse_string_contents(start, Some('"'));
+                    let inner = self.parse_string_contents(Some('"'));
                     if !self.next_char_exact('"') {
                         self.errors.push(
                             self.end_span(start)
@@ -588,7 +666,9 @@ impl Lexer {
                 c if is_ident_char(c) || c == '&' => {
                     let mut ident = c.to_string();
                     // Collect characters
-                    while let Some(c) = self.next_char_if(is_ident_char) {
+                    while let Some(c) =
+                        self.next_char_if(|c| is_ident_char(c) || is_combining_mark(c))
+                    {
                         ident.push(c);
                     }
                     // Try to parse as primitives
@@ -661,6 +741,7 @@ impl Lexer {
     }
     fn number(&mut self, init: char) -> bool {
         // Whole part
+        let whole_start = self.loc;
         let mut got_digit = false;
         while self.next_char_if(|c| c.is_ascii_digit()).is_some() {
             got_digit = true;
@@ -668,6 +749,22 @@ impl Lexer {
         if !init.is_ascii_digit() && !got_digit {
             return false;
         }
+        // Hexadecimal and binary
+        let whole = self.chars_since(whole_start);
+        if (init == '0' && whole.is_empty()) || (!init.is_ascii_digit() && whole == ['0']) {
+            let before_radix = self.loc;
+            if let Some(c) = self.next_char_if(|c| "xXbB".contains(c)) {
+                let radix = if "xX".contains(c) { 16 } else { 2 };
+                let mut got_digit = false;
+                while self.next_char_if(|c| c.is_digit(radix)).is_some() {
+                    got_digit = true;
+                }
+                if got_digit {
+                    return true;
+                }
+                self.loc = before_radix;
+            }
+        }
         // Fractional part
         let before_dot = self.loc;
         if self.next_char_exact('.') {
@@ -693,50 +790,66 @@ impl Lexer {
         }
         true
     }
-    fn character(
-        &mut self,
-        escaped: &mut bool,
-        escape_char: Option<char>,
-    ) -> Result<Option<char>, char> {
-        let Some(c) =
-            self.next_char_if(|c| !"\r\n".contains(c) && (Some(c) != escape_char || *escaped))
-        else {
+    /// Lex a character of a character or string literal, handling escape sequences
+    ///
+    /// Invalid escape sequences are reported with the span of just the sequence.
+    fn character(&mut self, escape_char: Option<char>) -> Result<Option<char>, Sp<LexError>> {
+        let start = self.loc;
+        let Some(c) = self.next_char_if(|c| !"\r\n".contains(c) && Some(c) != escape_char) else {
             return Ok(None);
         };
-        Ok(Some(if *escaped {
-            *escaped = false;
-            match c {
-                'n' => '\n',
-                'r' => '\r',
-                't' => '\t',
-                '0' => '\0',
-                '\\' => '\\',
-                '"' => '"',
-                '\'' => '\'',
-                '_' => char::MAX,
-                c => return Err(c),
+        if c != '\\' {
+            return Ok(Some(c));
+        }
+        let Some(c) = self.next_char_if(|c| !"\r\n".contains(c)) else {
+            return Ok(None);
+        };
+        Ok(Some(match c {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            '\\' => '\\',
+            '"' => '"',
+            '\'' => '\'',
+            '_' => char::MAX,
+            'u' => {
+                let valid = self.next_char_exact('{') && {
+                    let mut digits = 0;
+                    while digits < 6 && self.next_char_if(|c| c.is_ascii_hexdigit()).is_some() {
+                        digits += 1;
+                    }
+                    digits > 0 && self.next_char_exact('}')
+                };
+                let escape: String = self.chars_since(start).iter().collect();
+                let code = escape
+                    .strip_prefix("\\u{")
+                    .and_then(|s| s.strip_suffix('}'))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok());
+                match code.and_then(char::from_u32) {
+                    Some(c) if valid => c,
+                    _ => {
+                        return Err(self.end_span(start).sp(LexError::InvalidEscape(escape)));
+                    }
+                }
+            }
+            c => {
+                return Err(self
+                    .end_span(start)
+                    .sp(LexError::InvalidEscape(format!("\\{c}"))))
             }
-        } else if c == '\\' {
-            *escaped = true;
-            return self.character(escaped, escape_char);
-        } else {
-            c
         }))
     }
-    fn parse_string_contents(&mut self, start: Loc, escape_char: Option<char>) -> String {
+    fn parse_string_contents(&mut self, escape_char: Option<char>) -> String {
         let mut string = String::new();
-        let mut escaped = false;
         loop {
-            match self.character(&mut escaped, escape_char) {
+            match self.character(escape_char) {
                 Ok(Some(c)) => string.push(c),
                 Ok(None) => break,
-                Err(e) => {
-                    self.errors
-                        .push(self.end_span(start).sp(LexError::InvalidEscape(e)));
-                }
+                Err(e) => self.errors.push(e),
             }
         }
-        string
+        nfc(string)
     }
 }
 
@@ -757,6 +870,17 @@ fn parse_format_fragments(s: &str) -> Vec<String> {
     frags
 }
 
+/// Normalize a string to Unicode Normalization Form C
+///
+/// This makes text that looks the same compare equal, even if it was typed differently.
+pub(crate) fn nfc(s: String) -> String {
+    if is_nfc(&s) {
+        s
+    } else {
+        s.nfc().collect()
+    }
+}
+
 pub fn is_ident_char(c: char) -> bool {
     c.is_alphabetic() && !"ⁿₙηπτ".contains(c)
 }
@@ -1,7 +1,17 @@
 /*!
 The Uiua programming language
 
-This currently exists as a library only to reserve the name on crates.io.
+A [`Uiua`] runtime loads and runs code, and values can be passed between it and Rust.
+
+```
+use uiua::{Uiua, Value};
+
+let mut uiua = Uiua::with_native_sys();
+uiua.bind_value("X", 5).unwrap();
+let stack = uiua.run("+1 X").unwrap();
+assert_eq!(stack, [Value::from(6)]);
+```
+
 The current API should be considered deeply unstable.
 */
 
@@ -14,22 +24,42 @@ mod check;
 mod compile;
 mod cowslice;
 mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod format;
 pub mod function;
 mod grid_fmt;
+mod hash;
 pub mod lex;
 pub mod lsp;
+mod native;
+mod npy;
 pub mod parse;
+mod plot;
 pub mod primitive;
 #[doc(hidden)]
 pub mod profile;
 pub mod run;
+#[cfg(feature = "serde")]
+mod serialize;
 mod sys;
+mod sys_async;
+#[cfg(feature = "web")]
+mod sys_js;
+mod sys_sandbox;
+mod sys_test;
+mod sys_vfs;
+mod time;
 pub mod value;
 
 use std::sync::Arc;
 
-pub use {error::*, run::Uiua, sys::*};
+#[cfg(feature = "web")]
+pub use sys_js::JsBackend;
+pub use {
+    error::*, native::*, npy::*, run::Uiua, sys::*, sys_async::*, sys_sandbox::*,
+    sys_test::TestBackend, sys_vfs::*, value::Value,
+};
 
 pub type Ident = Arc<str>;
 
@@ -75,3 +105,679 @@ fn no_dbgs() {
         }
     });
 }
+#[test]
+fn steps() {
+    let mut env = Uiua::with_native_sys();
+    env.load_str("F ← +1×2").unwrap();
+    let f = env.all_bindings_in_scope()["F"]
+        .clone()
+        .into_function()
+        .unwrap();
+    env.push(3);
+    env.begin_steps(f).unwrap();
+    let mut steps = 1;
+    while env.step().unwrap() {
+        steps += 1;
+    }
+    assert_eq!(steps, 4);
+    assert_eq!(env.take_stack(), [value::Value::from(7)]);
+}
+
+#[test]
+fn modifier_signature_errors() {
+    for code in ["∵(..) [1 2]", "⊞(⊃+-) [1 2] [3 4]", "∺(1) 1 [1 2]"] {
+        let mut env = Uiua::with_native_sys();
+        let err = env.load_str(code).unwrap_err();
+        assert!(
+            err.message().contains("function must"),
+            "unexpected error for {code}: {err}"
+        );
+    }
+}
+
+#[test]
+fn multiple_errors() {
+    let mut env = Uiua::with_native_sys();
+    let err = env.load_str("F ← +1 Foo\nF 1\n+Bar 2").unwrap_err();
+    let UiuaError::Multi(errors) = err else {
+        panic!("expected multiple errors, got {err}");
+    };
+    assert_eq!(errors.len(), 2);
+}
+
+#[test]
+fn binding_warnings() {
+    let mut env = Uiua::with_native_sys();
+    env.load_str_path("X ← 1\nY ← 2\nY ← 3\n+X Y", "test.ua")
+        .unwrap();
+    let messages: Vec<_> = (env.take_diagnostics().into_iter())
+        .map(|diag| diag.message)
+        .collect();
+    assert_eq!(messages, ["`Y` is never used"]);
+}
+
+#[test]
+fn test_results() {
+    let code = "⍤\"x\" 1\n~~~\n⍤\"a\" =1 1\n⍤\"b\" =1 2\n∵(⍤\"c\" =1) [1 1 1]\n~~~";
+    let mut env = Uiua::with_native_sys().with_mode(run::RunMode::Test);
+    env.load_str(code).unwrap();
+    // Each assertion is counted once, even if it passes many times
+    let results = env.take_test_results();
+    assert_eq!(results.passed, 2);
+    assert_eq!(results.failures.len(), 1);
+    let failure = results.failures.into_iter().next().unwrap();
+    assert_eq!(failure.value(), value::Value::from("b"));
+    // Assertions are only counted in test scopes run in test mode
+    let mut env = Uiua::with_native_sys().with_mode(run::RunMode::All);
+    env.load_str(code).unwrap_err();
+    assert_eq!(env.take_test_results().passed, 0);
+}
+
+#[test]
+fn profile() {
+    use profile::ProfileTarget;
+    let mut env = Uiua::with_native_sys().with_profiling(true);
+    env.load_str("F ← +1\nF F F 0 ⍏ [3 1 2]").unwrap();
+    let profile = env.profile().unwrap();
+    let calls = |target: ProfileTarget| {
+        (profile.entries().into_iter())
+            .find(|(t, _)| **t == target)
+            .map_or(0, |(_, entry)| entry.calls)
+    };
+    assert_eq!(calls(ProfileTarget::Function("F".into())), 3);
+    assert_eq!(
+        calls(ProfileTarget::Primitive(primitive::Primitive::Rise)),
+        1
+    );
+}
+/// Values are moved between threads by `spawn` and by multi-threaded embedders
+#[test]
+fn send_sync() {
+    fn check<T: Send + Sync>() {}
+    check::<value::Value>();
+    check::<Uiua>();
+    check::<UiuaError>();
+}
+
+#[test]
+fn call_traces() {
+    let mut env = Uiua::with_native_sys();
+    let err = (env.load_str("F ← ∵(÷∶\"a\")\nH ← ≡F\nH [1_2 3_4]")).unwrap_err();
+    let UiuaError::Traced { trace, .. } = err else {
+        panic!("expected a traced error, got {err}");
+    };
+    let names: Vec<_> = (trace.iter())
+        .filter_map(|frame| match &frame.id {
+            function::FunctionId::Named(name) => Some(name.as_ref()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(names, ["F", "H"]);
+}
+
+#[test]
+fn recursion_through_modifiers() {
+    // Debug builds need as much native stack as a main thread has to reach the limit
+    let thread = std::thread::Builder::new().stack_size(8 << 20).spawn(|| {
+        let mut env = Uiua::with_native_sys();
+        let err = env
+            .load_str("F ← |1 ?(|1 F+1)(|1 F+1) =0 .\nF 0")
+            .unwrap_err();
+        assert!(err.message().contains("calls deep"), "{err}");
+        let mut env = Uiua::with_native_sys();
+        env.load_str("F ← |1 ?(|1 F+1)(|1 F+1) =0 .\n⍣(F 0)(1;)")
+            .unwrap();
+        assert_eq!(env.take_stack(), [value::Value::from(1)]);
+    });
+    thread.unwrap().join().unwrap();
+}
+
+#[test]
+fn call_depth_limit() {
+    for code in ["F ← |1 F+1\nF 0", "F ← |1 ?(|1 F+1)(|1 F+1) =0 .\nF 0"] {
+        let mut env = Uiua::with_native_sys().with_call_depth_limit(10);
+        let err = env.load_str(code).unwrap_err();
+        assert!(err.message().contains("more than 10 calls deep"), "{err}");
+        let mut env = Uiua::with_native_sys().with_call_depth_limit(10);
+        let caught = code.replace("\nF 0", "\n⍣(F 0)(1;)");
+        env.load_str(&caught).unwrap();
+        assert_eq!(env.take_stack(), [value::Value::from(1)]);
+    }
+}
+
+#[test]
+fn instruction_limit() {
+    for code in ["⍥(+1)1000 0", "F ← |1 F+1\nF 0"] {
+        let mut env = Uiua::with_native_sys().with_instruction_limit(100);
+        let err = env.load_str(code).unwrap_err();
+        assert!(
+            err.message().contains("more than 100 instructions"),
+            "{err}"
+        );
+    }
+    // Catching the error does not let the program keep running
+    let mut env = Uiua::with_native_sys().with_instruction_limit(100);
+    let err = env.load_str("⍣(⍥(+1)1000 0)(1;)").unwrap_err();
+    assert!(
+        err.message().contains("more than 100 instructions"),
+        "{err}"
+    );
+    // Imports share the limit of the input that imports them
+    let backend = TestBackend::default().with_file("loop.ua", "⍥(+1)60 0");
+    let mut env = Uiua::with_backend(backend).with_instruction_limit(100);
+    let err = env.load_str("⍥(+1)60 0\n&i \"loop.ua\"").unwrap_err();
+    assert!(
+        err.message().contains("more than 100 instructions"),
+        "{err}"
+    );
+}
+
+#[test]
+fn constant_pool() {
+    use value::Value;
+    let mut env = Uiua::with_native_sys();
+    env.load_str("\"abc\" \"abc\"").unwrap();
+    env.load_str("F ← ⊂\"abc\"\nF \"def\"").unwrap();
+    let stack = env.take_stack();
+    let [Value::Char(a), Value::Char(b), Value::Char(c)] = stack.as_slice() else {
+        panic!("unexpected stack {stack:?}");
+    };
+    assert!(a.data.ptr_eq(&b.data));
+    assert!(!a.data.ptr_eq(&c.data));
+}
+
+#[test]
+fn superinstructions() {
+    use function::Instr;
+    let mut env = Uiua::with_native_sys();
+    env.load_str("F ← -1 ×2").unwrap();
+    let f = env.all_bindings_in_scope()["F"]
+        .clone()
+        .into_function()
+        .unwrap();
+    assert!(f
+        .instrs
+        .iter()
+        .all(|instr| matches!(instr, Instr::Super { .. })));
+    env.load_str("G ← ⍜F(+1)\nG 3").unwrap();
+    assert_eq!(env.take_stack(), [value::Value::from(3.5)]);
+}
+
+#[test]
+fn bindings_resolved_at_compile_time() {
+    use function::Instr;
+    let mut env = Uiua::with_native_sys();
+    env.load_str("X ← 5\nY ← +1 X\nF ← ×Y").unwrap();
+    let f = env.all_bindings_in_scope()["F"]
+        .clone()
+        .into_function()
+        .unwrap();
+    let [Instr::Super { first, .. }] = f.instrs.as_slice() else {
+        panic!("unexpected instructions {:?}", f.instrs);
+    };
+    assert_eq!(first.as_push(), Some(&value::Value::from(6)));
+}
+
+#[test]
+fn panics_become_errors() {
+    use function::{DynamicFunction, Function, FunctionId, Instr, Signature};
+    let mut env = Uiua::with_native_sys();
+    let f = Function::new(
+        FunctionId::Main,
+        [Instr::Dynamic(DynamicFunction {
+            id: 0,
+            f: Arc::new(|_| panic!("oh no")),
+            signature: Signature::new(0, 0),
+        })],
+        Signature::new(0, 0),
+    );
+    let err = env.call_function(f).unwrap_err();
+    assert!(err.message().contains("oh no"), "{err}");
+    env.load_str("1").unwrap();
+    assert_eq!(env.take_stack(), [value::Value::from(1)]);
+}
+
+#[test]
+fn binding_docs() {
+    let mut env = Uiua::with_native_sys();
+    env.load_str("# Add one\n# to a number\n#\n# Very useful\nInc ← +1\n\n# Not docs\n+1 2\nX ← 2")
+        .unwrap();
+    assert_eq!(
+        env.binding_docs("Inc"),
+        Some("Add one to a number\n\nVery useful")
+    );
+    assert_eq!(env.binding_docs("X"), None);
+}
+
+#[test]
+fn format_keeps_comments() {
+    let input = "\
+# Doubles
+F ← (
+  +1 # inc
+  # alone
+  ×2
+)
+X ← [1 # one
+     2]
+
+# end
+";
+    let config = format::FormatConfig::default();
+    let output = format::format_str(input, &config).unwrap().output;
+    assert_eq!(output, input);
+}
+
+#[test]
+fn zero_argument_bindings() {
+    let mut env = Uiua::with_native_sys();
+    env.load_str_path("X ← ⚂\nY ← |0.1 ⚂\n≠Y Y\nX", "test.ua")
+        .unwrap();
+    let stack = env.take_stack();
+    assert_eq!(stack[0], value::Value::from(1));
+    let kinds: Vec<_> = (env.take_diagnostics().into_iter())
+        .map(|diag| diag.kind)
+        .collect();
+    assert_eq!(kinds, [DiagnosticKind::Advice]);
+}
+
+#[test]
+fn error_provenance() {
+    let mut env = Uiua::with_native_sys();
+    let err = env.load_str("F ← +1_2\nF 1_2_3").unwrap_err();
+    let Some(lex::Span::Code(span)) = err.span() else {
+        panic!("expected a code span, got {err}");
+    };
+    assert_eq!(span.as_str(), "+");
+    assert_eq!(span.start.line, 1);
+    let instr = err.instr().expect("error should have an instruction");
+    let Some(lex::Span::Code(instr_span)) = env.instr_span(instr) else {
+        panic!("instruction should have a span");
+    };
+    assert_eq!(instr_span.byte_range(), span.byte_range());
+
+    let (items, _, _) = parse::parse("X ← +1 2\n[1 2]", None);
+    let spans: Vec<_> = (items.iter())
+        .map(|item| item.span().unwrap().as_str().to_string())
+        .collect();
+    assert_eq!(spans, ["X ← +1 2", "[1 2]"]);
+}
+
+#[test]
+fn format_glyphs_and_spaces() {
+    let config = format::FormatConfig::default();
+    let format = |input: &str| format::format_str(input, &config).unwrap().output;
+    assert_eq!(format("reverse range 5 # c"), "⇌⇡5 # c\n");
+    assert_eq!(format("+ 1 2\n!= 1 2\nneg 3"), "+1 2\n≠1 2\n¯ 3\n");
+    assert_eq!(format("< = 1 2"), "< =1 2\n");
+    let config = config.with_remove_glyph_spaces(false);
+    let output = format::format_str("reverse range 5", &config)
+        .unwrap()
+        .output;
+    assert_eq!(output, "⇌ ⇡ 5\n");
+}
+
+#[test]
+fn format_config_options() {
+    let format = |input: &str, config: format::FormatConfig| {
+        format::format_str(input, &config).unwrap().output
+    };
+    let config = format::FormatConfig::default;
+    assert_eq!(
+        format("X ← ⇌⇡5\n∵(×2) X", config().with_glyphs(false)),
+        "X ← reverse range 5\neach(multiply 2) X\n"
+    );
+    assert_eq!(
+        format(
+            "X ← 1\nLong ← 2\n\nY ← 3",
+            config().with_align_bindings(true)
+        ),
+        "X    ← 1\nLong ← 2\n\nY ← 3\n"
+    );
+    assert_eq!(
+        format("X ← [1 2 3 4 5 6 7 8]", config().with_max_line_width(14)),
+        "X ← [1 2 3 4\n     5 6 7 8]\n"
+    );
+    assert_eq!(format("X ← [1 2]", config()), "X ← [1 2]\n");
+}
+
+#[test]
+fn lookalike_characters() {
+    use value::Value;
+    let mut env = Uiua::with_native_sys();
+    env.load_str("− 1 5\n✕ 2 3\n‾3\n\u{200B}⧻\"e\u{301}\"")
+        .unwrap();
+    let stack = env.take_stack();
+    let expected: [Value; 4] = [4.into(), 6.into(), (-3).into(), 1.into()];
+    assert_eq!(stack, expected);
+    env.load_str("Cafe\u{301} ← 2\nCafé").unwrap();
+    assert_eq!(env.take_stack(), [Value::from(2)]);
+    let err = env.load_str("“hi”").unwrap_err();
+    assert!(err.to_string().contains("did you mean '\"'"), "{err}");
+    let config = format::FormatConfig::default();
+    let output = format::format_str("− 1 ‾2", &config).unwrap().output;
+    assert_eq!(output, "-1 ¯2\n");
+}
+
+#[test]
+fn unknown_identifier_suggestions() {
+    let mut env = Uiua::with_native_sys();
+    let err = env.load_str("revrse 1_2").unwrap_err();
+    assert!(
+        err.to_string().contains("Did you mean `reverse` ⇌?"),
+        "{err}"
+    );
+    let err = env.load_str("Count ← 5\n+1 Cuont").unwrap_err();
+    assert!(err.to_string().contains("Did you mean `Count`?"), "{err}");
+    let err = env.load_str("Xyzzy").unwrap_err();
+    assert!(!err.to_string().contains("Did you mean"), "{err}");
+}
+
+#[test]
+fn span_kinds() {
+    use lsp::{spans, PrimHighlight, SpanKind};
+    let input = "F ← (+1 ⊙∘)\n[1 \"a\"] # c";
+    let kinds: Vec<(&str, SpanKind)> = (spans(input).into_iter())
+        .filter(|sp| sp.value != SpanKind::Whitespace)
+        .map(|sp| (&input[sp.span.byte_range()], sp.value))
+        .collect();
+    use primitive::Primitive::*;
+    assert_eq!(
+        kinds,
+        [
+            ("F", SpanKind::BindingName),
+            ("(", SpanKind::Delimiter),
+            ("+", SpanKind::Primitive(Add)),
+            ("1", SpanKind::Number),
+            ("⊙", SpanKind::Primitive(Dip)),
+            ("∘", SpanKind::Primitive(Identity)),
+            (")", SpanKind::Delimiter),
+            ("[", SpanKind::Delimiter),
+            ("1", SpanKind::Number),
+            ("\"a\"", SpanKind::String),
+            ("]", SpanKind::Delimiter),
+            ("# c", SpanKind::Comment),
+        ]
+    );
+    assert_eq!(PrimHighlight::new(Add), PrimHighlight::Dyadic);
+    assert_eq!(PrimHighlight::new(Dip), PrimHighlight::Modifier(1));
+    assert_eq!(PrimHighlight::new(Identity), PrimHighlight::Stack);
+}
+
+#[test]
+fn ast_visitors() {
+    use ast::{walk_word, Visit, VisitMut, Word};
+    use lex::Sp;
+    use primitive::Primitive;
+    struct CountPrims(usize);
+    impl Visit for CountPrims {
+        fn visit_word(&mut self, word: &Sp<Word>) {
+            if let Word::Primitive(_) = &word.value {
+                self.0 += 1;
+            }
+            walk_word(self, word);
+        }
+    }
+    struct AddToSub;
+    impl VisitMut for AddToSub {
+        fn visit_word_mut(&mut self, word: &mut Sp<Word>) {
+            if let Word::Primitive(prim @ Primitive::Add) = &mut word.value {
+                *prim = Primitive::Sub;
+            }
+            ast::walk_word_mut(self, word);
+        }
+    }
+    let (mut items, errors, _) = parse::parse("F ← (+1 [+2 3])\n+ F 4", None);
+    assert!(errors.is_empty());
+    let mut count = CountPrims(0);
+    for item in &items {
+        count.visit_item(item);
+    }
+    assert_eq!(count.0, 3);
+    for item in &mut items {
+        AddToSub.visit_item_mut(item);
+    }
+    let config = format::FormatConfig::default();
+    let output = format::format_items(&items, &config).output;
+    assert_eq!(output, "F ← (-1 [-2 3])\n-F 4\n");
+}
+
+#[test]
+fn shebang_line() {
+    let mut env = Uiua::with_native_sys();
+    env.load_str("#!/usr/bin/env uiua\nF ← +1\nF 2").unwrap();
+    assert_eq!(env.take_stack(), [value::Value::from(3)]);
+    assert_eq!(env.binding_docs("F"), None);
+}
+
+#[test]
+fn line_continuation_diagnostics() {
+    let parse = |input: &str| parse::parse(input, None);
+    let (_, errors, _) = parse("X ← (+1\n×2\nX 3");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].value.to_string(), "Unclosed `(`");
+    assert_eq!(errors[0].span.start.col, 5);
+    let (_, errors, diagnostics) = parse("∵\n+1 [1 2]");
+    assert!(errors.is_empty());
+    let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+    assert_eq!(messages.len(), 1, "{messages:?}");
+    assert!(messages[0].starts_with("`∵` needs a function"));
+    // `X ←` binds the top of the stack
+    let (_, errors, diagnostics) = parse("5\nX ←\n+1 X");
+    assert!(errors.is_empty() && diagnostics.is_empty());
+    let (_, errors, diagnostics) = parse("X ← (\n  +1\n  ×2\n)\n∵(+1) [1 2]");
+    assert!(errors.is_empty() && diagnostics.is_empty());
+}
+
+#[test]
+fn printed_instrs() {
+    let mut env = Uiua::with_backend(TestBackend::default()).print_instrs(true);
+    env.load_str("F ← (+1)\nF 2").unwrap();
+    let backend = env.downcast_backend::<TestBackend>().unwrap();
+    let stderr = backend.stderr();
+    assert!(stderr.starts_with("`F` |1.1\n"), "{stderr}");
+    assert!(stderr.contains("\nmain |0.1\n"), "{stderr}");
+}
+
+#[test]
+fn output_mode() {
+    use run::OutputMode;
+    let mut env = Uiua::with_backend(TestBackend::default());
+    env.load_str(r#"↯2_2⇡4 "hi""#).unwrap();
+    let stack = env.take_stack();
+    assert!(!env.pretty_stdout() && !env.pretty_stderr());
+    assert_eq!(env.show_value(&stack[1]), "[0 1]\n[2 3]");
+    assert_eq!(env.show_value(&stack[0]), "hi");
+    let env = env.with_output_mode(OutputMode::Pretty);
+    assert!(env.pretty_stdout() && env.pretty_stderr());
+    assert_eq!(env.show_value(&stack[1]), stack[1].show());
+    assert_eq!(env.show_value(&stack[0]), r#""hi""#);
+}
+
+#[test]
+fn embedding() {
+    use function::Signature;
+    let mut env = Uiua::with_backend(TestBackend::default());
+    env.bind_value("Xs", Value::from(vec![1.0, 2.0, 3.0]))
+        .unwrap();
+    env.bind_function("Double", Signature::new(1, 1), |env| {
+        let x = env.pop(1)?.as_num(env, "Expected a number")?;
+        env.push(x * 2.0);
+        Ok(())
+    })
+    .unwrap();
+    assert_eq!(
+        env.run("/+Xs Double 4").unwrap(),
+        [8.0, 6.0].map(Value::from)
+    );
+    env.load_str("Y ← ∵Double Xs").unwrap();
+    assert_eq!(
+        env.binding_value("Y"),
+        Some(Value::from(vec![2.0, 4.0, 6.0]))
+    );
+    assert!(env.bind_value("not a name", 1).is_err());
+    assert!(env.run("Missing").is_err());
+}
+
+#[test]
+fn native_functions() {
+    let mut env = Uiua::with_backend(TestBackend::default());
+    env.bind_native("Hyp", |a: f64, b: f64| a.hypot(b)).unwrap();
+    env.bind_native("Greet", |name: String| format!("Hello, {name}!"))
+        .unwrap();
+    env.bind_native("MinMax", |xs: Vec<f64>| {
+        let min = xs.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = xs.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        (min, max)
+    })
+    .unwrap();
+    env.bind_native("Nth", |n: usize, xs: Vec<f64>| {
+        xs.get(n).copied().ok_or_else(|| format!("No element {n}"))
+    })
+    .unwrap();
+    env.bind_native("Answer", || 42).unwrap();
+    let stack = env
+        .run(r#"Answer Nth 1 [4 5 6] MinMax [3 1 2] Greet "World" Hyp 3 4"#)
+        .unwrap();
+    let expected: [Value; 6] = [
+        5.into(),
+        "Hello, World!".into(),
+        3.into(),
+        1.into(),
+        5.into(),
+        42.into(),
+    ];
+    assert_eq!(stack, expected);
+    let err = env.run("Nth 5 [1 2]").unwrap_err();
+    assert!(err.message().contains("No element 5"), "{err}");
+    let err = env.run(r#"Hyp "a" 1"#).unwrap_err();
+    assert!(err.message().contains("Argument must be a number"), "{err}");
+}
+
+#[test]
+fn value_conversions() {
+    let mut env = Uiua::with_backend(TestBackend::default());
+    let mut run = |code: &str| env.run(code).unwrap().pop().unwrap();
+    assert_eq!(f64::try_from(run("1.5")), Ok(1.5));
+    assert_eq!(i64::try_from(run("¯3")), Ok(-3));
+    assert_eq!(bool::try_from(run("=1 1")), Ok(true));
+    assert_eq!(String::try_from(run(r#""abc""#)), Ok("abc".into()));
+    assert_eq!(
+        Vec::<f64>::try_from(run("[1 2 3]")),
+        Ok(vec![1.0, 2.0, 3.0])
+    );
+    assert_eq!(
+        Vec::<Vec<f64>>::try_from(run("↯2_2⇡4")),
+        Ok(vec![vec![0.0, 1.0], vec![2.0, 3.0]])
+    );
+    assert_eq!(
+        <(Vec<usize>, Vec<f64>)>::try_from(run("↯2_1_2⇡4")),
+        Ok((vec![2, 1, 2], vec![0.0, 1.0, 2.0, 3.0]))
+    );
+    let table = Value::try_from(vec![vec![1.0, 2.0], vec![3.0, 4.0]]).unwrap();
+    assert_eq!(table, run("[1_2 3_4]"));
+    assert_eq!(
+        Value::try_from((vec![3], vec![1.0, 2.0, 3.0])),
+        Ok(run("[1 2 3]"))
+    );
+    assert_eq!(Value::from(7i64), run("7"));
+    for (result, message) in [
+        (
+            f64::try_from(run("[1 2]")).map(drop),
+            "but the value has shape [2]",
+        ),
+        (i64::try_from(run("1.5")).map(drop), "but it is 1.5"),
+        (bool::try_from(run("2")).map(drop), "but it is 2"),
+        (
+            String::try_from(run("5")).map(drop),
+            "but the value is a number array",
+        ),
+        (Vec::<f64>::try_from(run("@a")).map(drop), "character array"),
+        (
+            Value::try_from(vec![vec![1.0], vec![]]).map(drop),
+            "row 0 has 1 elements and row 1 has 0",
+        ),
+        (
+            Value::try_from((vec![2, 2], vec![1.0])).map(drop),
+            "there are 1 numbers",
+        ),
+        (
+            Value::try_from((vec![usize::MAX, 2], vec![])).map(drop),
+            "too many elements",
+        ),
+    ] {
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains(message), "{err}");
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    use std::mem::discriminant;
+    let mut env = Uiua::with_backend(TestBackend::default());
+    let values = env
+        .run(r#"↯2_3⇡6 [1.5 ¯2] ↯0_2 0 ⊢[1] "hello" ↯2_2"abcd" {1 "ab" {2}} =1 [1 2]"#)
+        .unwrap();
+    for value in values {
+        let json = serde_json::to_string(&value).unwrap();
+        let round_trip: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip, value, "{json}");
+        assert_eq!(round_trip.shape(), value.shape(), "{json}");
+        assert_eq!(discriminant(&round_trip), discriminant(&value), "{json}");
+        assert_eq!(serde_json::to_string(&round_trip).unwrap(), json);
+    }
+    let json = serde_json::to_string(&env.run("[1 2]").unwrap()[0]).unwrap();
+    assert_eq!(json, r#"{"num":{"shape":[2],"data":[1.0,2.0]}}"#);
+    let err = serde_json::from_str::<Value>(r#"{"num":{"shape":[2,2],"data":[1]}}"#).unwrap_err();
+    assert!(
+        err.to_string().contains("Shape [2 × 2] has 4 elements"),
+        "{err}"
+    );
+    let json = r#"{"num":{"shape":[4294967296,4294967296],"data":[]}}"#;
+    let err = serde_json::from_str::<Value>(json).unwrap_err();
+    assert!(err.to_string().contains("too many elements"), "{err}");
+    let f = env.run("(+1)").unwrap().pop().unwrap();
+    assert!(serde_json::to_string(&f).is_err());
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn c_api() {
+    use ffi::*;
+    use std::ffi::{CStr, CString};
+    unsafe {
+        let interp = uiua_new();
+        let code = CString::new("X ← ↯2_3 [0.5 1 2 3 4 5]").unwrap();
+        assert_eq!(uiua_eval(interp, code.as_ptr()), 0);
+        assert!(uiua_error(interp).is_null());
+        assert_eq!(uiua_result_count(interp), 0);
+
+        let code = CString::new(r#"{1} =1 [1 2] "hi" X"#).unwrap();
+        assert_eq!(uiua_eval(interp, code.as_ptr()), 0);
+        assert_eq!(uiua_result_count(interp), 4);
+        let mut array = std::mem::zeroed::<UiuaArray>();
+        assert!(uiua_result(interp, 0, &mut array));
+        assert_eq!(array.element_type, UiuaElementType::Num);
+        assert_eq!(std::slice::from_raw_parts(array.shape, array.rank), [2, 3]);
+        let data = std::slice::from_raw_parts(array.data as *const f64, array.len);
+        assert_eq!(data, [0.5, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert!(uiua_result(interp, 2, &mut array));
+        assert_eq!(array.element_type, UiuaElementType::Byte);
+        assert!(uiua_result(interp, 3, &mut array));
+        assert_eq!(array.element_type, UiuaElementType::Func);
+        assert!(array.data.is_null());
+        assert!(uiua_result(interp, 1, &mut array));
+        assert_eq!(array.element_type, UiuaElementType::Char);
+        let data = std::slice::from_raw_parts(array.data as *const u32, array.len);
+        assert_eq!(data, ['h' as u32, 'i' as u32]);
+        assert!(!uiua_result(interp, 4, &mut array));
+
+        let code = CString::new("+\"a\" \"b\"").unwrap();
+        assert_eq!(uiua_eval(interp, code.as_ptr()), 1);
+        assert_eq!(uiua_result_count(interp), 0);
+        let err = CStr::from_ptr(uiua_error(interp)).to_str().unwrap();
+        assert!(!err.is_empty());
+        uiua_free(interp);
+    }
+}
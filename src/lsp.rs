@@ -4,7 +4,7 @@ use crate::{
     ast::{Item, Word},
     lex::{CodeSpan, Loc, Sp},
     parse::parse,
-    primitive::Primitive,
+    primitive::{PrimClass, Primitive},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -15,10 +15,48 @@ pub enum SpanKind {
     Comment,
     Strand,
     Ident,
+    /// The name of a binding where it is bound
+    BindingName,
     Signature,
+    /// A bracket, parenthesis, or curly brace of a function or array
+    Delimiter,
     Whitespace,
 }
 
+/// How a primitive should be highlighted, based on how many arguments it takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimHighlight {
+    Stack,
+    Noadic,
+    Monadic,
+    Dyadic,
+    Triadic,
+    Variadic,
+    /// A modifier and how many functions it takes
+    Modifier(usize),
+}
+
+impl PrimHighlight {
+    pub fn new(prim: Primitive) -> Self {
+        if let Some(margs) = prim.modifier_args() {
+            PrimHighlight::Modifier(margs as usize)
+        } else if prim.class() == PrimClass::Stack {
+            PrimHighlight::Stack
+        } else {
+            match prim.args() {
+                Some(0) => PrimHighlight::Noadic,
+                Some(1) => PrimHighlight::Monadic,
+                Some(2) => PrimHighlight::Dyadic,
+                Some(3) => PrimHighlight::Triadic,
+                _ => PrimHighlight::Variadic,
+            }
+        }
+    }
+}
+
+/// Classify the code in some input for highlighting
+///
+/// The spans are in order and do not overlap.
 pub fn spans(input: &str) -> Vec<Sp<SpanKind>> {
     let (items, _, _) = parse(input, None);
     items_spans(&items)
@@ -31,6 +69,7 @@ fn items_spans(items: &[Item]) -> Vec<Sp<SpanKind>> {
             Item::Scoped { items, .. } => spans.extend(items_spans(items)),
             Item::Words(words) => spans.extend(words_spans(words)),
             Item::Binding(binding) => {
+                spans.push(binding.name.span.clone().sp(SpanKind::BindingName));
                 if let Some(sig) = &binding.signature {
                     spans.push(sig.span.clone().sp(SpanKind::Signature));
                 }
@@ -53,7 +92,9 @@ fn words_spans(words: &[Sp<Word>]) -> Vec<Sp<SpanKind>> {
             Word::MultilineString(lines) => {
                 spans.extend((lines.iter()).map(|line| line.span.clone().sp(SpanKind::String)))
             }
-            Word::Ident(_) => spans.push(word.span.clone().sp(SpanKind::Ident)),
+            Word::Ident(_) | Word::BindLocal(_) | Word::Placeholder(_) => {
+                spans.push(word.span.clone().sp(SpanKind::Ident))
+            }
             Word::Strand(items) => {
                 for (i, word) in items.iter().enumerate() {
                     let item_spans = words_spans(slice::from_ref(word));
@@ -78,12 +119,20 @@ fn words_spans(words: &[Sp<Word>]) -> Vec<Sp<SpanKind>> {
                     spans.extend(item_spans);
                 }
             }
-            Word::Array(arr) => spans.extend(arr.lines.iter().flat_map(|w| words_spans(w))),
+            Word::Array(arr) => {
+                let [open, close] = delimiter_spans(&word.span);
+                spans.extend(open);
+                spans.extend(arr.lines.iter().flat_map(|w| words_spans(w)));
+                spans.extend(close);
+            }
             Word::Func(func) => {
+                let [open, close] = delimiter_spans(&word.span);
+                spans.extend(open);
                 if let Some(sig) = &func.signature {
                     spans.push(sig.span.clone().sp(SpanKind::Signature));
                 }
                 spans.extend(func.lines.iter().flat_map(|w| words_spans(w)));
+                spans.extend(close);
             }
             Word::Primitive(prim) => spans.push(word.span.clone().sp(SpanKind::Primitive(*prim))),
             Word::Modified(m) => {
@@ -97,6 +146,38 @@ fn words_spans(words: &[Sp<Word>]) -> Vec<Sp<SpanKind>> {
     spans
 }
 
+/// Get the spans of the opening and closing delimiters of a function or array
+fn delimiter_spans(span: &CodeSpan) -> [Option<Sp<SpanKind>>; 2] {
+    let text = span.as_str();
+    let open = text.starts_with(['(', '[', '{']).then(|| {
+        let end = Loc {
+            col: span.start.col + 1,
+            char_pos: span.start.char_pos + 1,
+            byte_pos: span.start.byte_pos + 1,
+            ..span.start
+        };
+        CodeSpan {
+            end,
+            ..span.clone()
+        }
+        .sp(SpanKind::Delimiter)
+    });
+    let close = (text.len() > 1 && text.ends_with([')', ']', '}'])).then(|| {
+        let start = Loc {
+            col: span.end.col - 1,
+            char_pos: span.end.char_pos - 1,
+            byte_pos: span.end.byte_pos - 1,
+            ..span.end
+        };
+        CodeSpan {
+            start,
+            ..span.clone()
+        }
+        .sp(SpanKind::Delimiter)
+    });
+    [open, close]
+}
+
 #[cfg(feature = "lsp")]
 pub use server::run_server;
 
@@ -110,14 +191,16 @@ mod server {
     use super::*;
 
     use crate::{
+        ast::push_doc_line,
         format::{format_str, FormatConfig /*, FormatConfigSource*/},
         lex::Loc,
+        parse::{Parsed, TextEdit as ParseEdit},
         primitive::PrimDocFragment,
         Ident, Uiua,
     };
 
     pub struct LspDoc {
-        pub input: String,
+        pub parsed: Parsed,
         pub spans: Vec<Sp<SpanKind>>,
         pub bindings: BindingsInfo,
     }
@@ -125,16 +208,35 @@ mod server {
     type BindingsInfo = BTreeMap<Sp<Ident>, Arc<BindingInfo>>;
 
     impl LspDoc {
-        fn new(input: String) -> Self {
-            let (items, _, _) = parse(&input, None);
-            let spans = items_spans(&items);
-            let bindings = bindings_info(&items);
+        fn new(input: &str) -> Self {
+            Self::from_parsed(Parsed::new(input, None))
+        }
+        fn from_parsed(parsed: Parsed) -> Self {
+            let spans = items_spans(&parsed.items);
+            let bindings = bindings_info(&parsed.items);
             Self {
-                input,
+                parsed,
                 spans,
                 bindings,
             }
         }
+        fn input(&self) -> &str {
+            self.parsed.input()
+        }
+        /// Apply a change, reparsing only the lines around it
+        fn change(self, change: TextDocumentContentChangeEvent) -> Self {
+            let Some(range) = change.range else {
+                return Self::new(&change.text);
+            };
+            let mut parsed = self.parsed;
+            let input = parsed.input();
+            let range = lsp_pos_to_byte(input, range.start)..lsp_pos_to_byte(input, range.end);
+            parsed.edit(ParseEdit {
+                range,
+                replacement: change.text,
+            });
+            Self::from_parsed(parsed)
+        }
     }
 
     pub struct BindingInfo {
@@ -150,21 +252,8 @@ mod server {
             match item {
                 Item::Scoped { items, .. } => scope_bindings.push(bindings_info(items)),
                 Item::Words(words) => {
-                    if let [Sp {
-                        value: Word::Comment(comment),
-                        ..
-                    }] = words.as_slice()
-                    {
-                        let full = last_comment.get_or_insert_with(String::new);
-                        if !full.is_empty() {
-                            if comment.trim().is_empty() {
-                                full.push('\n');
-                                full.push('\n');
-                            } else {
-                                full.push(' ');
-                            }
-                        }
-                        full.push_str(comment.trim());
+                    if let Some(comment) = item.comment() {
+                        push_doc_line(last_comment.get_or_insert_with(String::new), comment);
                     } else {
                         last_comment = None;
                         for word in words {
@@ -226,7 +315,7 @@ mod server {
             Ok(InitializeResult {
                 capabilities: ServerCapabilities {
                     text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                        TextDocumentSyncKind::FULL,
+                        TextDocumentSyncKind::INCREMENTAL,
                     )),
                     hover_provider: Some(HoverProviderCapability::Simple(true)),
                     document_formatting_provider: Some(OneOf::Left(true)),
@@ -239,6 +328,10 @@ mod server {
                                         SemanticTokenType::STRING,
                                         SemanticTokenType::NUMBER,
                                         SemanticTokenType::COMMENT,
+                                        SemanticTokenType::VARIABLE,
+                                        SemanticTokenType::FUNCTION,
+                                        SemanticTokenType::MACRO,
+                                        SemanticTokenType::OPERATOR,
                                     ],
                                     token_modifiers: vec![],
                                 },
@@ -262,15 +355,20 @@ mod server {
         async fn did_open(&self, param: DidOpenTextDocumentParams) {
             self.docs.insert(
                 param.text_document.uri,
-                LspDoc::new(param.text_document.text),
+                LspDoc::new(&param.text_document.text),
             );
         }
 
         async fn did_change(&self, params: DidChangeTextDocumentParams) {
-            self.docs.insert(
-                params.text_document.uri,
-                LspDoc::new(params.content_changes[0].text.clone()),
-            );
+            let uri = params.text_document.uri;
+            let mut doc = match self.docs.remove(&uri) {
+                Some((_, doc)) => doc,
+                None => LspDoc::new(""),
+            };
+            for change in params.content_changes {
+                doc = doc.change(change);
+            }
+            self.docs.insert(uri, doc);
         }
 
         async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
@@ -377,7 +475,7 @@ mod server {
             // };
 
             let Ok(formatted) = format_str(
-                &doc.input,
+                doc.input(),
                 &FormatConfig {
                     multiline_indent: params.options.tab_size as usize,
                     ..Default::default()
@@ -408,7 +506,7 @@ mod server {
                 return Ok(None);
             };
             let mut env = Uiua::with_native_sys();
-            Ok(if env.load_str(&doc.input).is_ok() {
+            Ok(if env.load_str(doc.input()).is_ok() {
                 let stack = env.take_stack();
                 let mut text = String::new();
                 for val in stack {
@@ -447,11 +545,18 @@ mod server {
                     SpanKind::String => 0,
                     SpanKind::Number => 1,
                     SpanKind::Comment => 2,
+                    SpanKind::BindingName => 3,
+                    SpanKind::Primitive(prim) => match PrimHighlight::new(prim) {
+                        PrimHighlight::Modifier(_) => 5,
+                        PrimHighlight::Stack => 6,
+                        _ => 4,
+                    },
+                    SpanKind::Delimiter => 6,
                     _ => continue,
                 };
                 let span = &sp.span;
                 let start = uiua_loc_to_lsp(span.start);
-                let delta_start = if start.character > prev_char {
+                let delta_start = if start.line == prev_line {
                     start.character - prev_char
                 } else {
                     start.character
@@ -477,6 +582,23 @@ mod server {
         }
     }
 
+    /// Get the byte position in the input of an LSP position
+    fn lsp_pos_to_byte(input: &str, pos: Position) -> usize {
+        let mut line_start = 0;
+        for _ in 0..pos.line {
+            match input[line_start..].find('\n') {
+                Some(i) => line_start += i + 1,
+                None => return input.len(),
+            }
+        }
+        let line = &input[line_start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        line_start
+            + (line.char_indices())
+                .nth(pos.character as usize)
+                .map_or(line.len(), |(i, _)| i)
+    }
+
     fn lsp_pos_to_uiua(pos: Position) -> (usize, usize) {
         (pos.line as usize + 1, pos.character as usize + 1)
     }
@@ -4,6 +4,7 @@ compile_error!("To compile the uiua interpreter binary, you must enable the `bin
 use std::{
     env, fmt, fs,
     io::{self, stderr, Write},
+    mem::take,
     path::{Path, PathBuf},
     process::{exit, Child, Command, Stdio},
     sync::mpsc::channel,
@@ -19,8 +20,8 @@ use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 use uiua::{
     format::{format_file, FormatConfig, FormatConfigSource},
-    run::RunMode,
-    Uiua, UiuaError, UiuaResult,
+    run::{InterruptHandle, OutputMode, RunMode},
+    NativeSys, SysBackend, Uiua, UiuaError, UiuaResult,
 };
 
 fn main() {
@@ -33,6 +34,10 @@ fn main() {
             *child = None;
             println!("# Program interrupted");
             print_watching();
+        } else if let Some(handle) = RUNTIME_INTERRUPT.lock().take() {
+            // Interrupt the running program
+            // If it doesn't stop, the next Ctrl-C will exit
+            handle.interrupt();
         } else {
             if let Ok(App::Watch { .. }) | Err(_) = App::try_parse() {
                 clear_watching_with(" ", "");
@@ -41,13 +46,32 @@ fn main() {
         }
     });
 
-    if let Err(e) = run() {
-        println!("{}", e.show(true));
+    let res = run();
+    clean_up();
+    if let Err(e) = res {
+        if let Some(code) = e.exit_code() {
+            exit(code);
+        }
+        let color = match *OUTPUT_MODE.lock() {
+            OutputMode::Auto => NativeSys.stderr_is_terminal(),
+            OutputMode::Pretty => true,
+            OutputMode::Plain => false,
+        };
+        eprintln!("{}", e.show(color));
         exit(1);
     }
 }
 
+/// Clean up after the program before the process exits
+fn clean_up() {
+    // Don't leave the terminal in raw mode if the program didn't turn it off
+    _ = NativeSys.set_raw_mode(false);
+    _ = NativeSys.clean_up_temp();
+}
+
 static WATCH_CHILD: Lazy<Mutex<Option<Child>>> = Lazy::new(Default::default);
+static RUNTIME_INTERRUPT: Lazy<Mutex<Option<InterruptHandle>>> = Lazy::new(Default::default);
+static OUTPUT_MODE: Lazy<Mutex<OutputMode>> = Lazy::new(Default::default);
 
 fn run() -> UiuaResult {
     if cfg!(feature = "profile") {
@@ -85,6 +109,7 @@ fn run() -> UiuaResult {
                 formatter_options,
                 no_update,
                 mode,
+                runtime_options,
                 #[cfg(feature = "audio")]
                 audio_options,
                 args,
@@ -113,31 +138,40 @@ fn run() -> UiuaResult {
                 let mode = mode.unwrap_or(RunMode::Normal);
                 #[cfg(feature = "audio")]
                 setup_audio(audio_options);
-                let mut rt = Uiua::with_native_sys()
+                let mut rt = runtime_options
+                    .apply(Uiua::with_native_sys())
                     .with_mode(mode)
                     .with_file_path(&path)
                     .with_args(args)
                     .print_diagnostics(true);
-                rt.load_file(path)?;
+                *RUNTIME_INTERRUPT.lock() = Some(rt.interrupt_handle());
+                let res = rt.load_file(path);
+                print_profile(&rt);
+                res?;
                 for value in rt.take_stack() {
-                    println!("{}", value.show());
+                    println!("{}", rt.show_value(&value));
                 }
             }
             App::Eval {
                 code,
+                runtime_options,
                 #[cfg(feature = "audio")]
                 audio_options,
                 args,
             } => {
                 #[cfg(feature = "audio")]
                 setup_audio(audio_options);
-                let mut rt = Uiua::with_native_sys()
+                let mut rt = runtime_options
+                    .apply(Uiua::with_native_sys())
                     .with_mode(RunMode::Normal)
                     .with_args(args)
                     .print_diagnostics(true);
-                rt.load_str(&code)?;
+                *RUNTIME_INTERRUPT.lock() = Some(rt.interrupt_handle());
+                let res = rt.load_str(&code);
+                print_profile(&rt);
+                res?;
                 for value in rt.take_stack() {
-                    println!("{}", value.show());
+                    println!("{}", rt.show_value(&value));
                 }
             }
             App::Test {
@@ -158,11 +192,28 @@ fn run() -> UiuaResult {
                 let config =
                     FormatConfig::from_source(formatter_options.format_config_source, Some(&path))?;
                 format_file(&path, &config)?;
-                Uiua::with_native_sys()
+                let mut rt = Uiua::with_native_sys()
                     .with_mode(RunMode::Test)
-                    .print_diagnostics(true)
-                    .load_file(path)?;
-                println!("No failures!");
+                    .print_diagnostics(true);
+                rt.load_file(path)?;
+                let results = rt.take_test_results();
+                for failure in &results.failures {
+                    println!("{}", failure.show(true));
+                }
+                let plural = |n: usize| if n == 1 { "" } else { "s" };
+                let passed = results.passed;
+                let failed = results.failures.len();
+                if failed == 0 {
+                    println!("{passed} assertion{} passed. No failures!", plural(passed));
+                } else {
+                    println!(
+                        "{passed} assertion{} passed, {failed} test{} failed",
+                        plural(passed),
+                        plural(failed)
+                    );
+                    clean_up();
+                    exit(1);
+                }
             }
             App::Watch {
                 no_format,
@@ -188,6 +239,14 @@ fn run() -> UiuaResult {
             }
             #[cfg(feature = "lsp")]
             App::Lsp => uiua::lsp::run_server(),
+            App::Repl {
+                runtime_options,
+                args,
+            } => repl(
+                runtime_options
+                    .apply(Uiua::with_native_sys())
+                    .with_args(args),
+            ),
         },
         Err(e) if e.kind() == ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => {
             show_update_message();
@@ -218,7 +277,23 @@ fn run() -> UiuaResult {
                 eprintln!("Error watching file: {e}");
             }
         }
-        Err(e) => _ = e.print(),
+        Err(e) if e.kind() == ErrorKind::InvalidSubcommand => {
+            // `uiua <PATH>` runs a script, like from a `#!` line
+            let mut args = env::args().skip(1);
+            let path = PathBuf::from(args.next().unwrap_or_default());
+            if !path.is_file() {
+                e.exit();
+            }
+            let mut rt = Uiua::with_native_sys()
+                .with_file_path(&path)
+                .with_args(args.collect())
+                .print_diagnostics(true);
+            rt.load_file(path)?;
+            for value in rt.take_stack() {
+                println!("{}", rt.show_value(&value));
+            }
+        }
+        Err(e) => e.exit(),
     }
     Ok(())
 }
@@ -426,19 +501,23 @@ enum App {
         no_update: bool,
         #[clap(long, help = "Run the file in a specific mode")]
         mode: Option<RunMode>,
+        #[clap(flatten)]
+        runtime_options: RuntimeOptions,
         #[cfg(feature = "audio")]
         #[clap(flatten)]
         audio_options: AudioOptions,
-        #[clap(trailing_var_arg = true)]
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
     #[clap(about = "Evaluate an expression and print its output")]
     Eval {
         code: String,
+        #[clap(flatten)]
+        runtime_options: RuntimeOptions,
         #[cfg(feature = "audio")]
         #[clap(flatten)]
         audio_options: AudioOptions,
-        #[clap(trailing_var_arg = true)]
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
     #[clap(about = "Format and test a file")]
@@ -459,7 +538,7 @@ enum App {
         clear: bool,
         #[clap(long, help = "Read stdin from file")]
         stdin_file: Option<PathBuf>,
-        #[clap(trailing_var_arg = true)]
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
         args: Vec<String>,
     },
     #[clap(about = "Format a uiua file or all files in the current directory")]
@@ -471,6 +550,13 @@ enum App {
     #[cfg(feature = "lsp")]
     #[clap(about = "Run the Language Server")]
     Lsp,
+    #[clap(about = "Start an interactive session")]
+    Repl {
+        #[clap(flatten)]
+        runtime_options: RuntimeOptions,
+        #[clap(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
 }
 
 #[derive(clap::Args)]
@@ -490,6 +576,60 @@ struct FormatterOptions {
     stdout: bool,
 }
 
+#[derive(clap::Args)]
+struct RuntimeOptions {
+    #[clap(long, help = "Print the compiled instructions of each item to stderr")]
+    emit_instrs: bool,
+    #[clap(
+        long,
+        help = "Print the time spent in each primitive and named function to stderr"
+    )]
+    profile: bool,
+    #[clap(long, help = "Limit the execution time in seconds")]
+    time_limit: Option<f64>,
+    #[clap(long, help = "Limit the depth of the call stack")]
+    max_depth: Option<usize>,
+    #[clap(long, help = "Limit the number of instructions executed")]
+    instruction_limit: Option<u64>,
+    #[clap(
+        long,
+        conflicts_with = "plain",
+        help = "Print boxed values and colored errors even when not writing to a terminal"
+    )]
+    pretty: bool,
+    #[clap(
+        long,
+        help = "Print values without box-drawing characters and errors without color"
+    )]
+    plain: bool,
+}
+
+impl RuntimeOptions {
+    fn apply(self, mut rt: Uiua) -> Uiua {
+        rt = rt
+            .print_instrs(self.emit_instrs)
+            .with_profiling(self.profile);
+        if let Some(limit) = self.time_limit {
+            rt = rt.with_execution_limit(Duration::from_secs_f64(limit));
+        }
+        if let Some(limit) = self.max_depth {
+            rt = rt.with_call_depth_limit(limit);
+        }
+        if let Some(limit) = self.instruction_limit {
+            rt = rt.with_instruction_limit(limit);
+        }
+        let output_mode = if self.pretty {
+            OutputMode::Pretty
+        } else if self.plain {
+            OutputMode::Plain
+        } else {
+            OutputMode::Auto
+        };
+        *OUTPUT_MODE.lock() = output_mode;
+        rt.with_output_mode(output_mode)
+    }
+}
+
 #[cfg(feature = "audio")]
 #[derive(clap::Args)]
 struct AudioOptions {
@@ -512,6 +652,71 @@ fn setup_audio(options: AudioOptions) {
     }
 }
 
+fn repl(mut rt: Uiua) {
+    rt = rt.print_diagnostics(true);
+    let stdin = io::stdin();
+    let mut line = String::new();
+    let mut comments = String::new();
+    loop {
+        print!("» ");
+        _ = io::stdout().flush();
+        line.clear();
+        if stdin.read_line(&mut line).map_or(true, |n| n == 0) {
+            println!();
+            print_profile(&rt);
+            break;
+        }
+        // `)doc Name` shows the documentation of a binding
+        if let Some(name) = line.trim().strip_prefix(")doc") {
+            let name = name.trim();
+            match rt.binding_docs(name) {
+                Some(doc) => println!("{doc}"),
+                None if rt.all_bindings_in_scope().contains_key(name) => {
+                    println!("`{name}` has no documentation")
+                }
+                None => println!("`{name}` is not bound"),
+            }
+            continue;
+        }
+        // Comments are kept until the next line so that they can document a binding
+        if line.trim_start().starts_with('#') {
+            comments.push_str(&line);
+            continue;
+        }
+        let input = take(&mut comments) + &line;
+        let backup = rt.stack().to_vec();
+        // Only interrupt the program while it is running
+        *RUNTIME_INTERRUPT.lock() = Some(rt.interrupt_handle());
+        let res = rt.load_str(&input);
+        *RUNTIME_INTERRUPT.lock() = None;
+        match res {
+            Ok(()) => {
+                for value in rt.stack() {
+                    println!("{}", rt.show_value(value));
+                }
+            }
+            Err(e) => {
+                if let Some(code) = e.exit_code() {
+                    clean_up();
+                    exit(code);
+                }
+                println!("{}", e.show(true));
+                // Restore the stack to how it was before the error
+                rt.take_stack();
+                for value in backup {
+                    rt.push(value);
+                }
+            }
+        }
+    }
+}
+
+fn print_profile(rt: &Uiua) {
+    if let Some(profile) = rt.profile() {
+        eprint!("{profile}");
+    }
+}
+
 fn uiua_files() -> Vec<PathBuf> {
     fs::read_dir(".")
         .unwrap()
@@ -598,3 +803,24 @@ fn format_multi_files(config: &FormatConfig, stdout: bool) -> Result<(), UiuaErr
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn max_depth_option() {
+        let app = App::try_parse_from(["uiua", "eval", "--max-depth", "10", "F 0"]).unwrap();
+        let App::Eval {
+            code,
+            runtime_options,
+            ..
+        } = app
+        else {
+            panic!("expected eval");
+        };
+        let mut rt = runtime_options.apply(Uiua::with_native_sys());
+        let err = rt.load_str(&format!("F ← |1 F+1\n{code}")).unwrap_err();
+        assert!(err.message().contains("more than 10 calls deep"), "{err}");
+    }
+}
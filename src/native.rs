@@ -0,0 +1,180 @@
+//! Rust functions that Uiua code can call with [`Uiua::bind_native`]
+
+use crate::{array::Array, function::Signature, value::Value, Uiua, UiuaResult};
+
+/// A type that a native function can take as an argument
+pub trait FromValue: Sized {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value, _: &Uiua) -> UiuaResult<Self> {
+        Ok(value)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.as_num(env, "Argument must be a number")
+    }
+}
+
+impl FromValue for usize {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.as_nat(env, "Argument must be a natural number")
+    }
+}
+
+impl FromValue for isize {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.as_int(env, "Argument must be an integer")
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        i64::try_from(value).map_err(|e| env.error(e))
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.as_bool(env, "Argument must be a boolean")
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.as_string(env, "Argument must be a string")
+    }
+}
+
+impl FromValue for Vec<f64> {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.as_number_list(env, "Argument must be a list of numbers", |_| true, |f| f)
+    }
+}
+
+impl FromValue for Vec<Vec<f64>> {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        Vec::try_from(value).map_err(|e| env.error(e))
+    }
+}
+
+impl FromValue for Vec<usize> {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.as_naturals(env, "Argument must be a list of natural numbers")
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: Value, env: &Uiua) -> UiuaResult<Self> {
+        value.into_bytes(env, "Argument must be a list of bytes")
+    }
+}
+
+/// The outputs of a native function
+pub trait IntoOutputs {
+    /// The number of values pushed to the stack
+    const COUNT: usize;
+    fn push_outputs(self, env: &mut Uiua) -> UiuaResult;
+}
+
+macro_rules! into_output {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl IntoOutputs for $ty {
+                const COUNT: usize = 1;
+                fn push_outputs(self, env: &mut Uiua) -> UiuaResult {
+                    env.push(self);
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+into_output!(
+    Value,
+    f64,
+    usize,
+    i32,
+    i64,
+    bool,
+    u8,
+    char,
+    String,
+    &'static str,
+    Vec<f64>,
+    Vec<u8>,
+    Array<f64>,
+    Array<u8>,
+    Array<char>,
+);
+
+impl IntoOutputs for () {
+    const COUNT: usize = 0;
+    fn push_outputs(self, _: &mut Uiua) -> UiuaResult {
+        Ok(())
+    }
+}
+
+/// The first output ends up on top of the stack
+impl<A: IntoOutputs, B: IntoOutputs> IntoOutputs for (A, B) {
+    const COUNT: usize = A::COUNT + B::COUNT;
+    fn push_outputs(self, env: &mut Uiua) -> UiuaResult {
+        self.1.push_outputs(env)?;
+        self.0.push_outputs(env)
+    }
+}
+
+/// An error becomes a Uiua error at the call site
+impl<T: IntoOutputs> IntoOutputs for Result<T, String> {
+    const COUNT: usize = T::COUNT;
+    fn push_outputs(self, env: &mut Uiua) -> UiuaResult {
+        match self {
+            Ok(outputs) => outputs.push_outputs(env),
+            Err(e) => Err(env.error(e)),
+        }
+    }
+}
+
+/// A Rust function that can be bound with [`Uiua::bind_native`]
+///
+/// This is implemented for functions of up to 4 arguments that implement [`FromValue`]
+/// and whose return type implements [`IntoOutputs`].
+pub trait NativeFn<Args>: Send + Sync + 'static {
+    /// The signature of the function in Uiua
+    const SIGNATURE: Signature;
+    /// Pop the arguments, call the function, and push the outputs
+    fn call(&self, env: &mut Uiua) -> UiuaResult;
+}
+
+macro_rules! native_fn {
+    ($count:literal $(, $arg:ident)*) => {
+        impl<F, R, $($arg),*> NativeFn<($($arg,)*)> for F
+        where
+            F: Fn($($arg),*) -> R + Send + Sync + 'static,
+            R: IntoOutputs,
+            $($arg: FromValue,)*
+        {
+            const SIGNATURE: Signature = Signature::new($count, R::COUNT);
+            #[allow(non_snake_case, unused_variables, unused_mut)]
+            fn call(&self, env: &mut Uiua) -> UiuaResult {
+                let mut i = 0;
+                $(
+                    i += 1;
+                    let value = env.pop(i)?;
+                    let $arg = $arg::from_value(value, env)?;
+                )*
+                self($($arg),*).push_outputs(env)
+            }
+        }
+    };
+}
+
+native_fn!(0);
+native_fn!(1, A);
+native_fn!(2, A, B);
+native_fn!(3, A, B, C);
+native_fn!(4, A, B, C, D);
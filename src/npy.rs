@@ -0,0 +1,498 @@
+//! Reading and writing NumPy's `.npy` and `.npz` formats
+
+use crate::{array::Array, hash::crc32, value::Value};
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Encode a value in the `.npy` format
+///
+/// Numbers are saved as `<f8`, bytes as `|u1`, and characters as `<U1`.
+pub fn value_to_npy_bytes(value: &Value) -> Result<Vec<u8>, String> {
+    let (descr, data): (&str, Vec<u8>) = match value {
+        Value::Num(array) => (
+            "<f8",
+            array.data.iter().flat_map(|n| n.to_le_bytes()).collect(),
+        ),
+        Value::Byte(array) => ("|u1", array.data.to_vec()),
+        Value::Char(array) => (
+            "<U1",
+            (array.data.iter())
+                .flat_map(|&c| (c as u32).to_le_bytes())
+                .collect(),
+        ),
+        Value::Func(_) => return Err("Functions and boxes cannot be encoded as .npy".into()),
+    };
+    let shape = match value.shape() {
+        [n] => format!("({n},)"),
+        shape => {
+            let dims: Vec<String> = shape.iter().map(usize::to_string).collect();
+            format!("({})", dims.join(", "))
+        }
+    };
+    let mut header = format!("{{'descr': '{descr}', 'fortran_order': False, 'shape': {shape}, }}");
+    // The header is padded so that the data is aligned to 64 bytes
+    let prefix_len = if header.len() + 11 < 1 << 16 { 10 } else { 12 };
+    let padded_len = (prefix_len + header.len() + 1).next_multiple_of(64) - prefix_len;
+    while header.len() + 1 < padded_len {
+        header.push(' ');
+    }
+    header.push('\n');
+    let mut bytes = NPY_MAGIC.to_vec();
+    if prefix_len == 10 {
+        bytes.extend([1, 0]);
+        bytes.extend((header.len() as u16).to_le_bytes());
+    } else {
+        bytes.extend([2, 0]);
+        bytes.extend((header.len() as u32).to_le_bytes());
+    }
+    bytes.extend(header.into_bytes());
+    bytes.extend(data);
+    Ok(bytes)
+}
+
+/// Decode a value from the `.npy` format
+///
+/// Booleans and unsigned bytes become byte arrays, and other numeric types become number arrays.
+/// Unicode strings become character arrays and byte strings become byte arrays.
+/// Strings of more than one character add an axis whose length is the string length.
+pub fn npy_bytes_to_value(bytes: &[u8]) -> Result<Value, String> {
+    if !bytes.starts_with(NPY_MAGIC) || bytes.len() < 10 {
+        return Err("Invalid .npy file: missing magic string".into());
+    }
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16_at(bytes, 8)? as usize, 10),
+        2 | 3 => (u32_at(bytes, 8)? as usize, 12),
+        version => return Err(format!("Unsupported .npy version {version}")),
+    };
+    let header = (bytes.get(header_start..header_start + header_len))
+        .ok_or("Invalid .npy file: header is truncated")?;
+    let header =
+        std::str::from_utf8(header).map_err(|_| "Invalid .npy file: header is not text")?;
+    let descr = parse_descr(header_value(header, "descr")?)?;
+    let fortran_order = match header_value(header, "fortran_order")? {
+        v if v.starts_with("True") => true,
+        v if v.starts_with("False") => false,
+        _ => return Err("Invalid .npy file: fortran_order must be True or False".into()),
+    };
+    let shape = parse_shape(header_value(header, "shape")?)?;
+    let count = (shape.iter()).try_fold(1usize, |count, &n| count.checked_mul(n));
+    let (Some(count), Some(size)) = (count, count.and_then(|n| n.checked_mul(descr.size))) else {
+        return Err(format!("Invalid .npy file: shape {shape:?} is too large"));
+    };
+    let data = &bytes[header_start + header_len..];
+    if data.len() < size {
+        return Err(format!(
+            "Invalid .npy file: shape {shape:?} needs {size} bytes of data, but there are {}",
+            data.len()
+        ));
+    }
+    let elements = ElementOrder::new(&shape, fortran_order);
+    let element = |i: usize| {
+        let start = elements.source(i) * descr.size;
+        &data[start..start + descr.size]
+    };
+    Ok(match descr.kind {
+        Kind::Bool | Kind::Uint if descr.size == 1 => {
+            let data: Vec<u8> = (0..count).map(|i| element(i)[0]).collect();
+            Array::new(shape.as_slice(), data).into()
+        }
+        Kind::Bool => return Err("Booleans must be 1 byte".into()),
+        Kind::Float | Kind::Int | Kind::Uint => {
+            let data = (0..count)
+                .map(|i| descr.number(element(i)))
+                .collect::<Result<Vec<f64>, _>>()?;
+            Array::new(shape.as_slice(), data).into()
+        }
+        Kind::Unicode => {
+            let len = descr.size / 4;
+            let mut chars = Vec::with_capacity(count * len);
+            for i in 0..count {
+                for code in element(i).chunks_exact(4) {
+                    let code = descr.u32(code);
+                    chars.push(char::from_u32(code).ok_or_else(|| {
+                        format!("Invalid .npy file: {code:#x} is not a valid character")
+                    })?);
+                }
+            }
+            let shape = string_shape(shape, len);
+            Array::new(shape.as_slice(), chars).into()
+        }
+        Kind::Bytes => {
+            let data: Vec<u8> = (0..count).flat_map(element).copied().collect();
+            let shape = string_shape(shape, descr.size);
+            Array::new(shape.as_slice(), data).into()
+        }
+    })
+}
+
+fn string_shape(mut shape: Vec<usize>, len: usize) -> Vec<usize> {
+    if len != 1 {
+        shape.push(len);
+    }
+    shape
+}
+
+/// Maps indices in row-major order to indices in the data
+struct ElementOrder {
+    /// The shape and the data strides, if the data is in column-major order
+    fortran: Option<(Vec<usize>, Vec<usize>)>,
+}
+
+impl ElementOrder {
+    fn new(shape: &[usize], fortran_order: bool) -> Self {
+        let fortran = (fortran_order && shape.len() > 1).then(|| {
+            let mut strides = Vec::with_capacity(shape.len());
+            let mut stride = 1;
+            for &dim in shape {
+                strides.push(stride);
+                stride *= dim;
+            }
+            (shape.to_vec(), strides)
+        });
+        ElementOrder { fortran }
+    }
+    fn source(&self, mut i: usize) -> usize {
+        let Some((shape, strides)) = &self.fortran else {
+            return i;
+        };
+        let mut source = 0;
+        for (&dim, &stride) in shape.iter().zip(strides).rev() {
+            source += (i % dim) * stride;
+            i /= dim;
+        }
+        source
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Bool,
+    Int,
+    Uint,
+    Float,
+    Unicode,
+    Bytes,
+}
+
+struct Descr {
+    kind: Kind,
+    /// The size of an element in bytes
+    size: usize,
+    big_endian: bool,
+}
+
+impl Descr {
+    fn u32(&self, bytes: &[u8]) -> u32 {
+        let bytes = bytes.try_into().unwrap();
+        if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        }
+    }
+    fn number(&self, bytes: &[u8]) -> Result<f64, String> {
+        let mut buf = [0; 8];
+        if self.big_endian {
+            buf[8 - bytes.len()..].copy_from_slice(bytes);
+        } else {
+            buf[..bytes.len()].copy_from_slice(bytes);
+        }
+        let raw = if self.big_endian {
+            u64::from_be_bytes(buf)
+        } else {
+            u64::from_le_bytes(buf)
+        };
+        let bits = bytes.len() as u32 * 8;
+        Ok(match (self.kind, bytes.len()) {
+            (Kind::Float, 4) => f32::from_bits(raw as u32) as f64,
+            (Kind::Float, 8) => f64::from_bits(raw),
+            (Kind::Float, size) => return Err(format!("{size}-byte floats are not supported")),
+            (Kind::Uint, _) => raw as f64,
+            // Sign-extend the integer
+            _ => ((raw << (64 - bits)) as i64 >> (64 - bits)) as f64,
+        })
+    }
+}
+
+fn parse_descr(value: &str) -> Result<Descr, String> {
+    let quote = (value.chars().next()).filter(|&c| c == '\'' || c == '"');
+    let descr = quote
+        .and_then(|q| value[1..].split(q).next())
+        .ok_or("Only simple .npy data types are supported")?;
+    let (big_endian, rest) = match descr.as_bytes().first() {
+        Some(b'>') => (true, &descr[1..]),
+        Some(b'<' | b'|' | b'=') => (false, &descr[1..]),
+        _ => (false, descr),
+    };
+    let unsupported = || format!("The .npy data type {descr:?} is not supported");
+    let mut chars = rest.chars();
+    let kind = match chars.next() {
+        Some('b') => Kind::Bool,
+        Some('i') => Kind::Int,
+        Some('u') => Kind::Uint,
+        Some('f') => Kind::Float,
+        Some('U') => Kind::Unicode,
+        Some('S') => Kind::Bytes,
+        _ => return Err(unsupported()),
+    };
+    let count: usize = chars.as_str().parse().map_err(|_| unsupported())?;
+    let size = match kind {
+        Kind::Unicode => count * 4,
+        Kind::Int | Kind::Uint if ![1, 2, 4, 8].contains(&count) => return Err(unsupported()),
+        _ => count,
+    };
+    Ok(Descr {
+        kind,
+        size,
+        big_endian,
+    })
+}
+
+fn parse_shape(value: &str) -> Result<Vec<usize>, String> {
+    let inner = (value.strip_prefix('('))
+        .and_then(|v| v.split(')').next())
+        .ok_or("Invalid .npy file: shape must be a tuple")?;
+    (inner.split(',').map(str::trim))
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| {
+            dim.trim_end_matches('L')
+                .parse()
+                .map_err(|_| format!("Invalid .npy file: {dim:?} is not a valid dimension"))
+        })
+        .collect()
+}
+
+/// Get the text after a key in a the header's dictionary
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, String> {
+    let start = [format!("'{key}'"), format!("\"{key}\"")]
+        .iter()
+        .find_map(|quoted| header.find(quoted.as_str()).map(|i| i + quoted.len()))
+        .ok_or_else(|| format!("Invalid .npy file: header has no {key}"))?;
+    let rest = header[start..].trim_start();
+    let rest = (rest.strip_prefix(':')).ok_or_else(|| format!("Invalid .npy file: bad {key}"))?;
+    Ok(rest.trim_start())
+}
+
+/// Encode named values in the `.npz` format
+///
+/// Each value is a `.npy` file in an uncompressed zip archive.
+pub fn values_to_npz_bytes(arrays: &[(String, Value)]) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut central = Vec::new();
+    let too_large = || "The arrays are too large for a .npz file".to_string();
+    for (name, value) in arrays {
+        let name = format!("{name}.npy");
+        let data = value_to_npy_bytes(value)?;
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let offset = u32::try_from(bytes.len()).map_err(|_| too_large())?;
+        let crc = crc32(&data);
+        // Version 2.0, no flags, stored, and a timestamp of 1980-01-01 00:00
+        let common = |header: &mut Vec<u8>| {
+            header.extend(20u16.to_le_bytes());
+            header.extend(0u16.to_le_bytes());
+            header.extend(0u16.to_le_bytes());
+            header.extend(0u16.to_le_bytes());
+            header.extend(33u16.to_le_bytes());
+            header.extend(crc.to_le_bytes());
+            header.extend(size.to_le_bytes());
+            header.extend(size.to_le_bytes());
+            header.extend((name.len() as u16).to_le_bytes());
+            header.extend(0u16.to_le_bytes());
+        };
+        bytes.extend(b"PK\x03\x04");
+        common(&mut bytes);
+        bytes.extend(name.as_bytes());
+        bytes.extend(data);
+        central.extend(b"PK\x01\x02");
+        central.extend(20u16.to_le_bytes());
+        common(&mut central);
+        // No comment, disk 0, and no file attributes
+        central.extend([0; 10]);
+        central.extend(offset.to_le_bytes());
+        central.extend(name.as_bytes());
+    }
+    let count = u16::try_from(arrays.len()).map_err(|_| "Too many arrays for a .npz file")?;
+    let central_offset = u32::try_from(bytes.len()).map_err(|_| too_large())?;
+    let central_len = central.len() as u32;
+    bytes.extend(central);
+    bytes.extend(b"PK\x05\x06");
+    bytes.extend([0; 4]);
+    bytes.extend(count.to_le_bytes());
+    bytes.extend(count.to_le_bytes());
+    bytes.extend(central_len.to_le_bytes());
+    bytes.extend(central_offset.to_le_bytes());
+    bytes.extend(0u16.to_le_bytes());
+    Ok(bytes)
+}
+
+/// Decode named values from the `.npz` format
+///
+/// Files in the archive that are not `.npy` files are ignored.
+/// Compressed archives can only be read when the `gzip` feature is enabled.
+pub fn npz_bytes_to_values(bytes: &[u8]) -> Result<Vec<(String, Value)>, String> {
+    let eocd = (0..bytes.len().saturating_sub(21))
+        .rev()
+        .take(1 << 16)
+        .find(|&i| bytes[i..].starts_with(b"PK\x05\x06"))
+        .ok_or("Invalid .npz file: it is not a zip archive")?;
+    let mut count = u16_at(bytes, eocd + 10)? as u64;
+    let mut offset = u32_at(bytes, eocd + 16)? as u64;
+    if offset == u32::MAX as u64 || count == u16::MAX as u64 {
+        // Zip64 end of central directory
+        let locator = eocd
+            .checked_sub(20)
+            .ok_or("Invalid .npz file: missing zip64 locator")?;
+        let record = u64_at(bytes, locator + 8)? as usize;
+        count = u64_at(bytes, record + 32)?;
+        offset = u64_at(bytes, record + 48)?;
+    }
+    let mut pos = offset as usize;
+    let mut values = Vec::new();
+    for _ in 0..count {
+        if !bytes[pos.min(bytes.len())..].starts_with(b"PK\x01\x02") {
+            return Err("Invalid .npz file: bad central directory".into());
+        }
+        let method = u16_at(bytes, pos + 10)?;
+        let mut compressed_size = u32_at(bytes, pos + 20)? as u64;
+        let mut size = u32_at(bytes, pos + 24)? as u64;
+        let name_len = u16_at(bytes, pos + 28)? as usize;
+        let extra_len = u16_at(bytes, pos + 30)? as usize;
+        let comment_len = u16_at(bytes, pos + 32)? as usize;
+        let mut local = u32_at(bytes, pos + 42)? as u64;
+        let name = slice(bytes, pos + 46, name_len)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        // Sizes that do not fit in 32 bits are in the zip64 extra field
+        let mut extra = slice(bytes, pos + 46 + name_len, extra_len)?;
+        while extra.len() >= 4 {
+            let id = u16_at(extra, 0)?;
+            let len = u16_at(extra, 2)? as usize;
+            if id == 1 {
+                let mut field = 4;
+                for value in [&mut size, &mut compressed_size, &mut local] {
+                    if *value == u32::MAX as u64 {
+                        *value = u64_at(extra, field)?;
+                        field += 8;
+                    }
+                }
+            }
+            extra = extra.get(4 + len..).unwrap_or_default();
+        }
+        pos += 46 + name_len + extra_len + comment_len;
+        let Some(name) = name.strip_suffix(".npy") else {
+            continue;
+        };
+        let local = local as usize;
+        if !bytes[local.min(bytes.len())..].starts_with(b"PK\x03\x04") {
+            return Err("Invalid .npz file: bad local header".into());
+        }
+        let start =
+            local + 30 + u16_at(bytes, local + 26)? as usize + u16_at(bytes, local + 28)? as usize;
+        let data = slice(bytes, start, compressed_size as usize)?;
+        let value = match method {
+            0 => npy_bytes_to_value(data)?,
+            8 => npy_bytes_to_value(&inflate(data, size as usize)?)?,
+            method => return Err(format!("Unsupported .npz compression method {method}")),
+        };
+        values.push((name.to_string(), value));
+    }
+    Ok(values)
+}
+
+#[cfg(feature = "gzip")]
+fn inflate(data: &[u8], size: usize) -> Result<Vec<u8>, String> {
+    use std::io::Read;
+    let mut inflated = Vec::with_capacity(size);
+    flate2::read::DeflateDecoder::new(data)
+        .read_to_end(&mut inflated)
+        .map_err(|e| format!("Failed to decompress .npz file: {e}"))?;
+    Ok(inflated)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn inflate(_: &[u8], _: usize) -> Result<Vec<u8>, String> {
+    Err("Compressed .npz files are not supported in this build of Uiua".into())
+}
+
+fn slice(bytes: &[u8], start: usize, len: usize) -> Result<&[u8], String> {
+    (bytes.get(start..start.saturating_add(len)))
+        .ok_or_else(|| "Invalid file: it is truncated".into())
+}
+
+fn u16_at(bytes: &[u8], i: usize) -> Result<u16, String> {
+    Ok(u16::from_le_bytes(slice(bytes, i, 2)?.try_into().unwrap()))
+}
+
+fn u32_at(bytes: &[u8], i: usize) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(slice(bytes, i, 4)?.try_into().unwrap()))
+}
+
+fn u64_at(bytes: &[u8], i: usize) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(slice(bytes, i, 8)?.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TestBackend, Uiua};
+
+    #[test]
+    fn numpy_formats() {
+        fn npy(descr: &str, fortran_order: bool, shape: &str, data: &[u8]) -> Vec<u8> {
+            let mut header = format!(
+                "{{'descr': '{descr}', 'fortran_order': {}, 'shape': {shape}, }}",
+                if fortran_order { "True" } else { "False" }
+            );
+            while (10 + header.len() + 1) % 64 != 0 {
+                header.push(' ');
+            }
+            header.push('\n');
+            let mut bytes = b"\x93NUMPY\x01\x00".to_vec();
+            bytes.extend((header.len() as u16).to_le_bytes());
+            bytes.extend(header.as_bytes());
+            bytes.extend(data);
+            bytes
+        }
+        let decode = |bytes: Vec<u8>| npy_bytes_to_value(&bytes).unwrap();
+        let expected = |code: &str| {
+            Uiua::with_backend(TestBackend::default())
+                .run(code)
+                .unwrap()[0]
+                .clone()
+        };
+
+        let value = decode(npy("<f8", true, "(2, 3)", &{
+            let mut data = Vec::new();
+            for n in [0.0f64, 3.0, 1.0, 4.0, 2.0, 5.0] {
+                data.extend(n.to_le_bytes());
+            }
+            data
+        }));
+        assert_eq!(value, expected("↯2_3⇡6"));
+        let value = decode(npy(">i2", false, "(3,)", &[0xff, 0xfe, 0, 1, 1, 0]));
+        assert_eq!(value, expected("[¯2 1 256]"));
+        let value = decode(npy("<U3", false, "(2,)", &{
+            let mut data = Vec::new();
+            for c in "abcde\0".chars() {
+                data.extend((c as u32).to_le_bytes());
+            }
+            data
+        }));
+        assert_eq!(value, expected(r#"["abc" "de\0"]"#));
+        let value = decode(npy("|b1", false, "()", &[1]));
+        assert_eq!(value, expected("1"));
+        assert!(matches!(value, Value::Byte(_)));
+        let err = npy_bytes_to_value(&npy("<c16", false, "(1,)", &[0; 16])).unwrap_err();
+        assert!(err.contains("c16"), "{err}");
+        for shape in ["(4294967296, 4294967297)", "(4294967296, 4294967296)"] {
+            let err = npy_bytes_to_value(&npy("<f8", false, shape, &[])).unwrap_err();
+            assert!(err.contains("too large"), "{err}");
+        }
+
+        let arrays = vec![
+            ("xs".to_string(), expected("↯2_2 [1.5 2 3 4]")),
+            ("name".to_string(), expected(r#""Uiua""#)),
+        ];
+        let bytes = values_to_npz_bytes(&arrays).unwrap();
+        assert_eq!(npz_bytes_to_values(&bytes).unwrap(), arrays);
+    }
+}
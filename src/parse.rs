@@ -1,4 +1,4 @@
-use std::{error::Error, fmt, path::Path};
+use std::{error::Error, fmt, ops::Range, path::Path, sync::Arc};
 
 use crate::{
     ast::*,
@@ -17,6 +17,7 @@ pub enum ParseError {
     InvalidArgCount(String),
     InvalidOutCount(String),
     AmpersandBindingName,
+    Unclosed(AsciiToken),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -78,6 +79,7 @@ impl fmt::Display for ParseError {
             ParseError::InvalidArgCount(n) => write!(f, "Invalid argument count `{n}`"),
             ParseError::InvalidOutCount(n) => write!(f, "Invalid output count `{n}`"),
             ParseError::AmpersandBindingName => write!(f, "Binding names may not contain `&`"),
+            ParseError::Unclosed(open) => write!(f, "Unclosed `{open}`"),
         }
     }
 }
@@ -90,7 +92,17 @@ pub fn parse(
     input: &str,
     path: Option<&Path>,
 ) -> (Vec<Item>, Vec<Sp<ParseError>>, Vec<Diagnostic>) {
-    let (tokens, lex_errors) = lex(input, path);
+    let parsed = Parsed::new(input, path);
+    (parsed.items, parsed.errors, parsed.diagnostics)
+}
+
+/// Parse tokens into items
+///
+/// The last value is whether all of the tokens were parsed
+fn parse_tokens(
+    tokens: Vec<Sp<Token>>,
+    lex_errors: Vec<Sp<LexError>>,
+) -> (Vec<Item>, Vec<Sp<ParseError>>, Vec<Diagnostic>, bool) {
     let errors = lex_errors
         .into_iter()
         .map(|e| e.map(ParseError::Lex))
@@ -102,7 +114,8 @@ pub fn parse(
         diagnostics: Vec::new(),
     };
     let items = parser.items(true);
-    if parser.errors.is_empty() && parser.index < parser.tokens.len() {
+    let complete = parser.index == parser.tokens.len();
+    if parser.errors.is_empty() && !complete {
         parser.errors.push(
             parser
                 .tokens
@@ -110,7 +123,287 @@ pub fn parse(
                 .map(ParseError::Unexpected),
         );
     }
-    (items, parser.errors, parser.diagnostics)
+    (items, parser.errors, parser.diagnostics, complete)
+}
+
+/// A change to some source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    /// The byte range of the old code that is replaced
+    pub range: Range<usize>,
+    /// The code that replaces it
+    pub replacement: String,
+}
+
+/// Parsed source code
+///
+/// After a [`TextEdit`], [`Parsed::edit`] reparses only the lines around the edit
+/// and moves the spans of the rest of the code.
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pub items: Vec<Item>,
+    pub errors: Vec<Sp<ParseError>>,
+    pub diagnostics: Vec<Diagnostic>,
+    input: Arc<str>,
+    path: Option<Arc<Path>>,
+    /// Whether the items cover all of the input
+    complete: bool,
+}
+
+impl Parsed {
+    /// Parse some source code
+    pub fn new(input: &str, path: Option<&Path>) -> Self {
+        Self::parse_all(input.into(), path.map(Into::into))
+    }
+    fn parse_all(input: Arc<str>, path: Option<Arc<Path>>) -> Self {
+        let (tokens, lex_errors) = lex_region(&input, path.as_deref(), Loc::default(), input.len());
+        let (items, errors, diagnostics, complete) = parse_tokens(tokens, lex_errors);
+        Parsed {
+            items,
+            errors,
+            diagnostics,
+            input,
+            path,
+            complete,
+        }
+    }
+    /// Get the source code
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+    /// Apply an edit to the source code and reparse it
+    ///
+    /// Only the top-level items on the lines around the edit are reparsed.
+    /// If the edit changes the structure of the code beyond those lines,
+    /// for example by opening a bracket or a scope, the whole code is reparsed.
+    ///
+    /// # Panics
+    /// Panics if the edit's range is not on character boundaries of the code
+    pub fn edit(&mut self, edit: TextEdit) {
+        let old = self.input.clone();
+        let TextEdit { range, replacement } = edit;
+        let mut new = String::with_capacity(old.len() + replacement.len());
+        new.push_str(&old[..range.start]);
+        new.push_str(&replacement);
+        new.push_str(&old[range.end..]);
+        let new: Arc<str> = new.into();
+        let changes_scopes = |s: &str| s.contains("---") || s.contains("~~~");
+        if !self.complete
+            || changes_scopes(&old[range.clone()])
+            || changes_scopes(&replacement)
+            || (self.items.iter()).any(|item| matches!(item, Item::Scoped { .. }))
+        {
+            *self = Self::parse_all(new, self.path.take());
+            return;
+        }
+        // Find the lines to reparse. They start and end next to non-blank lines,
+        // and they contain every item they touch.
+        let item_ranges: Vec<Range<usize>> = self.items.iter_mut().map(item_range).collect();
+        let mut start = region_start(&old, line_start(&old, range.start).saturating_sub(1));
+        let mut end = region_end(&old, line_end(&old, line_end(&old, range.end)));
+        loop {
+            let (mut new_start, mut new_end) = (start, end);
+            for r in &item_ranges {
+                if r.start < end && r.end > start {
+                    new_start = new_start.min(region_start(&old, r.start));
+                    new_end = new_end.max(region_end(&old, r.end));
+                }
+            }
+            if (new_start, new_end) == (start, end) {
+                break;
+            }
+            (start, end) = (new_start, new_end);
+        }
+        // Reparse the lines
+        let old_region = &old[start..end];
+        let new_end = end + replacement.len() - range.len();
+        let new_region = &new[start..new_end];
+        let prefix = &old[..start];
+        let region_loc = Loc {
+            char_pos: prefix.chars().count(),
+            byte_pos: start,
+            line: prefix.matches('\n').count() + 1,
+            col: 1,
+        };
+        let (tokens, lex_errors) = lex_region(&new, self.path.as_deref(), region_loc, new_end);
+        let (region_items, region_errors, region_diagnostics, _) = parse_tokens(tokens, lex_errors);
+        // Errors may come from code outside of the lines, like an unclosed bracket
+        if !region_errors.is_empty() {
+            *self = Self::parse_all(new, self.path.take());
+            return;
+        }
+        // Move the spans of the rest of the code
+        let shift = SpanShift {
+            input: new.clone(),
+            from: end,
+            bytes: new_end as isize - end as isize,
+            chars: new_region.chars().count() as isize - old_region.chars().count() as isize,
+            lines: new_region.matches('\n').count() as isize
+                - old_region.matches('\n').count() as isize,
+        };
+        let before = item_ranges.iter().take_while(|r| r.end <= start).count();
+        let after = item_ranges.iter().filter(|r| r.start >= end).count();
+        let mut after_items = self.items.split_off(self.items.len() - after);
+        self.items.truncate(before);
+        for item in self.items.iter_mut().chain(&mut after_items) {
+            item_spans_mut(item, &mut |span| shift.apply(span));
+        }
+        self.items.extend(region_items);
+        self.items.append(&mut after_items);
+        let outside = |span: &CodeSpan| span.end.byte_pos <= start || span.start.byte_pos >= end;
+        self.errors.retain(|error| outside(&error.span));
+        for error in &mut self.errors {
+            shift.apply(&mut error.span);
+            if let ParseError::Expected(_, Some(found)) = &mut error.value {
+                shift.apply(&mut found.span);
+            }
+        }
+        self.diagnostics.retain(|diag| match &diag.span {
+            Span::Code(span) => outside(span),
+            Span::Builtin => true,
+        });
+        for diag in &mut self.diagnostics {
+            if let Span::Code(span) = &mut diag.span {
+                shift.apply(span);
+            }
+        }
+        self.diagnostics.extend(region_diagnostics);
+        self.input = new;
+    }
+}
+
+/// How to move a span that is not reparsed after an edit
+struct SpanShift {
+    input: Arc<str>,
+    /// The byte position at which spans are moved
+    from: usize,
+    bytes: isize,
+    chars: isize,
+    lines: isize,
+}
+
+impl SpanShift {
+    fn apply(&self, span: &mut CodeSpan) {
+        if span.start.byte_pos >= self.from {
+            for loc in [&mut span.start, &mut span.end] {
+                loc.byte_pos = (loc.byte_pos as isize + self.bytes) as usize;
+                loc.char_pos = (loc.char_pos as isize + self.chars) as usize;
+                loc.line = (loc.line as isize + self.lines) as usize;
+            }
+        }
+        span.input = self.input.clone();
+    }
+}
+
+fn line_start(s: &str, pos: usize) -> usize {
+    s[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(s: &str, pos: usize) -> usize {
+    s[pos..].find('\n').map_or(s.len(), |i| pos + i + 1)
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// Get the start of the line containing `pos`, or of the nearest non-blank line before it
+fn region_start(s: &str, pos: usize) -> usize {
+    let mut start = line_start(s, pos);
+    while start > 0 && is_blank(&s[start..line_end(s, start)]) {
+        start = line_start(s, start - 1);
+    }
+    start
+}
+
+/// Extend `end` to the end of its line and past any blank lines after it
+fn region_end(s: &str, end: usize) -> usize {
+    let mut end = if end == 0 || s.as_bytes()[end - 1] == b'\n' {
+        end
+    } else {
+        line_end(s, end)
+    };
+    while end < s.len() && is_blank(&s[end..line_end(s, end)]) {
+        end = line_end(s, end);
+    }
+    end
+}
+
+/// Get the byte range of an item
+fn item_range(item: &mut Item) -> Range<usize> {
+    let mut range: Option<Range<usize>> = None;
+    item_spans_mut(item, &mut |span| {
+        let (start, end) = (span.start.byte_pos, span.end.byte_pos);
+        range = Some(match range.take() {
+            Some(r) => r.start.min(start)..r.end.max(end),
+            None => start..end,
+        });
+    });
+    range.unwrap_or(0..0)
+}
+
+fn item_spans_mut(item: &mut Item, f: &mut dyn FnMut(&mut CodeSpan)) {
+    match item {
+        Item::Scoped { items, .. } => {
+            for item in items {
+                item_spans_mut(item, f);
+            }
+        }
+        Item::Words(words) => words_spans_mut(words, f),
+        Item::Binding(binding) => {
+            f(&mut binding.name.span);
+            if let Some(sig) = &mut binding.signature {
+                f(&mut sig.span);
+            }
+            words_spans_mut(&mut binding.words, f);
+        }
+        Item::ExtraNewlines(span) => f(span),
+    }
+}
+
+fn words_spans_mut(words: &mut [Sp<Word>], f: &mut dyn FnMut(&mut CodeSpan)) {
+    for word in words {
+        f(&mut word.span);
+        match &mut word.value {
+            Word::MultilineString(lines) => {
+                for line in lines {
+                    f(&mut line.span);
+                }
+            }
+            Word::Strand(items) => words_spans_mut(items, f),
+            Word::Array(arr) => {
+                for line in &mut arr.lines {
+                    words_spans_mut(line, f);
+                }
+            }
+            Word::Func(func) => {
+                function_id_spans_mut(&mut func.id, f);
+                if let Some(sig) = &mut func.signature {
+                    f(&mut sig.span);
+                }
+                for line in &mut func.lines {
+                    words_spans_mut(line, f);
+                }
+            }
+            Word::Modified(modified) => {
+                f(&mut modified.modifier.span);
+                words_spans_mut(&mut modified.operands, f);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn function_id_spans_mut(id: &mut FunctionId, f: &mut dyn FnMut(&mut CodeSpan)) {
+    match id {
+        FunctionId::Anonymous(span) => f(span),
+        FunctionId::Composed(ids) => {
+            for id in ids {
+                function_id_spans_mut(id, f);
+            }
+        }
+        _ => {}
+    }
 }
 
 struct Parser {
@@ -138,6 +431,13 @@ impl Parser {
         self.next_token_map(|t| (t == &token).then_some(()))
             .map(|t| t.span)
     }
+    /// Whether the next token ends the line
+    fn at_line_end(&self) -> bool {
+        match self.tokens.get(self.index) {
+            Some(token) => matches!(token.value, Newline | Comment),
+            None => true,
+        }
+    }
     fn prev_span(&self) -> CodeSpan {
         if let Some(token) = self.tokens.get(self.index.saturating_sub(1)) {
             token.span.clone()
@@ -268,7 +568,7 @@ impl Parser {
     }
     fn try_ident(&mut self) -> Option<Sp<Ident>> {
         let span = self.try_exact(Token::Ident)?;
-        let s: Ident = span.as_str().into();
+        let s: Ident = nfc(span.as_str().into()).into();
         Some(span.sp(s))
     }
     fn try_signature(&mut self) -> Option<Sp<Signature>> {
@@ -324,10 +624,24 @@ impl Parser {
             Some(words)
         }
     }
-    fn multiline_words(&mut self) -> Vec<Vec<Sp<Word>>> {
+    fn multiline_words(&mut self, locals: bool) -> Vec<Vec<Sp<Word>>> {
         let mut lines = Vec::new();
         while self.try_exact(Newline).is_some() || self.try_spaces().is_some() {}
-        while let Some(words) = self.try_words() {
+        loop {
+            let binding = if locals {
+                self.try_local_binding()
+            } else {
+                None
+            };
+            let words = match (binding, self.try_words()) {
+                (Some(binding), words) => {
+                    let mut line = vec![binding];
+                    line.extend(words.into_iter().flatten());
+                    line
+                }
+                (None, Some(words)) => words,
+                (None, None) => break,
+            };
             lines.push(words);
             let mut newlines = 0;
             while self.try_exact(Newline).is_some() {
@@ -343,6 +657,17 @@ impl Parser {
         }
         lines
     }
+    /// Try to parse the `name ←` at the start of a local binding line in a function
+    fn try_local_binding(&mut self) -> Option<Sp<Word>> {
+        let start = self.index;
+        let ident = self.try_ident()?;
+        self.try_spaces();
+        let Some(arrow) = self.try_exact(LeftArrow) else {
+            self.index = start;
+            return None;
+        };
+        Some(ident.span.merge(arrow).sp(Word::BindLocal(ident.value)))
+    }
     fn try_word(&mut self) -> Option<Sp<Word>> {
         self.comment()
             .map(|c| c.map(Word::Comment))
@@ -432,6 +757,31 @@ impl Parser {
                 break;
             }
         }
+        let found = (args.iter())
+            .filter(|arg| !matches!(arg.value, Word::Spaces))
+            .count();
+        if !terminated && found < margs as usize && self.at_line_end() {
+            let needs = if margs == 1 {
+                "a function".into()
+            } else {
+                format!("{margs} functions")
+            };
+            let ends = if found == 0 {
+                "before it".into()
+            } else {
+                format!("after {found}")
+            };
+            self.diagnostics.push(Diagnostic::new(
+                format!(
+                    "`{}` needs {needs}, but the line ends {ends}. \
+                    A modifier's functions must start on the same line as it. \
+                    To write them across lines, wrap them in `()`.",
+                    modifier.value
+                ),
+                modifier.span.clone(),
+                DiagnosticKind::Warning,
+            ));
+        }
         Some(if args.is_empty() {
             modifier.map(Word::Primitive)
         } else {
@@ -460,6 +810,8 @@ impl Parser {
             ident.map(Word::Ident)
         } else if let Some(sn) = self.try_num() {
             sn.map(|(s, n)| Word::Number(s, n))
+        } else if let Some(n) = self.next_token_map(Token::as_placeholder) {
+            n.map(Word::Placeholder)
         } else if let Some(c) = self.next_token_map(Token::as_char) {
             c.map(Into::into).map(Word::Char)
         } else if let Some(s) = self.next_token_map(Token::as_string) {
@@ -479,16 +831,16 @@ impl Parser {
         } else if let Some(expr) = self.try_func() {
             expr
         } else if let Some(start) = self.try_exact(OpenBracket) {
-            let items = self.multiline_words();
-            let end = self.expect_close(CloseBracket);
+            let items = self.multiline_words(false);
+            let end = self.expect_close(&start, CloseBracket);
             let span = start.merge(end);
             span.sp(Word::Array(Arr {
                 lines: items,
                 constant: false,
             }))
         } else if let Some(start) = self.try_exact(OpenCurly) {
-            let items = self.multiline_words();
-            let end = self.expect_close(CloseCurly);
+            let items = self.multiline_words(false);
+            let end = self.expect_close(&start, CloseCurly);
             let span = start.merge(end);
             span.sp(Word::Array(Arr {
                 lines: items,
@@ -502,9 +854,26 @@ impl Parser {
     }
     fn try_num(&mut self) -> Option<Sp<(String, f64)>> {
         let span = self.try_exact(Token::Number)?;
-        let s = span.as_str().to_string();
+        // Lookalikes of `¯` are lexed as part of numbers
+        let s = span.as_str().replace(['‾', 'ˉ'], "¯");
         let parseable = s.replace(['`', '¯'], "-");
-        let n: f64 = match parseable.parse() {
+        let (negative, unsigned) = match parseable.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, parseable.as_str()),
+        };
+        let radix = match unsigned.get(..2) {
+            Some("0x" | "0X") => Some(16),
+            Some("0b" | "0B") => Some(2),
+            _ => None,
+        };
+        let parsed = if let Some(radix) = radix {
+            u128::from_str_radix(&unsigned[2..], radix)
+                .map(|n| if negative { -(n as f64) } else { n as f64 })
+                .map_err(drop)
+        } else {
+            parseable.parse().map_err(drop)
+        };
+        let n: f64 = match parsed {
             Ok(n) => n,
             Err(_) => {
                 self.errors
@@ -529,8 +898,8 @@ impl Parser {
         Some(if let Some(start) = self.try_exact(OpenParen) {
             while self.try_exact(Newline).is_some() || self.try_spaces().is_some() {}
             let signature = self.try_signature();
-            let body = self.multiline_words();
-            let end = self.expect_close(CloseParen);
+            let body = self.multiline_words(true);
+            let end = self.expect_close(&start, CloseParen);
             let span = start.merge(end);
             span.clone().sp(Word::Func(Func {
                 id: FunctionId::Anonymous(span),
@@ -544,9 +913,19 @@ impl Parser {
     fn try_spaces(&mut self) -> Option<Sp<Word>> {
         self.try_exact(Spaces).map(|span| span.sp(Word::Spaces))
     }
-    fn expect_close(&mut self, ascii: AsciiToken) -> CodeSpan {
+    fn expect_close(&mut self, start: &CodeSpan, ascii: AsciiToken) -> CodeSpan {
         if let Some(span) = self.try_exact(ascii) {
             span
+        } else if self.index == self.tokens.len() {
+            // The code ended without closing the delimiter, so point at where it was opened
+            let open = match ascii {
+                CloseParen => OpenParen,
+                CloseBracket => OpenBracket,
+                _ => OpenCurly,
+            };
+            self.errors
+                .push(start.clone().sp(ParseError::Unclosed(open)));
+            self.prev_span()
         } else {
             self.errors
                 .push(self.expected([Expectation::Term, Expectation::Simple(ascii)]));
@@ -554,3 +933,111 @@ impl Parser {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(parsed: &mut Parsed) -> Vec<String> {
+        let mut spans = Vec::new();
+        for item in &mut parsed.items {
+            item_spans_mut(item, &mut |span| {
+                spans.push(format!(
+                    "{:?} {:?} {:?}",
+                    span.start,
+                    span.end,
+                    span.as_str()
+                ))
+            });
+        }
+        for error in &parsed.errors {
+            spans.push(format!("{error:?}"));
+        }
+        spans.extend(parsed.diagnostics.iter().map(|diag| format!("{diag:?}")));
+        spans.sort();
+        spans
+    }
+
+    #[test]
+    fn incremental_reparse() {
+        let input = "\
+# Doubles
+Double ← ×2
+F ← (
+  +1
+  ×2
+)
+ 
+$ multi
+$ line
+
+X ← [1 2
+     3]
+⊂ Double F X \"ab\" @c
+";
+        let insertions = [
+            "(", ")", "[", "]", "\n", "\n\n", "$ s", "$$ r", "# c", "X ← 1", "a ← ", "+", " ", "1",
+            "\"", "---", "é", "|2.1 ", "\n$ x\n",
+        ];
+        let mut parsed = Parsed::new(input, None);
+        let mut seed: u64 = 1;
+        let mut random = |n: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % n
+        };
+        for _ in 0..2000 {
+            let text = parsed.input().to_string();
+            let boundaries: Vec<usize> = (text.char_indices().map(|(i, _)| i))
+                .chain([text.len()])
+                .collect();
+            let start = random(boundaries.len());
+            let end = (start + random(4)).min(boundaries.len() - 1);
+            let replacement = insertions[random(insertions.len())];
+            let range = boundaries[start]..boundaries[end];
+            let mut expected_text = text.clone();
+            expected_text.replace_range(range.clone(), replacement);
+            let mut expected = Parsed::new(&expected_text, None);
+            parsed.edit(TextEdit {
+                range,
+                replacement: replacement.into(),
+            });
+            assert_eq!(parsed.input(), expected_text);
+            assert_eq!(
+                format!("{:?}", parsed.items),
+                format!("{:?}", expected.items),
+                "{expected_text}"
+            );
+            assert_eq!(spans(&mut parsed), spans(&mut expected), "{expected_text}");
+            // Keep editing valid code so that most edits are reparsed incrementally
+            if !parsed.errors.is_empty() || parsed.input().len() > 400 {
+                parsed = Parsed::new(input, None);
+            }
+        }
+    }
+
+    #[test]
+    fn exponent_literals() {
+        let (items, errors, _) = parse("1.5e1 ¯1.5e1 2e¯3 ¯5e2", None);
+        assert!(errors.is_empty(), "{errors:?}");
+        let [Item::Words(words)] = items.as_slice() else {
+            panic!("unexpected items {items:?}");
+        };
+        let numbers: Vec<(&str, f64)> = (words.iter())
+            .filter_map(|word| match &word.value {
+                Word::Number(s, n) => Some((s.as_str(), *n)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            numbers,
+            [
+                ("1.5e1", 15.0),
+                ("¯1.5e1", -15.0),
+                ("2e¯3", 0.002),
+                ("¯5e2", -500.0)
+            ]
+        );
+    }
+}
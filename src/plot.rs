@@ -0,0 +1,327 @@
+//! Rendering plots of numeric arrays into image arrays
+//!
+//! Plots are rendered directly into pixels, so they do not depend on the image feature.
+
+use tinyvec::tiny_vec;
+
+use crate::array::Array;
+
+const DEFAULT_WIDTH: usize = 600;
+const DEFAULT_HEIGHT: usize = 400;
+const MAX_SIZE: usize = 4096;
+const MARGIN: usize = 20;
+const POINT_RADIUS: isize = 3;
+
+const BACKGROUND: [f64; 3] = [1.0, 1.0, 1.0];
+const AXIS: [f64; 3] = [0.6, 0.6, 0.6];
+const MISSING: [f64; 3] = [0.5, 0.5, 0.5];
+
+/// The named colors, in the order series are colored when no color is given
+const COLORS: [(&str, [f64; 3]); 8] = [
+    ("blue", [0.12, 0.47, 0.71]),
+    ("red", [0.84, 0.15, 0.16]),
+    ("green", [0.17, 0.63, 0.17]),
+    ("yellow", [0.9, 0.7, 0.0]),
+    ("magenta", [0.75, 0.2, 0.6]),
+    ("cyan", [0.09, 0.75, 0.81]),
+    ("black", [0.0, 0.0, 0.0]),
+    ("white", [1.0, 1.0, 1.0]),
+];
+
+/// The stops of the heatmap color scale, from low to high
+const HEAT: [[f64; 3]; 5] = [
+    [0.267, 0.005, 0.329],
+    [0.229, 0.322, 0.546],
+    [0.128, 0.567, 0.551],
+    [0.369, 0.789, 0.383],
+    [0.993, 0.906, 0.144],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PlotKind {
+    Line,
+    Scatter,
+    Heatmap,
+}
+
+/// The kind, size, and color of a plot
+#[derive(Debug, Clone)]
+pub(crate) struct PlotStyle {
+    pub kind: PlotKind,
+    pub size: Option<(usize, usize)>,
+    pub color: Option<[f64; 3]>,
+}
+
+impl PlotStyle {
+    /// Parse a style from space-separated words
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut style = PlotStyle {
+            kind: PlotKind::Line,
+            size: None,
+            color: None,
+        };
+        for word in s.split_whitespace() {
+            match word {
+                "line" => style.kind = PlotKind::Line,
+                "scatter" => style.kind = PlotKind::Scatter,
+                "heatmap" => style.kind = PlotKind::Heatmap,
+                word => {
+                    if let Some(&(_, color)) = COLORS.iter().find(|(name, _)| *name == word) {
+                        style.color = Some(color);
+                    } else if let Some((w, h)) = word.split_once('x') {
+                        let parse = |n: &str| {
+                            n.parse::<usize>()
+                                .ok()
+                                .filter(|n| (1..=MAX_SIZE).contains(n))
+                                .ok_or_else(|| {
+                                    format!(
+                                        "Plot size {word:?} must be two whole numbers \
+                                        from 1 to {MAX_SIZE}"
+                                    )
+                                })
+                        };
+                        style.size = Some((parse(w)?, parse(h)?));
+                    } else {
+                        return Err(format!("Unknown plot style {word:?}"));
+                    }
+                }
+            }
+        }
+        Ok(style)
+    }
+}
+
+/// An RGB image being drawn
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<[f64; 3]>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width * height],
+        }
+    }
+    fn set(&mut self, x: isize, y: isize, color: [f64; 3]) {
+        if x >= 0 && y >= 0 && (x as usize) < self.width && (y as usize) < self.height {
+            self.pixels[y as usize * self.width + x as usize] = color;
+        }
+    }
+    fn line(&mut self, (x0, y0): (isize, isize), (x1, y1): (isize, isize), color: [f64; 3]) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+        loop {
+            for (ox, oy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                self.set(x + ox, y + oy, color);
+            }
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+    fn point(&mut self, (x, y): (isize, isize), color: [f64; 3]) {
+        let r = POINT_RADIUS;
+        for oy in -r..=r {
+            for ox in -r..=r {
+                if ox * ox + oy * oy <= r * r {
+                    self.set(x + ox, y + oy, color);
+                }
+            }
+        }
+    }
+    fn into_array(self) -> Array<f64> {
+        let data: Vec<f64> = self.pixels.into_iter().flatten().collect();
+        Array::new(tiny_vec![self.height, self.width, 3], data)
+    }
+}
+
+/// The range of the finite values, widened if it is empty
+fn range(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    let (min, max) = values
+        .filter(|v| v.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(v), max.max(v))
+        });
+    if min > max {
+        (0.0, 1.0)
+    } else if min == max {
+        (min - 1.0, max + 1.0)
+    } else {
+        (min, max)
+    }
+}
+
+/// Render a plot of a numeric array into a `[height width 3]` image array
+pub(crate) fn plot(style: &PlotStyle, data: &Array<f64>) -> Result<Array<f64>, String> {
+    match style.kind {
+        PlotKind::Heatmap => heatmap(style, data),
+        PlotKind::Line | PlotKind::Scatter => {
+            let series: Vec<Vec<(f64, f64)>> = match (style.kind, data.shape()) {
+                (PlotKind::Scatter, &[_, 2]) => vec![data
+                    .data
+                    .chunks_exact(2)
+                    .map(|point| (point[0], point[1]))
+                    .collect()],
+                (_, &[_]) => vec![indexed(&data.data)],
+                (_, &[_, n]) if n > 0 => data.data.chunks_exact(n).map(indexed).collect(),
+                (_, &[_, _]) => Vec::new(),
+                (PlotKind::Scatter, shape) => {
+                    return Err(format!(
+                        "Scatter plot data must be a list of numbers, a list of [x y] points, \
+                        or a list of lists of numbers, but its shape is {shape:?}"
+                    ))
+                }
+                (_, shape) => {
+                    return Err(format!(
+                        "Line plot data must be a list of numbers or a list of lists of numbers, \
+                        but its shape is {shape:?}"
+                    ))
+                }
+            };
+            Ok(graph(style, &series))
+        }
+    }
+}
+
+fn indexed(ys: &[f64]) -> Vec<(f64, f64)> {
+    ys.iter().enumerate().map(|(i, &y)| (i as f64, y)).collect()
+}
+
+fn graph(style: &PlotStyle, series: &[Vec<(f64, f64)>]) -> Array<f64> {
+    let (width, height) = style.size.unwrap_or((DEFAULT_WIDTH, DEFAULT_HEIGHT));
+    let mut canvas = Canvas::new(width, height);
+    let margin = MARGIN.min(width / 4).min(height / 4) as isize;
+    let (left, top) = (margin, margin);
+    let (right, bottom) = (width as isize - 1 - margin, height as isize - 1 - margin);
+    let points = || series.iter().flatten();
+    let (x_min, x_max) = range(points().map(|&(x, _)| x));
+    let (y_min, y_max) = range(points().map(|&(_, y)| y));
+    let to_pixel = |(x, y): (f64, f64)| {
+        let px = left as f64 + (x - x_min) / (x_max - x_min) * (right - left) as f64;
+        let py = bottom as f64 - (y - y_min) / (y_max - y_min) * (bottom - top) as f64;
+        (px.round() as isize, py.round() as isize)
+    };
+    // Axes
+    canvas.line((left, bottom), (right, bottom), AXIS);
+    canvas.line((left, top), (left, bottom), AXIS);
+    if y_min < 0.0 && y_max > 0.0 {
+        let (_, zero) = to_pixel((x_min, 0.0));
+        canvas.line((left, zero), (right, zero), AXIS);
+    }
+    // Data
+    let colors = style
+        .color
+        .into_iter()
+        .chain(COLORS[..6].iter().map(|&(_, color)| color));
+    for (points, color) in series.iter().zip(colors.cycle()) {
+        let mut prev = None;
+        for &(x, y) in points {
+            if !(x.is_finite() && y.is_finite()) {
+                prev = None;
+                continue;
+            }
+            let pixel = to_pixel((x, y));
+            match style.kind {
+                PlotKind::Scatter => canvas.point(pixel, color),
+                _ => {
+                    if let Some(prev) = prev {
+                        canvas.line(prev, pixel, color);
+                    } else {
+                        canvas.set(pixel.0, pixel.1, color);
+                    }
+                }
+            }
+            prev = Some(pixel);
+        }
+    }
+    canvas.into_array()
+}
+
+fn heatmap(style: &PlotStyle, data: &Array<f64>) -> Result<Array<f64>, String> {
+    let &[rows, cols] = data.shape() else {
+        return Err(format!(
+            "Heatmap data must be a rank 2 array, but its shape is {:?}",
+            data.shape()
+        ));
+    };
+    if rows == 0 || cols == 0 {
+        return Err("Heatmap data must not be empty".into());
+    }
+    let (width, height) = style.size.unwrap_or_else(|| {
+        let scale = (DEFAULT_WIDTH / rows.max(cols)).max(1);
+        (cols * scale, rows * scale)
+    });
+    let (min, max) = range(data.data.iter().copied());
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let value = data.data[y * rows / height * cols + x * cols / width];
+            let color = if value.is_finite() {
+                heat_color((value - min) / (max - min))
+            } else {
+                MISSING
+            };
+            canvas.set(x as isize, y as isize, color);
+        }
+    }
+    Ok(canvas.into_array())
+}
+
+fn heat_color(t: f64) -> [f64; 3] {
+    let t = t.clamp(0.0, 1.0) * (HEAT.len() - 1) as f64;
+    let i = (t.floor() as usize).min(HEAT.len() - 2);
+    let frac = t - i as f64;
+    let (a, b) = (HEAT[i], HEAT[i + 1]);
+    [0, 1, 2].map(|c| a[c] + (b[c] - a[c]) * frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{value::Value, Uiua};
+
+    #[test]
+    fn plots() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str(
+            r#"
+    △&plot "line" ○÷10⇡100
+    △&plot "scatter 50x40 red" ⍉⊟.⇡10
+    △&plot "heatmap" ⊞+.⇡3
+    ⊡0_0 &plot "heatmap 30x30" ⊞+.⇡3
+    "#,
+        )
+        .unwrap();
+        let stack = env.take_stack();
+        assert_eq!(
+            stack[..3],
+            [
+                Value::from(vec![400.0, 600.0, 3.0]),
+                Value::from(vec![40.0, 50.0, 3.0]),
+                Value::from(vec![600.0, 600.0, 3.0]),
+            ]
+        );
+        assert_eq!(stack[3], Value::from(vec![0.267, 0.005, 0.329]));
+        for code in [
+            r#"&plot "pie" [1 2 3]"#,
+            r#"&plot "0x10" [1 2 3]"#,
+            r#"&plot "heatmap" [1 2 3]"#,
+            r#"&plot "line" "abc""#,
+        ] {
+            assert!(Uiua::with_native_sys().load_str(code).is_err(), "{code}");
+        }
+    }
+}
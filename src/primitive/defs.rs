@@ -1422,6 +1422,31 @@ primitive!(
     /// ex: parse "3.1415926535897932"
     /// ex! parse "dog"
     (1, Parse, Misc, "parse"),
+    /// Encode a string as bytes
+    ///
+    /// The first argument is the name of the encoding, which is one of `utf-8`, `utf-16le`, or `utf-16be`.
+    /// `utf-16` is the same as `utf-16be`.
+    /// ex: encode "utf-8" "hello"
+    /// ex: encode "utf-8" "ⁿ√"
+    /// ex: encode "utf-16le" "hi"
+    ///
+    /// [encode] is the inverse of [decode].
+    /// ex: decode "utf-16be" encode "utf-16be" "hello"
+    (2, Encode, Misc, "encode"),
+    /// Decode bytes into a string
+    ///
+    /// The first argument is the name of the encoding, which is one of `utf-8`, `utf-16le`, or `utf-16be`.
+    /// `utf-16` is the same as `utf-16be`.
+    /// ex: decode "utf-8" [104 105]
+    /// ex: decode "utf-16le" [104 0 105 0]
+    ///
+    /// By default, invalid bytes are an error.
+    /// ex! decode "utf-8" [255 104 105]
+    /// If the encoding name is followed by `lossy`, invalid bytes are replaced with the replacement character `U+FFFD` instead.
+    /// ex: decode "utf-8 lossy" [255 104 105]
+    ///
+    /// See also: [encode]
+    (2, Decode, Misc, "decode"),
     /// Generate a random number between 0 and 1
     ///
     /// If you need a seeded random number, use [gen].
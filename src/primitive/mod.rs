@@ -380,6 +380,16 @@ impl Primitive {
                 env.call(f)?
             }
             Primitive::Parse => env.monadic_env(|v, env| v.parse_num(env))?,
+            Primitive::Encode => {
+                let encoding = env.pop(1)?.as_string(env, "Encoding must be a string")?;
+                let s = env.pop(2)?;
+                env.push(s.encode(&encoding, env)?);
+            }
+            Primitive::Decode => {
+                let encoding = env.pop(1)?.as_string(env, "Encoding must be a string")?;
+                let bytes = env.pop(2)?;
+                env.push(bytes.decode(&encoding, env)?);
+            }
             Primitive::Range => env.monadic_ref_env(Value::range)?,
             Primitive::Reverse => env.monadic_mut(Value::reverse)?,
             Primitive::Deshape => env.monadic_mut(Value::deshape)?,
@@ -516,6 +526,9 @@ impl Primitive {
                 let backup = env.clone_stack_top(f_args);
                 let bottom = env.stack_size().saturating_sub(f_args);
                 if let Err(e) = env.call(f) {
+                    if e.is_uncatchable() {
+                        return Err(e);
+                    }
                     env.truncate_stack(bottom);
                     env.backend.save_error_color(&e);
                     env.push(e.value());
@@ -531,6 +544,10 @@ impl Primitive {
                 if !cond.as_nat(env, "").is_ok_and(|n| n == 1) {
                     return Err(UiuaError::Throw(msg.into(), env.span().clone()));
                 }
+                let span = env.span_index();
+                if let Some(passed) = &mut env.passed_asserts {
+                    passed.insert(span);
+                }
             }
             Primitive::Rand => {
                 thread_local! {
@@ -591,7 +608,7 @@ impl Primitive {
                 let handle = env.pop(1)?;
                 env.wait(handle)?;
             }
-            Primitive::Now => env.push(instant::now() / 1000.0),
+            Primitive::Now => env.push(env.backend.now()),
             Primitive::Trace => trace(env, false)?,
             Primitive::InvTrace => trace(env, true)?,
             Primitive::Dump => dump(env)?,
@@ -1,3 +1,7 @@
+use std::{cmp::Ordering, collections::HashMap, fmt};
+
+use crate::{primitive::Primitive, Ident};
+
 #[macro_export]
 macro_rules! profile_function {
     () => {
@@ -21,6 +25,90 @@ pub fn run_profile() {
     enabled::run_profile();
 }
 
+/// Time and call counts recorded by a [`Uiua`](crate::Uiua) runtime with profiling enabled
+///
+/// Times are inclusive, so a function's time includes the time of everything it calls.
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    entries: HashMap<ProfileTarget, ProfileEntry>,
+    /// Named functions currently being timed, with the call stack height and time they were called at
+    open: Vec<(usize, Ident, f64)>,
+}
+
+/// Something whose execution is recorded in a [`Profile`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ProfileTarget {
+    Primitive(Primitive),
+    Function(Ident),
+}
+
+/// The recorded execution of a [`ProfileTarget`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProfileEntry {
+    /// The number of times it was called
+    pub calls: u64,
+    /// The total time spent in it in milliseconds
+    pub time: f64,
+}
+
+impl Profile {
+    /// Get the recorded entries, sorted from most to least time spent
+    pub fn entries(&self) -> Vec<(&ProfileTarget, &ProfileEntry)> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by(|(_, a), (_, b)| {
+            b.time
+                .partial_cmp(&a.time)
+                .unwrap_or(Ordering::Equal)
+                .then(b.calls.cmp(&a.calls))
+        });
+        entries
+    }
+    pub(crate) fn record(&mut self, target: ProfileTarget, start: f64) {
+        let entry = self.entries.entry(target).or_default();
+        entry.calls += 1;
+        entry.time += instant::now() - start;
+    }
+    pub(crate) fn record_call(&mut self, target: ProfileTarget) {
+        self.entries.entry(target).or_default().calls += 1;
+    }
+    pub(crate) fn begin_function(&mut self, height: usize, name: Ident) {
+        // Discard functions whose frames were abandoned by an error
+        while self.open.last().is_some_and(|(h, ..)| *h >= height) {
+            self.open.pop();
+        }
+        self.open.push((height, name, instant::now()));
+    }
+    pub(crate) fn end_function(&mut self, height: usize) {
+        if self.open.last().is_some_and(|(h, ..)| *h == height) {
+            let (_, name, start) = self.open.pop().unwrap();
+            self.record(ProfileTarget::Function(name), start);
+        }
+    }
+}
+
+impl fmt::Display for ProfileTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileTarget::Primitive(prim) => match prim.name() {
+                Some(name) if name != prim.to_string() => write!(f, "{prim} ({name})"),
+                _ => write!(f, "{prim}"),
+            },
+            ProfileTarget::Function(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<24} {:>10} {:>12}", "name", "calls", "time (ms)")?;
+        for (target, entry) in self.entries() {
+            let name = target.to_string();
+            writeln!(f, "{name:<24} {:>10} {:>12.3}", entry.calls, entry.time)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(feature = "profile")]
 pub(crate) mod enabled {
     use std::{
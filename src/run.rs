@@ -1,26 +1,31 @@
 use std::{
-    collections::{BTreeSet, HashMap, HashSet},
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet},
     fs,
-    hash::Hash,
-    mem::take,
+    hash::{Hash, Hasher},
+    mem::{replace, take},
     panic::{catch_unwind, AssertUnwindSafe},
     path::{Path, PathBuf},
     str::FromStr,
-    sync::Arc,
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc,
+    },
 };
 
 use instant::Duration;
 use parking_lot::Mutex;
 
 use crate::{
-    array::Array,
+    array::{Array, FormatShape},
+    compile::{binding_diagnostics, CurrentBinding, PooledConstant},
     function::*,
-    lex::Span,
+    lex::{is_ident_char, Span},
     parse::parse,
     primitive::{Primitive, CONSTANTS},
+    profile::{Profile, ProfileTarget},
     value::Value,
-    Diagnostic, DiagnosticKind, Handle, Ident, NativeSys, SysBackend, TraceFrame, UiuaError,
-    UiuaResult,
+    AsyncSysBackend, BlockingBackend, Diagnostic, DiagnosticKind, Handle, Ident, NativeFn,
+    NativeSys, SysBackend, TraceFrame, UiuaError, UiuaResult,
 };
 
 /// The Uiua runtime
@@ -48,6 +53,27 @@ pub struct Uiua {
     execution_limit: Option<f64>,
     /// The time at which execution started
     execution_start: f64,
+    /// The maximum number of elements a single array operation may create
+    element_limit: usize,
+    /// A limit on the depth of the call stack
+    call_depth_limit: usize,
+    /// The number of functions that are running on the native stack
+    native_call_depth: usize,
+    /// A limit on the number of instructions executed
+    instruction_limit: Option<u64>,
+    /// The number of instructions executed since execution started
+    instructions_executed: u64,
+    /// A flag that can be set to interrupt execution
+    interrupt: InterruptHandle,
+    /// A function to call before each instruction is executed
+    #[cfg(feature = "instrument")]
+    instr_hook: Option<InstrHook>,
+    /// The call stack height at which stepping through a function started
+    step_height: Option<usize>,
+    /// Whether the first part of the next superinstruction has already run
+    super_first_done: bool,
+    /// Time and call counts, if profiling is enabled
+    profile: Option<Profile>,
     /// The paths of files currently being imported (used to detect import cycles)
     current_imports: Arc<Mutex<HashSet<PathBuf>>>,
     /// The stacks of imported files
@@ -56,6 +82,26 @@ pub struct Uiua {
     pub(crate) diagnostics: BTreeSet<Diagnostic>,
     /// Print diagnostics as they are encountered
     pub(crate) print_diagnostics: bool,
+    /// How values and diagnostics are printed
+    output_mode: OutputMode,
+    /// Print the instructions of each item as it is compiled
+    pub(crate) print_instrs: bool,
+    /// The results of the assertions in test scopes
+    pub(crate) test_results: TestResults,
+    /// The spans of the assertions that have passed on the test scope line being run
+    pub(crate) passed_asserts: Option<HashSet<usize>>,
+    /// Errors encountered while loading the current input
+    pub(crate) load_errors: Vec<UiuaError>,
+    /// Whether the remaining items should only be compiled because an error has occurred
+    pub(crate) compile_only: bool,
+    /// The binding currently being compiled
+    pub(crate) current_binding: Option<CurrentBinding>,
+    /// The names of the local bindings of each function currently being compiled
+    pub(crate) local_names: Vec<Vec<Ident>>,
+    /// Constants that have been compiled, so that identical constants can share their data
+    pub(crate) constants: HashSet<PooledConstant>,
+    /// The documentation of bindings, by global index
+    pub(crate) binding_docs: HashMap<usize, String>,
     /// Arguments passed from the command line
     cli_arguments: Vec<String>,
     /// File that was passed to the interpreter for execution
@@ -91,6 +137,7 @@ impl Default for Scope {
                 call_span: 0,
                 pc: 0,
                 spans: Vec::new(),
+                locals: Vec::new(),
             }],
             names: HashMap::new(),
             local: false,
@@ -116,6 +163,8 @@ struct StackFrame {
     pc: usize,
     /// Additional spans for error reporting
     spans: Vec<(usize, Option<Primitive>)>,
+    /// The values of the function's local bindings
+    locals: Vec<Value>,
 }
 
 impl Default for Uiua {
@@ -124,6 +173,52 @@ impl Default for Uiua {
     }
 }
 
+/// A handle that can be used to interrupt a running [`Uiua`] runtime
+///
+/// Interrupting makes the runtime stop at the next instruction with [`UiuaError::Interrupted`]
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle(Arc<AtomicBool>);
+
+impl InterruptHandle {
+    /// Interrupt execution
+    pub fn interrupt(&self) {
+        self.0.store(true, atomic::Ordering::Relaxed);
+    }
+    /// Check if execution has been interrupted
+    pub fn is_interrupted(&self) -> bool {
+        self.0.load(atomic::Ordering::Relaxed)
+    }
+    fn reset(&self) {
+        self.0.store(false, atomic::Ordering::Relaxed);
+    }
+}
+
+/// The results of running a program's test scopes with [`RunMode::Test`]
+#[derive(Debug, Clone, Default)]
+pub struct TestResults {
+    /// The number of assertions that passed
+    pub passed: usize,
+    /// The errors of the lines in test scopes that failed
+    pub failures: Vec<UiuaError>,
+}
+
+/// A function that is called before each instruction is executed
+#[cfg(feature = "instrument")]
+pub type InstrHook = Arc<dyn Fn(&Instr, &[Value]) + Send + Sync>;
+
+/// The default maximum number of elements that a single array operation may create
+pub const DEFAULT_ELEMENT_LIMIT: usize = 1 << 30;
+
+/// The default maximum depth of the call stack
+pub const DEFAULT_CALL_DEPTH_LIMIT: usize = 100_000;
+
+/// The maximum number of functions called by modifiers that may be running at once
+///
+/// Unlike other calls, a function called by a modifier runs on the native stack,
+/// so this limit is much lower than [`DEFAULT_CALL_DEPTH_LIMIT`].
+/// Debug builds use far more native stack per call.
+pub const MAX_NATIVE_CALL_DEPTH: usize = if cfg!(debug_assertions) { 30 } else { 150 };
+
 /// A mode that affects how non-binding lines are run
 ///
 /// Regardless of the mode, lines with a call to `import` will always be run
@@ -150,6 +245,18 @@ impl FromStr for RunMode {
     }
 }
 
+/// How values and diagnostics are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum OutputMode {
+    /// Pretty output for streams that are terminals and plain output for the others
+    #[default]
+    Auto,
+    /// Boxed values and colored diagnostics
+    Pretty,
+    /// Values without box-drawing characters and diagnostics without color
+    Plain,
+}
+
 impl Uiua {
     /// Create a new Uiua runtime with the standard IO backend
     pub fn with_native_sys() -> Self {
@@ -174,34 +281,165 @@ impl Uiua {
             diagnostics: BTreeSet::new(),
             backend: Arc::new(NativeSys),
             print_diagnostics: false,
+            output_mode: OutputMode::Auto,
+            print_instrs: false,
+            current_binding: None,
+            load_errors: Vec::new(),
+            compile_only: false,
+            test_results: TestResults::default(),
+            passed_asserts: None,
+            local_names: Vec::new(),
+            constants: HashSet::new(),
+            binding_docs: HashMap::new(),
             cli_arguments: Vec::new(),
             cli_file_path: PathBuf::new(),
             execution_limit: None,
             execution_start: 0.0,
+            element_limit: DEFAULT_ELEMENT_LIMIT,
+            call_depth_limit: DEFAULT_CALL_DEPTH_LIMIT,
+            native_call_depth: 0,
+            instruction_limit: None,
+            instructions_executed: 0,
+            interrupt: InterruptHandle::default(),
+            #[cfg(feature = "instrument")]
+            instr_hook: None,
+            step_height: None,
+            super_first_done: false,
+            profile: None,
         }
     }
     /// Create a new Uiua runtime with a custom IO backend
     pub fn with_backend(backend: impl SysBackend) -> Self {
+        Self::with_shared_backend(Arc::new(backend))
+    }
+    /// Create a new Uiua runtime with an IO backend that may be shared with other runtimes
+    pub fn with_shared_backend(backend: Arc<dyn SysBackend>) -> Self {
         Uiua {
-            backend: Arc::new(backend),
+            backend,
             ..Default::default()
         }
     }
+    /// Create a new Uiua runtime with an async IO backend
+    ///
+    /// The backend is wrapped in a [`BlockingBackend`], which blocks the current thread on its futures.
+    pub fn with_async_backend(backend: impl AsyncSysBackend) -> Self {
+        Self::with_backend(BlockingBackend(backend))
+    }
     pub fn backend(&self) -> &dyn SysBackend {
         &*self.backend
     }
     pub fn downcast_backend<T: SysBackend>(&self) -> Option<&T> {
         self.backend.any().downcast_ref()
     }
+    pub fn downcast_async_backend<T: AsyncSysBackend>(&self) -> Option<&T> {
+        self.downcast_backend::<BlockingBackend<T>>()
+            .map(BlockingBackend::inner)
+    }
     pub fn print_diagnostics(mut self, print_diagnostics: bool) -> Self {
         self.print_diagnostics = print_diagnostics;
         self
     }
+    /// Set how values and diagnostics are printed
+    pub fn with_output_mode(mut self, output_mode: OutputMode) -> Self {
+        self.output_mode = output_mode;
+        self
+    }
+    /// Whether values printed to stdout should be pretty
+    pub fn pretty_stdout(&self) -> bool {
+        match self.output_mode {
+            OutputMode::Auto => self.backend.stdout_is_terminal(),
+            OutputMode::Pretty => true,
+            OutputMode::Plain => false,
+        }
+    }
+    /// Whether errors and diagnostics printed to stderr should be colored
+    pub fn pretty_stderr(&self) -> bool {
+        match self.output_mode {
+            OutputMode::Auto => self.backend.stderr_is_terminal(),
+            OutputMode::Pretty => true,
+            OutputMode::Plain => false,
+        }
+    }
+    /// Format a value for printing to stdout according to the output mode
+    pub fn show_value(&self, value: &Value) -> String {
+        if self.pretty_stdout() {
+            value.show()
+        } else {
+            value.plain_string()
+        }
+    }
+    /// Print the disassembled instructions of each item to stderr as it is compiled
+    pub fn print_instrs(mut self, print_instrs: bool) -> Self {
+        self.print_instrs = print_instrs;
+        self
+    }
     /// Limit the execution duration
     pub fn with_execution_limit(mut self, limit: Duration) -> Self {
         self.execution_limit = Some(limit.as_millis() as f64);
         self
     }
+    /// Limit the depth of the call stack
+    ///
+    /// Default is [`DEFAULT_CALL_DEPTH_LIMIT`]
+    pub fn with_call_depth_limit(mut self, limit: usize) -> Self {
+        self.call_depth_limit = limit;
+        self
+    }
+    /// Limit the number of instructions that may be executed
+    pub fn with_instruction_limit(mut self, limit: u64) -> Self {
+        self.instruction_limit = Some(limit);
+        self
+    }
+    /// Use the given [`InterruptHandle`] to allow interrupting execution
+    pub fn with_interrupt_handle(mut self, handle: InterruptHandle) -> Self {
+        self.interrupt = handle;
+        self
+    }
+    /// Get a handle that can be used to interrupt execution from another thread
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+    /// Set a function to call before each instruction is executed
+    ///
+    /// The function is passed the instruction and the current stack.
+    /// This is useful for building debuggers, tracers, and coverage tools.
+    #[cfg(feature = "instrument")]
+    pub fn with_instr_hook(
+        mut self,
+        hook: impl Fn(&Instr, &[Value]) + Send + Sync + 'static,
+    ) -> Self {
+        self.instr_hook = Some(Arc::new(hook));
+        self
+    }
+    /// Record the time spent in and the number of calls to each primitive and named function
+    ///
+    /// The results can be retrieved with [`Uiua::profile`].
+    /// Named functions are not inlined while profiling so that their calls are recorded.
+    pub fn with_profiling(mut self, profiling: bool) -> Self {
+        self.profile = profiling.then(Profile::default);
+        self
+    }
+    /// Take the results of the test scopes run so far
+    ///
+    /// Lines in test scopes that fail are only recorded in [`RunMode::Test`].
+    /// In other modes, a failure stops the program.
+    pub fn take_test_results(&mut self) -> TestResults {
+        take(&mut self.test_results)
+    }
+    /// Get the profile recorded so far, if profiling is enabled
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+    pub(crate) fn is_profiling(&self) -> bool {
+        self.profile.is_some()
+    }
+    /// Limit the number of elements that a single array operation may create
+    ///
+    /// Default is [`DEFAULT_ELEMENT_LIMIT`]
+    pub fn with_element_limit(mut self, limit: usize) -> Self {
+        self.element_limit = limit;
+        self
+    }
     /// Set the [`RunMode`]
     ///
     /// Default is [`RunMode::Normal`]
@@ -259,17 +497,30 @@ impl Uiua {
         self.higher_scopes.push(take(&mut self.scope));
         self.scope.local = local;
         let start_height = self.stack.len();
-        f(self)?;
-        let end_height = self.stack.len();
+        let res = f(self);
         self.scope = self.higher_scopes.pop().unwrap();
+        res?;
+        let end_height = self.stack.len();
         Ok(self.stack.split_off(start_height.min(end_height)))
     }
     fn load_impl(&mut self, input: &str, path: Option<&Path>) -> UiuaResult {
+        // Imports count toward the limits of the input that imports them
         self.execution_start = instant::now();
-        let (items, errors, diagnostics) = parse(input, path);
+        self.instructions_executed = 0;
+        self.interrupt.reset();
+        self.load_input(input, path)
+    }
+    fn load_input(&mut self, input: &str, path: Option<&Path>) -> UiuaResult {
+        let (items, errors, mut diagnostics) = parse(input, path);
+        // Only whole files are checked for unused bindings, because
+        // bindings made on one line of a session may be used on later lines
+        if path.is_some() {
+            diagnostics.extend(binding_diagnostics(&items));
+        }
         if self.print_diagnostics {
             for diagnostic in diagnostics {
-                println!("{}", diagnostic.show(true));
+                let diagnostic = format!("{}\n", diagnostic.show(self.pretty_stderr()));
+                _ = self.backend.print_str_stderr(&diagnostic);
             }
         } else {
             self.diagnostics.extend(diagnostics);
@@ -280,7 +531,12 @@ impl Uiua {
         if let Some(path) = path {
             self.current_imports.lock().insert(path.into());
         }
-        let res = match catch_unwind(AssertUnwindSafe(|| self.items(items, false))) {
+        // Imports are loaded in the middle of loading another input
+        let outer_errors = take(&mut self.load_errors);
+        let outer_compile_only = replace(&mut self.compile_only, false);
+        let res = match catch_unwind(AssertUnwindSafe(|| {
+            self.restore_on_error(|env| env.items(items, false))
+        })) {
             Ok(res) => res,
             Err(_) => Err(self.error(format!(
                 "\
@@ -298,8 +554,77 @@ code:
         if let Some(path) = path {
             self.current_imports.lock().remove(path);
         }
+        let mut errors = replace(&mut self.load_errors, outer_errors);
+        self.compile_only = outer_compile_only;
+        errors.extend(res.err());
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.pop().unwrap()),
+            _ => Err(UiuaError::Multi(errors)),
+        }
+    }
+    /// Run a function, cleaning up intermediate state if it fails so that the runtime can continue to be used
+    pub(crate) fn restore_on_error(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> UiuaResult,
+    ) -> UiuaResult {
+        let array_height = self.scope.array.len();
+        let inline_height = self.inline_stack.len();
+        let under_height = self.under_stack.len();
+        let function_height = self.new_functions.len();
+        let res = f(self);
+        if res.is_err() {
+            self.scope.array.truncate(array_height);
+            self.inline_stack.truncate(inline_height);
+            self.under_stack.truncate(under_height);
+            self.new_functions.truncate(function_height);
+        }
         res
     }
+    /// Get a human-readable listing of a function's instructions
+    ///
+    /// Each instruction is listed with the span of the code it was compiled from.
+    /// Listings of any functions the instructions push follow the function's own listing.
+    pub fn disassemble(&self, f: &Function) -> String {
+        let mut listing = String::new();
+        let mut queue = vec![f.clone()];
+        let mut i = 0;
+        while let Some(f) = queue.get(i).cloned() {
+            i += 1;
+            if i > 1 {
+                listing.push('\n');
+            }
+            listing.push_str(&format!("{} {}\n", f.id, f.signature()));
+            let spans = self.spans.lock();
+            for (j, instr) in f.instrs.iter().enumerate() {
+                let span = instr.span().map(|span| &spans[span]);
+                let text = match instr {
+                    Instr::Prim(prim, _) => match prim.name() {
+                        Some(name) if name != prim.to_string() => format!("{instr} ({name})"),
+                        _ => instr.to_string(),
+                    },
+                    instr => instr.to_string(),
+                };
+                let line = match span {
+                    Some(span) => format!("{j:>4}  {text:<24} {span}"),
+                    None => format!("{j:>4}  {text}"),
+                };
+                listing.push_str(&line);
+                listing.push('\n');
+                // Queue pushed functions to be listed
+                if let Instr::Push(val) = instr {
+                    if let Value::Func(fs) = &**val {
+                        for f in fs.data.iter() {
+                            if f.as_primitive().is_none() && !queue.iter().any(|g| g == &**f) {
+                                queue.push((**f).clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        listing
+    }
     fn trace_error(&self, mut error: UiuaError, frame: StackFrame) -> UiuaError {
         let mut frames = Vec::new();
         for (span, prim) in &frame.spans {
@@ -307,12 +632,14 @@ code:
                 frames.push(TraceFrame {
                     id: FunctionId::Primitive(*prim),
                     span: self.spans.lock()[*span].clone(),
+                    instr: None,
                 });
             }
         }
         frames.push(TraceFrame {
             id: frame.function.id.clone(),
             span: self.spans.lock()[frame.call_span].clone(),
+            instr: frame.function.instrs.get(frame.pc).cloned(),
         });
         if let UiuaError::Traced { trace, .. } = &mut error {
             trace.extend(frames);
@@ -332,7 +659,7 @@ code:
             )));
         }
         if !self.imports.lock().contains_key(path) {
-            let import = self.in_scope(false, |env| env.load_str_path(input, path).map(drop))?;
+            let import = self.in_scope(false, |env| env.load_input(input, Some(path)).map(drop))?;
             self.imports.lock().insert(path.into(), import);
         }
         self.stack.extend(self.imports.lock()[path].iter().cloned());
@@ -344,162 +671,355 @@ code:
             function: Arc::new(func),
             call_span: 0,
             spans: Vec::new(),
+            locals: Vec::new(),
             pc: 0,
         })
     }
+    fn push_frame(&mut self, frame: StackFrame) {
+        if let (Some(profile), FunctionId::Named(name)) = (&mut self.profile, &frame.function.id) {
+            profile.begin_function(self.scope.call.len(), name.clone());
+        }
+        self.scope.call.push(frame);
+    }
+    fn pop_frame(&mut self) {
+        self.scope.call.pop();
+        if let Some(profile) = &mut self.profile {
+            profile.end_function(self.scope.call.len());
+        }
+    }
     fn exec(&mut self, frame: StackFrame) -> UiuaResult {
+        self.check_call_depth(frame.call_span)?;
+        if self.native_call_depth >= MAX_NATIVE_CALL_DEPTH {
+            let span = self.spans.lock()[frame.call_span].clone();
+            return Err(UiuaError::Run(span.sp(format!(
+                "Execution limit exceeded: functions called by modifiers \
+                are more than {MAX_NATIVE_CALL_DEPTH} calls deep"
+            ))));
+        }
+        self.native_call_depth += 1;
         let ret_height = self.scope.call.len();
-        self.scope.call.push(frame);
+        self.push_frame(frame);
+        let mut res = Ok(());
         while self.scope.call.len() > ret_height {
-            let frame = self.scope.call.last().unwrap();
-            let Some(instr) = frame.function.instrs.get(frame.pc) else {
-                self.scope.call.pop();
-                continue;
-            };
-            // Uncomment to debug
-            // if !self.scope.array.is_empty() {
-            //     print!("array: ");
-            //     for val in &self.scope.array {
-            //         print!("{:?} ", val);
-            //     }
-            //     println!();
-            // }
-            // for val in &self.stack {
-            //     print!("{:?} ", val);
-            // }
-            // println!();
-            // println!("  {:?}", instr);
-            let res = match instr {
-                Instr::Push(val) => {
-                    self.stack.push(Value::clone(val));
+            res = self.exec_step(ret_height);
+            if res.is_err() {
+                break;
+            }
+        }
+        self.native_call_depth -= 1;
+        res
+    }
+    /// Execute the next instruction of the top frame
+    ///
+    /// Frames at or below `ret_height` are not popped if an error occurs
+    #[inline]
+    fn exec_step(&mut self, ret_height: usize) -> UiuaResult {
+        let frame = self.scope.call.last().unwrap();
+        let Some(instr) = frame.function.instrs.get(frame.pc) else {
+            self.pop_frame();
+            return Ok(());
+        };
+        // Uncomment to debug
+        // if !self.scope.array.is_empty() {
+        //     print!("array: ");
+        //     for val in &self.scope.array {
+        //         print!("{:?} ", val);
+        //     }
+        //     println!();
+        // }
+        // for val in &self.stack {
+        //     print!("{:?} ", val);
+        // }
+        // println!();
+        // println!("  {:?}", instr);
+        #[cfg(feature = "instrument")]
+        if let Some(hook) = &self.instr_hook {
+            hook(instr, &self.stack);
+        }
+        let res = match instr {
+            Instr::Push(val) => {
+                self.stack.push(Value::clone(val));
+                Ok(())
+            }
+            Instr::BeginArray => {
+                self.scope.array.push(self.stack.len());
+                Ok(())
+            }
+            &Instr::EndArray { span, constant } => (|| {
+                let start = self.scope.array.pop().unwrap();
+                self.push_span(span, None);
+                let values = self.stack.drain(start..).rev();
+                let values: Vec<Value> = if constant {
+                    values
+                        .map(Function::constant)
+                        .map(Arc::new)
+                        .map(Value::from)
+                        .collect()
+                } else {
+                    values.collect()
+                };
+                let val = if values.is_empty() && constant {
+                    Array::<Arc<Function>>::default().into()
+                } else {
+                    Value::from_row_values(values, self)?
+                };
+                self.pop_span();
+                self.push(val);
+                Ok(())
+            })(),
+            // Calls and recursion push a new frame onto the call stack
+            // instead of recursing so that deep calls don't overflow the native stack
+            &Instr::Call(span) => match self.pop("called function").map(Value::into_function) {
+                Ok(Ok(f)) => match self.check_call_depth(span) {
+                    Ok(()) => {
+                        self.scope.call.last_mut().unwrap().pc += 1;
+                        self.push_frame(StackFrame {
+                            function: f,
+                            call_span: span,
+                            spans: Vec::new(),
+                            locals: Vec::new(),
+                            pc: 0,
+                        });
+                        return Ok(());
+                    }
+                    Err(e) => Err(e),
+                },
+                Ok(Err(val)) => {
+                    self.push(val);
                     Ok(())
                 }
-                Instr::BeginArray => {
-                    self.scope.array.push(self.stack.len());
-                    Ok(())
+                Err(e) => Err(e),
+            },
+            &Instr::CallGlobal { index, span, .. } => {
+                let value = self.globals.lock()[index].clone();
+                match value.into_function() {
+                    Ok(f) => match self.check_call_depth(span) {
+                        Ok(()) => {
+                            self.scope.call.last_mut().unwrap().pc += 1;
+                            self.push_frame(StackFrame {
+                                function: f,
+                                call_span: span,
+                                spans: Vec::new(),
+                                locals: Vec::new(),
+                                pc: 0,
+                            });
+                            return Ok(());
+                        }
+                        Err(e) => Err(e),
+                    },
+                    Err(val) => {
+                        self.push(val);
+                        Ok(())
+                    }
                 }
-                &Instr::EndArray { span, constant } => (|| {
-                    let start = self.scope.array.pop().unwrap();
-                    self.push_span(span, None);
-                    let values = self.stack.drain(start..).rev();
-                    let values: Vec<Value> = if constant {
-                        values
-                            .map(Function::constant)
-                            .map(Arc::new)
-                            .map(Value::from)
-                            .collect()
-                    } else {
-                        values.collect()
-                    };
-                    let val = if values.is_empty() && constant {
-                        Array::<Arc<Function>>::default().into()
-                    } else {
-                        Value::from_row_values(values, self)?
-                    };
-                    self.pop_span();
-                    self.push(val);
-                    Ok(())
-                })(),
-                &Instr::Prim(prim, span) => (|| {
-                    self.push_span(span, Some(prim));
-                    prim.run(self)?;
-                    self.pop_span();
-                    Ok(())
-                })(),
-                &Instr::Call(span) => self
-                    .pop("called function")
-                    .and_then(|f| self.call_with_span(f, span)),
-                Instr::Dynamic(df) => df.f.clone()(self),
-                &Instr::PushTemp { count, span, kind } => (|| {
-                    self.push_span(span, None);
-                    for _ in 0..count {
-                        let value = self.pop("value to move to temp")?;
-                        let stack = match kind {
-                            TempKind::Inline => &mut self.inline_stack,
-                            TempKind::Under => &mut self.under_stack,
-                        };
-                        stack.push(value);
+            }
+            &Instr::Prim(Primitive::Recur, span) => match self.recur_target(span) {
+                Ok(Some((n, f))) => {
+                    let frame = self.scope.call.last_mut().unwrap();
+                    if n == 1 && frame.pc + 1 == frame.function.instrs.len() {
+                        // Tail recursion reuses the current frame
+                        frame.pc = 0;
+                        frame.spans.clear();
+                        if let (Some(profile), FunctionId::Named(name)) = (&mut self.profile, &f.id)
+                        {
+                            profile.record_call(ProfileTarget::Function(name.clone()));
+                        }
+                        return Ok(());
                     }
-                    self.pop_span();
-                    Ok(())
-                })(),
-                &Instr::PopTemp { count, span, kind } => (|| {
-                    self.push_span(span, None);
-                    for _ in 0..count {
-                        let stack = match kind {
-                            TempKind::Inline => &mut self.inline_stack,
-                            TempKind::Under => &mut self.under_stack,
-                        };
-                        let value = stack.pop().ok_or_else(|| {
-                            self.error("Temp stack was empty when evaluating value to pop")
-                        })?;
-                        self.push(value);
+                    match self.check_call_depth(span) {
+                        Ok(()) => {
+                            self.scope.call.last_mut().unwrap().pc += 1;
+                            self.push_frame(StackFrame {
+                                function: f,
+                                call_span: span,
+                                spans: Vec::new(),
+                                locals: Vec::new(),
+                                pc: 0,
+                            });
+                            return Ok(());
+                        }
+                        Err(e) => Err(e),
                     }
-                    self.pop_span();
-                    Ok(())
-                })(),
-                &Instr::CopyTemp {
-                    offset,
-                    count,
-                    span,
-                    kind,
-                } => (|| {
-                    self.push_span(span, None);
+                }
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            },
+            &Instr::Prim(prim, span) => self.run_prim(prim, span),
+            Instr::Super { first, prim, span } => {
+                let (prim, span) = (*prim, *span);
+                if replace(&mut self.super_first_done, false) {
+                    self.run_prim(prim, span)
+                } else {
+                    let res = match &**first {
+                        Instr::Push(val) => {
+                            self.stack.push(Value::clone(val));
+                            Ok(())
+                        }
+                        &Instr::Prim(first, first_span) => self.run_prim(first, first_span),
+                        _ => unreachable!("invalid superinstruction"),
+                    };
+                    // When stepping, each part of a superinstruction is its own step
+                    if res.is_ok() && self.step_height.is_some() {
+                        self.super_first_done = true;
+                        return Ok(());
+                    }
+                    res.and_then(|_| self.run_prim(prim, span))
+                }
+            }
+            Instr::Dynamic(df) => {
+                let f = df.f.clone();
+                self.catch_panic(|env| f(env))
+            }
+            &Instr::BindLocal { index, span } => (|| {
+                self.push_span(span, None);
+                let value = self.pop("value to bind")?;
+                self.pop_span();
+                let locals = &mut self.scope.call.last_mut().unwrap().locals;
+                if locals.len() <= index {
+                    locals.resize(index + 1, Value::default());
+                }
+                locals[index] = value;
+                Ok(())
+            })(),
+            &Instr::PushLocal { index, .. } => {
+                let value = self.scope.call.last().unwrap().locals[index].clone();
+                self.push(value);
+                Ok(())
+            }
+            &Instr::PushTemp { count, span, kind } => (|| {
+                self.push_span(span, None);
+                for _ in 0..count {
+                    let value = self.pop("value to move to temp")?;
                     let stack = match kind {
                         TempKind::Inline => &mut self.inline_stack,
                         TempKind::Under => &mut self.under_stack,
                     };
-                    if stack.len() < offset + count {
-                        return Err(
-                            self.error("Temp stack was empty when evaluating value to copy")
-                        );
-                    }
-                    let start = stack.len() - offset;
-                    for i in 0..count {
-                        let stack = match kind {
-                            TempKind::Inline => &mut self.inline_stack,
-                            TempKind::Under => &mut self.under_stack,
-                        };
-                        let value = stack[start - i - 1].clone();
-                        self.push(value);
-                    }
-                    self.pop_span();
-                    Ok(())
-                })(),
-                &Instr::DropTemp { count, span, kind } => (|| {
-                    self.push_span(span, None);
+                    stack.push(value);
+                }
+                self.pop_span();
+                Ok(())
+            })(),
+            &Instr::PopTemp { count, span, kind } => (|| {
+                self.push_span(span, None);
+                for _ in 0..count {
                     let stack = match kind {
                         TempKind::Inline => &mut self.inline_stack,
                         TempKind::Under => &mut self.under_stack,
                     };
-                    if stack.len() < count {
-                        return Err(
-                            self.error("Temp stack was empty when evaluating value to drop")
-                        );
-                    }
-                    stack.truncate(stack.len() - count);
-                    self.pop_span();
-                    Ok(())
-                })(),
-            };
-            if let Err(mut err) = res {
-                // Trace errors
-                let frames = self
-                    .scope
-                    .call
-                    .split_off(ret_height.min(self.scope.call.len()));
-                for frame in frames {
-                    err = self.trace_error(err, frame);
+                    let value = stack.pop().ok_or_else(|| {
+                        self.error("Temp stack was empty when evaluating value to pop")
+                    })?;
+                    self.push(value);
                 }
-                return Err(err);
-            } else {
-                // Go to next instruction
-                self.scope.call.last_mut().unwrap().pc += 1;
-                if let Some(limit) = self.execution_limit {
-                    if instant::now() - self.execution_start > limit {
-                        return Err(UiuaError::Timeout(self.span()));
-                    }
+                self.pop_span();
+                Ok(())
+            })(),
+            &Instr::CopyTemp {
+                offset,
+                count,
+                span,
+                kind,
+            } => (|| {
+                self.push_span(span, None);
+                let stack = match kind {
+                    TempKind::Inline => &mut self.inline_stack,
+                    TempKind::Under => &mut self.under_stack,
+                };
+                if stack.len() < offset + count {
+                    return Err(self.error("Temp stack was empty when evaluating value to copy"));
+                }
+                let start = stack.len() - offset;
+                for i in 0..count {
+                    let stack = match kind {
+                        TempKind::Inline => &mut self.inline_stack,
+                        TempKind::Under => &mut self.under_stack,
+                    };
+                    let value = stack[start - i - 1].clone();
+                    self.push(value);
+                }
+                self.pop_span();
+                Ok(())
+            })(),
+            &Instr::DropTemp { count, span, kind } => (|| {
+                self.push_span(span, None);
+                let stack = match kind {
+                    TempKind::Inline => &mut self.inline_stack,
+                    TempKind::Under => &mut self.under_stack,
+                };
+                if stack.len() < count {
+                    return Err(self.error("Temp stack was empty when evaluating value to drop"));
                 }
+                stack.truncate(stack.len() - count);
+                self.pop_span();
+                Ok(())
+            })(),
+        };
+        let res = res.and_then(|_| {
+            // Go to next instruction
+            self.scope.call.last_mut().unwrap().pc += 1;
+            self.check_limits()
+        });
+        if let Err(mut err) = res {
+            // Trace errors
+            let frames = self
+                .scope
+                .call
+                .split_off(ret_height.min(self.scope.call.len()));
+            for frame in frames.into_iter().rev() {
+                err = self.trace_error(err, frame);
+            }
+            return Err(err);
+        }
+        Ok(())
+    }
+    fn run_prim(&mut self, prim: Primitive, span: usize) -> UiuaResult {
+        self.push_span(span, Some(prim));
+        if self.profile.is_some() {
+            let start = instant::now();
+            self.catch_panic(|env| prim.run(env))?;
+            if let Some(profile) = &mut self.profile {
+                profile.record(ProfileTarget::Primitive(prim), start);
+            }
+        } else {
+            self.catch_panic(|env| prim.run(env))?;
+        }
+        self.pop_span();
+        Ok(())
+    }
+    /// Run a function, turning a panic into an error at the current span
+    ///
+    /// This keeps a bug in a primitive from taking down the program embedding the interpreter.
+    fn catch_panic(&mut self, f: impl FnOnce(&mut Self) -> UiuaResult) -> UiuaResult {
+        match catch_unwind(AssertUnwindSafe(|| f(self))) {
+            Ok(res) => res,
+            Err(payload) => {
+                let message = (payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                    .or_else(|| payload.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".into());
+                Err(self.error(format!(
+                    "The interpreter panicked: {message}\n\
+                    This is a bug. Please report it at \
+                    http://github.com/uiua-lang/uiua/issues/new"
+                )))
+            }
+        }
+    }
+    /// Check for interruption and that no execution limits have been exceeded
+    fn check_limits(&mut self) -> UiuaResult {
+        if self.interrupt.is_interrupted() {
+            return Err(UiuaError::Interrupted(self.span()));
+        }
+        if let Some(limit) = self.execution_limit {
+            if instant::now() - self.execution_start > limit {
+                return Err(UiuaError::Timeout(self.span()));
+            }
+        }
+        if let Some(limit) = self.instruction_limit {
+            self.instructions_executed += 1;
+            if self.instructions_executed > limit {
+                return Err(self.error(format!(
+                    "Execution limit exceeded: more than {limit} instructions were executed"
+                )));
             }
         }
         Ok(())
@@ -526,6 +1046,7 @@ code:
             function: f.into(),
             call_span,
             spans: Vec::new(),
+            locals: Vec::new(),
             pc: 0,
         })
     }
@@ -540,6 +1061,80 @@ code:
         let call_span = self.span_index();
         self.call_function_with_span(f, call_span)
     }
+    /// Begin calling a function one instruction at a time
+    ///
+    /// Use [`Uiua::step`] to execute each instruction.
+    /// Any function that was previously being stepped through is abandoned.
+    pub fn begin_steps(&mut self, f: impl Into<Arc<Function>>) -> UiuaResult {
+        self.end_steps();
+        let frame = StackFrame {
+            function: f.into(),
+            call_span: self.span_index(),
+            spans: Vec::new(),
+            locals: Vec::new(),
+            pc: 0,
+        };
+        self.check_call_depth(frame.call_span)?;
+        self.step_height = Some(self.scope.call.len());
+        self.push_frame(frame);
+        Ok(())
+    }
+    /// Execute the next instruction of a function started with [`Uiua::begin_steps`]
+    ///
+    /// Returns `Ok(true)` if there are more instructions to execute.
+    /// Each part of a superinstruction is its own step.
+    /// Functions called by modifiers run to completion in a single step.
+    pub fn step(&mut self) -> UiuaResult<bool> {
+        let Some(height) = self.step_height else {
+            return Ok(false);
+        };
+        if self.scope.call.len() > height {
+            if let Err(e) = self.exec_step(height) {
+                self.step_height = None;
+                return Err(e);
+            }
+        }
+        // Pop any frames that have finished
+        while self.scope.call.len() > height {
+            let frame = self.scope.call.last().unwrap();
+            if frame.pc < frame.function.instrs.len() {
+                return Ok(true);
+            }
+            self.pop_frame();
+        }
+        self.step_height = None;
+        Ok(false)
+    }
+    /// Stop stepping through a function started with [`Uiua::begin_steps`]
+    pub fn end_steps(&mut self) {
+        if let Some(height) = self.step_height.take() {
+            self.scope.call.truncate(height);
+        }
+        self.super_first_done = false;
+    }
+    /// Get the span of the next instruction to be executed by [`Uiua::step`]
+    pub fn next_step_span(&self) -> Option<Span> {
+        let height = self.step_height?;
+        let frame = self.scope.call.get(height..)?.last()?;
+        let span = match frame.function.instrs.get(frame.pc)? {
+            Instr::Prim(_, span)
+            | Instr::Call(span)
+            | Instr::CallGlobal { span, .. }
+            | Instr::BindLocal { span, .. }
+            | Instr::PushLocal { span, .. }
+            | Instr::EndArray { span, .. }
+            | Instr::PushTemp { span, .. }
+            | Instr::PopTemp { span, .. }
+            | Instr::CopyTemp { span, .. }
+            | Instr::DropTemp { span, .. } => *span,
+            Instr::Super { first, span, .. } => match **first {
+                Instr::Prim(_, first_span) if !self.super_first_done => first_span,
+                _ => *span,
+            },
+            _ => frame.call_span,
+        };
+        Some(self.spans.lock()[span].clone())
+    }
     #[inline]
     pub fn recur(&mut self, n: usize) -> UiuaResult {
         if n == 0 {
@@ -555,6 +1150,41 @@ code:
         let f = self.scope.call[self.scope.call.len() - n].function.clone();
         self.call_function(f)
     }
+    /// Check that calling a function at the given span would not exceed the call depth limit
+    fn check_call_depth(&self, call_span: usize) -> UiuaResult {
+        let limit = self.call_depth_limit;
+        if self.scope.call.len() >= limit {
+            let span = self.spans.lock()[call_span].clone();
+            Err(UiuaError::Run(span.sp(format!(
+                "Execution limit exceeded: the call stack is more than {limit} calls deep"
+            ))))
+        } else {
+            Ok(())
+        }
+    }
+    /// Get the function that a [`Primitive::Recur`] instruction should call
+    ///
+    /// Returns `None` if there is nothing to recur into
+    fn recur_target(&mut self, span: usize) -> UiuaResult<Option<(usize, Arc<Function>)>> {
+        self.push_span(span, Some(Primitive::Recur));
+        let n = self
+            .pop(1)?
+            .as_nat(self, "Recur expects a natural number")?;
+        if n == 0 {
+            self.pop_span();
+            return Ok(None);
+        }
+        if n > self.scope.call.len() {
+            return Err(self.error(format!(
+                "Cannot recur {} levels up, only {} levels down",
+                n,
+                self.scope.call.len()
+            )));
+        }
+        self.pop_span();
+        let f = self.scope.call[self.scope.call.len() - n].function.clone();
+        Ok(Some((n, f)))
+    }
     pub fn call_catch_break(&mut self, f: Value) -> UiuaResult<bool> {
         match self.call(f) {
             Ok(_) => Ok(false),
@@ -591,6 +1221,10 @@ code:
                 .unwrap_or(frame.call_span)
         })
     }
+    /// Get the span of an instruction
+    pub fn instr_span(&self, instr: &Instr) -> Option<Span> {
+        instr.span().map(|span| self.spans.lock()[span].clone())
+    }
     /// Get the span of the current function call
     pub fn span(&self) -> Span {
         self.spans.lock()[self.span_index()].clone()
@@ -605,6 +1239,24 @@ code:
     pub fn error(&self, message: impl ToString) -> UiuaError {
         UiuaError::Run(self.span().clone().sp(message.to_string()))
     }
+    /// Check that an array with the given shape would not exceed the element limit
+    pub(crate) fn validate_element_count(&self, shape: &[usize], action: &str) -> UiuaResult {
+        let count = shape
+            .iter()
+            .try_fold(1usize, |acc, &dim| acc.checked_mul(dim));
+        match count {
+            Some(count) if count <= self.element_limit => Ok(()),
+            _ => {
+                let count = shape.iter().map(|&dim| dim as f64).product::<f64>();
+                Err(self.error(format!(
+                    "{action} with shape {} would create an array with {count} elements, \
+                    which exceeds the limit of {}",
+                    FormatShape(shape),
+                    self.element_limit
+                )))
+            }
+        }
+    }
     pub fn diagnostic(&mut self, message: impl Into<String>, kind: DiagnosticKind) {
         self.diagnostics
             .insert(Diagnostic::new(message.into(), self.span(), kind));
@@ -632,10 +1284,82 @@ code:
     pub fn push(&mut self, val: impl Into<Value>) {
         self.stack.push(val.into());
     }
+    /// Get the stack
+    pub fn stack(&self) -> &[Value] {
+        &self.stack
+    }
     /// Take the entire stack
     pub fn take_stack(&mut self) -> Vec<Value> {
         take(&mut self.stack)
     }
+    /// Run a string of code and take the resulting stack
+    ///
+    /// Values are in the order they were pushed, so the top of the stack is last.
+    pub fn run(&mut self, input: &str) -> UiuaResult<Vec<Value>> {
+        self.load_str(input)?;
+        Ok(self.take_stack())
+    }
+    /// Bind a value to a name so that code loaded later can refer to it
+    ///
+    /// Binding a function makes the name call it, as if it had been bound in code.
+    pub fn bind_value(&mut self, name: &str, value: impl Into<Value>) -> UiuaResult {
+        if name.is_empty() || !name.chars().all(is_ident_char) {
+            return Err(UiuaError::Run(
+                Span::Builtin.sp(format!("`{name}` is not a valid binding name")),
+            ));
+        }
+        let index = {
+            let mut globals = self.globals.lock();
+            globals.push(value.into());
+            globals.len() - 1
+        };
+        self.scope.names.insert(name.into(), index);
+        Ok(())
+    }
+    /// Bind a Rust function to a name so that code loaded later can call it
+    ///
+    /// The function pops its arguments from and pushes its outputs to the runtime's stack.
+    pub fn bind_function(
+        &mut self,
+        name: &str,
+        signature: Signature,
+        f: impl Fn(&mut Uiua) -> UiuaResult + Send + Sync + 'static,
+    ) -> UiuaResult {
+        let id = {
+            let mut hasher = DefaultHasher::new();
+            name.hash(&mut hasher);
+            hasher.finish()
+        };
+        let dynamic = DynamicFunction {
+            id,
+            f: Arc::new(f),
+            signature,
+        };
+        let f = Function::new(
+            FunctionId::Named(name.into()),
+            [Instr::Dynamic(dynamic)],
+            signature,
+        );
+        self.bind_value(name, f)
+    }
+    /// Bind a Rust function to a name, converting its arguments from values and its outputs to values
+    ///
+    /// The signature comes from the function's type.
+    /// The first argument is the top of the stack.
+    /// ```
+    /// # use uiua::{Uiua, Value};
+    /// let mut uiua = Uiua::with_native_sys();
+    /// uiua.bind_native("Hyp", |a: f64, b: f64| a.hypot(b)).unwrap();
+    /// assert_eq!(uiua.run("Hyp 3 4").unwrap(), [Value::from(5)]);
+    /// ```
+    pub fn bind_native<Args, F: NativeFn<Args>>(&mut self, name: &str, f: F) -> UiuaResult {
+        self.bind_function(name, F::SIGNATURE, move |env| f.call(env))
+    }
+    /// Get the value of a binding in the current scope
+    pub fn binding_value(&self, name: &str) -> Option<Value> {
+        let index = self.scope.names.get(name)?;
+        Some(self.globals.lock()[*index].clone())
+    }
     /// Get the values for all bindings in the current scope
     pub fn all_bindings_in_scope(&self) -> HashMap<Ident, Value> {
         let mut bindings = HashMap::new();
@@ -647,6 +1371,13 @@ code:
         }
         bindings
     }
+    /// Get the documentation of a binding in the current scope
+    ///
+    /// This comes from the comments directly above the binding.
+    pub fn binding_docs(&self, name: &str) -> Option<&str> {
+        let index = self.scope.names.get(name)?;
+        self.binding_docs.get(index).map(String::as_str)
+    }
     pub fn diagnostics(&self) -> &BTreeSet<Diagnostic> {
         &self.diagnostics
     }
@@ -656,6 +1387,10 @@ code:
     pub fn take_diagnostics(&mut self) -> BTreeSet<Diagnostic> {
         take(&mut self.diagnostics)
     }
+    /// Clone the top `n` values of the stack
+    ///
+    /// Array data is reference-counted, so this does not copy the arrays' elements.
+    /// This makes it cheap to snapshot the stack, as [`Primitive::Try`] does.
     pub fn clone_stack_top(&self, n: usize) -> Vec<Value> {
         self.stack.iter().rev().take(n).rev().cloned().collect()
     }
@@ -821,11 +1556,32 @@ code:
             imports: self.imports.clone(),
             diagnostics: BTreeSet::new(),
             print_diagnostics: self.print_diagnostics,
+            output_mode: self.output_mode,
+            print_instrs: self.print_instrs,
+            current_binding: None,
+            load_errors: Vec::new(),
+            compile_only: false,
+            test_results: TestResults::default(),
+            passed_asserts: None,
+            local_names: Vec::new(),
+            constants: HashSet::new(),
+            binding_docs: HashMap::new(),
             cli_arguments: self.cli_arguments.clone(),
             cli_file_path: self.cli_file_path.clone(),
             backend: self.backend.clone(),
             execution_limit: self.execution_limit,
             execution_start: self.execution_start,
+            element_limit: self.element_limit,
+            call_depth_limit: self.call_depth_limit,
+            native_call_depth: 0,
+            instruction_limit: self.instruction_limit,
+            instructions_executed: self.instructions_executed,
+            interrupt: self.interrupt.clone(),
+            #[cfg(feature = "instrument")]
+            instr_hook: self.instr_hook.clone(),
+            step_height: None,
+            super_first_done: false,
+            profile: None,
         };
         self.backend
             .spawn(env, Box::new(f))
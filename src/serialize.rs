@@ -0,0 +1,112 @@
+//! Serde support for [`Value`] and [`Array`]
+//!
+//! An array is serialized as its shape and its flat data.
+//! A value is serialized as an array tagged with its element type, so deserializing it
+//! gives back the same shape and element type.
+//! Boxes are serialized as arrays of values, but other functions cannot be serialized.
+
+use std::sync::Arc;
+
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    array::{Array, ArrayValue, FormatShape},
+    function::Function,
+    value::Value,
+};
+
+#[derive(Serialize)]
+struct ArrayRef<'a, T> {
+    shape: &'a [usize],
+    data: &'a [T],
+}
+
+#[derive(Deserialize)]
+struct RawArray<T> {
+    shape: Vec<usize>,
+    data: Vec<T>,
+}
+
+impl<T> RawArray<T> {
+    fn validate<E: de::Error>(self) -> Result<(Vec<usize>, Vec<T>), E> {
+        let count = (self.shape.iter()).try_fold(1usize, |count, &n| count.checked_mul(n));
+        let Some(count) = count else {
+            return Err(E::custom(format!(
+                "Shape {} has too many elements",
+                FormatShape(&self.shape)
+            )));
+        };
+        if count != self.data.len() {
+            return Err(E::custom(format!(
+                "Shape {} has {count} elements, but the data has {}",
+                FormatShape(&self.shape),
+                self.data.len()
+            )));
+        }
+        Ok((self.shape, self.data))
+    }
+}
+
+impl<T: ArrayValue + Serialize> Serialize for Array<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ArrayRef {
+            shape: &self.shape,
+            data: &self.data,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T: ArrayValue + Deserialize<'de>> Deserialize<'de> for Array<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (shape, data) = RawArray::deserialize(deserializer)?.validate()?;
+        Ok(Array::new(shape.as_slice(), data))
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Num(array) => serializer.serialize_newtype_variant("Value", 0, "num", array),
+            Value::Byte(array) => serializer.serialize_newtype_variant("Value", 1, "byte", array),
+            Value::Char(array) => serializer.serialize_newtype_variant("Value", 2, "char", array),
+            Value::Func(array) => {
+                let data = (array.data.iter())
+                    .map(|f| f.as_constant())
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| ser::Error::custom("Functions cannot be serialized"))?;
+                let boxes = ArrayRef {
+                    shape: &array.shape,
+                    data: &data,
+                };
+                serializer.serialize_newtype_variant("Value", 3, "box", &boxes)
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Value", rename_all = "lowercase")]
+enum ValueRepr {
+    Num(Array<f64>),
+    Byte(Array<u8>),
+    Char(Array<char>),
+    Box(RawArray<Value>),
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match ValueRepr::deserialize(deserializer)? {
+            ValueRepr::Num(array) => array.into(),
+            ValueRepr::Byte(array) => array.into(),
+            ValueRepr::Char(array) => array.into(),
+            ValueRepr::Box(boxes) => {
+                let (shape, data) = boxes.validate()?;
+                let data: Vec<_> = (data.into_iter())
+                    .map(|value| Arc::new(Function::constant(value)))
+                    .collect();
+                Array::new(shape.as_slice(), data).into()
+            }
+        })
+    }
+}
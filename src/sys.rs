@@ -1,31 +1,43 @@
+#[cfg(feature = "image")]
+use std::collections::{HashMap, HashSet};
 use std::{
     any::Any,
-    collections::{HashMap, HashSet},
     env,
     fs::{self, File},
-    io::{stderr, stdin, stdout, BufRead, Cursor, Read, Write},
+    io::{stderr, stdin, stdout, BufRead, Cursor, ErrorKind, Read, Seek, SeekFrom, Write},
     net::*,
-    process::Command,
+    path::{Component, Path, PathBuf},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
     sync::{
-        atomic::{self, AtomicU64},
+        atomic::{self, AtomicBool, AtomicU64},
         Arc, OnceLock,
     },
-    thread::{sleep, spawn, JoinHandle},
-    time::Duration,
+    thread::{self, sleep, spawn, JoinHandle},
+    time::{Duration, UNIX_EPOCH},
 };
 
 use bufreaderwriter::seq::BufReaderWriterSeq;
 use dashmap::DashMap;
 use enum_iterator::Sequence;
 use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+#[cfg(feature = "image")]
 use image::{DynamicImage, ImageOutputFormat};
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+#[cfg(feature = "image")]
 use tinyvec::tiny_vec;
 
 use crate::{
-    array::Array, cowslice::CowSlice, function::Function, grid_fmt::GridFmt, primitive::PrimDoc,
-    value::Value, Uiua, UiuaError, UiuaResult,
+    array::Array,
+    function::Function,
+    grid_fmt::GridFmt,
+    hash::HashAlgorithm,
+    npy::*,
+    plot::{self, PlotStyle},
+    primitive::PrimDoc,
+    time,
+    value::Value,
+    Uiua, UiuaError, UiuaResult,
 };
 
 pub fn example_ua<T>(f: impl FnOnce(&mut String) -> T) -> T {
@@ -104,23 +116,97 @@ sys_op! {
     (1(0), Prin, "&pf", "print and flush"),
     /// Print a value to stdout followed by a newline
     (1(0), Print, "&p", "print with newline"),
+    /// Print a value to stdout with a style
+    ///
+    /// Expects a style and a value.
+    /// The style is a string of space-separated words.
+    /// Each word is a color, a color prefixed with `on_` for the background, or one of `bold`, `dim`, `italic`, or `underline`.
+    /// Colors are `black`, `red`, `green`, `yellow`, `blue`, `magenta`, `cyan`, and `white`, optionally prefixed with `bright_`.
+    ///
+    /// The style is only applied when stdout is a terminal, so output redirected to a file is plain text.
+    /// No newline is printed.
+    ///
+    /// ex: &ps "bold red" "Error: "
+    (2(0), PrintStyled, "&ps", "print styled"),
+    /// Print a value to stderr followed by a newline
+    ///
+    /// This is useful for diagnostics that should not be mixed with a program's output when it is used in a pipeline.
+    (1(0), EPrint, "&ep", "print to stderr with newline"),
+    /// Flush stdout and stderr
+    ///
+    /// Anything printed but not yet written, such as a partial line when stdout is line-buffered, is written.
+    (0(0), Flush, "&flush", "flush"),
+    /// Set whether stdout is line-buffered
+    ///
+    /// Expects a boolean.
+    /// By default, stdout is flushed after every print.
+    /// When it is line-buffered, it is only flushed at newlines and by [&flush], which is faster for programs that print a lot.
+    ///
+    /// ex: &lbuf 1
+    ///   : &pf "Hello, "
+    ///   : &p "World!"
+    (1(0), LineBuffered, "&lbuf", "set line buffering"),
     /// Read a line from stdin
     ///
     /// The normal output is a string.
     /// If EOF is reached, the number `0` is returned instead.
     /// Programs that wish to properly handle EOF should check for this.
     (0, ScanLine, "&sc", "scan line"),
+    /// Read all of stdin
+    ///
+    /// Returns a string of everything until EOF.
+    (0, ScanAll, "&sca", "scan all"),
+    /// Check if stdin has reached EOF
+    ///
+    /// This blocks until there is more input or EOF is reached.
+    (0, StdinEof, "&eof", "end of stdin"),
     /// Get the size of the terminal
     ///
     /// The result is a 2-element array of the height and width of the terminal.
     /// Height comes first so that the array can be used as a shape in [reshape].
     (0, TermSize, "&ts", "terminal size"),
+    /// Set the terminal to raw mode
+    ///
+    /// Expects a boolean. In raw mode, input is not echoed and is available to [&key] as soon as keys are pressed.
+    /// Pressing Ctrl-C does not stop the program in raw mode.
+    ///
+    /// Raw mode is turned off automatically when the `uiua` command exits.
+    (1(0), RawMode, "&raw", "set raw mode"),
+    /// Get the next key press without waiting
+    ///
+    /// Returns the name of the key, or the number `0` if no key has been pressed.
+    /// Letters and symbols are returned as themselves, like `"a"` or `"?"`.
+    /// Other keys are returned as `"enter"`, `"tab"`, `"backspace"`, `"esc"`, `"up"`, `"down"`, `"left"`, `"right"`, `"home"`, `"end"`, `"pageup"`, `"pagedown"`, `"insert"`, `"delete"`, or `"f1"` through `"f12"`.
+    /// Modifiers are prefixed, like `"ctrl+c"` or `"alt+shift+up"`.
+    ///
+    /// Use [&raw] first to receive keys without waiting for a newline.
+    (0, Key, "&key", "key press"),
     /// Get the command line arguments
     ///
-    /// The first element will always be the name of your script
+    /// The result is a list of [box]ed strings.
+    /// The first element will always be the name of your script, followed by the arguments passed after it.
+    /// Use [drop]`1` to get only the arguments.
+    ///
+    /// Arguments are passed with `uiua run <PATH> <ARGS>` or `uiua <PATH> <ARGS>`.
     (0, Args, "&args", "arguments"),
+    /// Stop the program with an exit code
+    ///
+    /// The error cannot be caught by [try].
+    /// When running a file with the `uiua` command, the process exits with the code.
+    (1(0), Exit, "&exit", "exit"),
     /// Get the value of an environment variable
+    ///
+    /// Returns an empty string if the variable is not set.
     (1, Var, "&var", "environment variable"),
+    /// Set the value of an environment variable
+    ///
+    /// Expects a name and a value.
+    /// The variable is visible to commands run afterward.
+    (2(0), SetVar, "&setvar", "set environment variable"),
+    /// Get all environment variables
+    ///
+    /// Returns a list of [box]ed names and a list of [box]ed values, sorted by name.
+    (0(2), Vars, "&vars", "environment variables"),
     /// Run a command and wait for it to finish
     ///
     /// Standard IO will be inherited.
@@ -133,19 +219,101 @@ sys_op! {
     ///
     /// Expects either a string, a rank `2` character array, or a rank `1` array of [box] strings.
     (1(2), RunCapture, "&runc", "run command capture"),
+    /// Run a command with some input and wait for it to finish
+    ///
+    /// Expects a command and a string or byte array to write to its stdin.
+    /// Stdout and stderr will each be pushed to the stack as strings, followed by the exit code.
+    /// A process terminated by a signal has an exit code of `¯1`.
+    ///
+    /// The command can be either a string, a rank `2` character array, or a rank `1` array of [box] strings.
+    (2(3), RunPiped, "&runp", "run command piped"),
+    /// Start a command without waiting for it to finish
+    ///
+    /// Returns a handle. Writing to the handle with [&w] writes to the process's stdin.
+    /// Reading from the handle with [&rs], [&rb], [&ru], or [&rl] reads from its stdout.
+    /// Stderr will be inherited.
+    ///
+    /// Use [&runw] to wait for the process to finish.
+    ///
+    /// Expects either a string, a rank `2` character array, or a rank `1` array of [box] strings.
+    (1, RunStream, "&runs", "run command stream"),
+    /// Wait for a process started with [&runs] to finish
+    ///
+    /// The process's stdin is closed first. Returns the exit code, and closes the handle.
+    /// A process terminated by a signal has an exit code of `¯1`.
+    (1, RunWait, "&runw", "run command wait"),
+    /// Get the contents of the system clipboard as a string
+    (0, Clipboard, "&clip", "clipboard - get"),
+    /// Set the contents of the system clipboard
+    ///
+    /// Expects a string or a value, which is copied as it would be printed with [&pf].
+    (1(0), SetClipboard, "&setclip", "clipboard - set"),
     /// Change the current directory
     (1(0), ChangeDirectory, "&cd", "change directory"),
     /// Sleep for n seconds
     ///
+    /// Fractional seconds are allowed. Sleeping for [infinity] seconds never returns.
+    ///
     /// On the web, this example will hang for 1 second.
     /// ex: ⚂ &sl 1
     (1(0), Sleep, "&sl", "sleep"),
+    /// Get the time in seconds from a monotonic clock
+    ///
+    /// Unlike [now], which is the time since the Unix epoch, this clock never goes backwards.
+    /// Only the difference between two times is meaningful, which makes it good for measuring durations.
+    ///
+    /// ex: ⍜&mono(&sl 0.1)
+    (0, Monotonic, "&mono", "monotonic time"),
+    /// Convert a Unix time to a UTC date and time
+    ///
+    /// The result is a list of `[year month day hour minute second]`.
+    /// The second may be fractional.
+    /// ex: &dt 0
+    /// ex: &dt 1234567890.5
+    /// An array of times gives an array of dates.
+    /// ex: &dt [0 86400 1e9]
+    /// Times more than about a billion years from the Unix epoch are an error.
+    (1, DateTime, "&dt", "date time"),
+    /// Convert a UTC date and time to a Unix time
+    ///
+    /// This is the inverse of [&dt].
+    /// Missing trailing components default to the start of their period.
+    /// Components outside their normal ranges roll over.
+    /// Years, months, and days must be at most a billion in magnitude.
+    /// ex: &dts [1970 1 2]
+    /// ex: &dts [2000 13 1 12]
+    /// ex: &dts &dt 1234567890
+    (1, DateTimeSeconds, "&dts", "date time to seconds"),
+    /// Format a Unix time as a UTC date string
+    ///
+    /// The pattern uses `strftime`-style directives:
+    /// `%Y` year, `%y` 2-digit year, `%m` month, `%d` day, `%e` space-padded day, `%j` day of the year,
+    /// `%H` hour, `%I` 12-hour hour, `%p` AM/PM, `%M` minute, `%S` second, `%f` milliseconds,
+    /// `%B`/`%b` month name, `%A`/`%a` weekday name, `%u` weekday number (Monday is 1),
+    /// `%F` is `%Y-%m-%d`, `%T` is `%H:%M:%S`, `%s` Unix time, `%z` UTC offset, `%Z` time zone, and `%%` a `%`.
+    /// ex: &tf "%Y-%m-%d %H:%M:%S" 1234567890
+    /// ex: &tf "%a, %d %b %Y" 0
+    (2, FormatTime, "&tf", "format time"),
+    /// Parse a date string into a Unix time
+    ///
+    /// The pattern uses the same directives as [&tf].
+    /// Whitespace in the pattern matches any amount of whitespace.
+    /// `%z` accepts `Z`, `+hhmm`, or `+hh:mm`, and the time is converted to UTC.
+    /// ex: &tp "%F %T" "2009-02-13 23:31:30"
+    /// ex: &tp "%FT%T%z" "2009-02-14T01:31:30+02:00"
+    /// ex: &tf "%F" &tp "%d %B %Y" "4 July 1976"
+    (2, ParseTime, "&tp", "parse time"),
     /// Read at most n bytes from a stream
     (2, ReadStr, "&rs", "read to string"),
     /// Read at most n bytes from a stream
     (2, ReadBytes, "&rb", "read to bytes"),
     /// Read from a stream until a delimiter is reached
     (2, ReadUntil, "&ru", "read until"),
+    /// Read a line from a stream
+    ///
+    /// The line does not include the newline.
+    /// If the end of the stream is reached, the number `0` is returned instead, like [&sc].
+    (1, ReadLine, "&rl", "read line"),
     /// Write an array to a stream
     (2(0), Write, "&w", "write"),
     /// Run the code from a file in a scope
@@ -177,6 +345,75 @@ sys_op! {
     (1, FListDir, "&fld", "file - list directory"),
     /// Check if a path is a file
     (1, FIsFile, "&fif", "file - is file"),
+    /// Get the size of a file in bytes
+    (1, FSize, "&fsz", "file - size"),
+    /// Get the time a file was last modified
+    ///
+    /// The time is in seconds since the Unix epoch, like [now].
+    (1, FModified, "&fmod", "file - modified time"),
+    /// Wait for a file or directory to change
+    ///
+    /// Expects a path and a timeout in seconds.
+    /// Directories are watched recursively.
+    /// Returns a list of the paths that changed, or an empty list if the timeout is reached first.
+    /// Pass [infinity] to wait forever.
+    ///
+    /// The path keeps being watched after this returns, so changes made between calls are not missed.
+    (2, FWatch, "&fw", "file - watch"),
+    /// Create a directory
+    ///
+    /// Any missing parent directories are also created.
+    (1(0), FMakeDir, "&fmd", "file - make directory"),
+    /// Remove an empty directory
+    (1(0), FRemoveDir, "&frd", "file - remove directory"),
+    /// Delete a file
+    (1(0), FDelete, "&fde", "file - delete"),
+    /// Create a temporary file
+    ///
+    /// Returns the path of a new empty file in the system's temporary directory.
+    /// The file is deleted when the program finishes.
+    ///
+    /// See also: [&tmpd]
+    (0, TempFile, "&tmpf", "file - temporary file"),
+    /// Create a temporary directory
+    ///
+    /// Returns the path of a new empty directory in the system's temporary directory.
+    /// The directory and everything in it are deleted when the program finishes.
+    ///
+    /// See also: [&tmpf]
+    (0, TempDir, "&tmpd", "file - temporary directory"),
+    /// Join two paths
+    ///
+    /// The second path is joined onto the first with the platform's separator.
+    /// If the second path is absolute, it replaces the first.
+    /// ex: &pj "dir" "file.txt"
+    /// ex: &pj &pj "a" "b" "c.ua"
+    (2, PathJoin, "&pj", "path - join"),
+    /// Split a path into its directory, file stem, and extension
+    ///
+    /// The directory is on top, followed by the stem and the extension.
+    /// Missing parts are empty strings.
+    /// ex: &psp "dir/file.txt"
+    /// ex: &psp "archive.tar.gz"
+    (1(3), PathSplit, "&psp", "path - split"),
+    /// Normalize a path
+    ///
+    /// `.` components are removed, and `..` components remove the component before them.
+    /// Separators are converted to the platform's separator.
+    /// The file system is not accessed, so symlinks are not resolved.
+    /// ex: &pn "a/./b/../c.txt"
+    (1, PathNormalize, "&pn", "path - normalize"),
+    /// Convert a path to an absolute, normalized path
+    ///
+    /// Relative paths are relative to the current working directory.
+    /// The path does not need to exist.
+    (1, PathAbsolute, "&pa", "path - absolute"),
+    /// Move to a byte position in a file handle
+    ///
+    /// The first argument is the position, and the second is the handle.
+    /// A negative position counts back from the end of the file,
+    /// so `¯1` is the end and `¯2` is just before the last byte.
+    (2(0), FSeek, "&fsk", "file - seek"),
     /// Read all the contents of a file into a string
     ///
     /// Expects a path and returns a [rank]`1` character array.
@@ -188,12 +425,17 @@ sys_op! {
     /// Write the entire contents of an array to a file
     ///
     /// Expects a path and a [rank]`1` array or either numbers or characters.
+    /// Byte arrays, like those returned by [&frab], are written verbatim.
+    /// Numbers must be whole numbers from `0` to `255`.
     (2(0), FWriteAll, "&fwa", "file - write all"),
     /// Decode an image from a byte array
     ///
     /// Supported formats are `jpg`, `png`, `bmp`, `gif`, and `ico`.
     ///
-    /// See also: [&ime]
+    /// The result is a rank 3 array of numbers from `0` to `1`.
+    /// Its shape is the height, the width, and `4` for the red, green, blue, and alpha channels.
+    ///
+    /// See also: [&ime] [&imr]
     (1, ImDecode, "&imd", "image - decode"),
     /// Encode an image into a byte array with the specified format
     ///
@@ -211,13 +453,27 @@ sys_op! {
     ///
     /// Supported formats are `jpg`, `png`, `bmp`, `gif`, and `ico`.
     ///
-    /// See also: [&ims] [&imd]
+    /// See also: [&ims] [&imd] [&imw]
     (2, ImEncode, "&ime", "image - encode"),
+    /// Read an image from a file
+    ///
+    /// Expects a path. The result is the same as [&imd] on the file's bytes.
+    ///
+    /// See also: [&imw]
+    (1, ImRead, "&imr", "image - read file"),
+    /// Write an image to a file
+    ///
+    /// Expects a path and an image. The image must conform to the format of [&ime].
+    /// The format is chosen from the path's extension, which must be `jpg`, `jpeg`, `png`, `bmp`, `gif`, or `ico`.
+    ///
+    /// See also: [&imr]
+    (2(0), ImWrite, "&imw", "image - write file"),
     /// Show an image
     ///
     /// How the image is shown depends on the system backend.
     ///
-    /// In the default backend, the image is shown in the terminal.
+    /// In the default backend, the image is shown in the terminal if stdout is a terminal.
+    /// Otherwise, it is written to a temporary file and opened in the default image viewer.
     /// On the web, the image is shown in the output area.
     ///
     /// The image must be a rank 2 or 3 numeric array.
@@ -232,22 +488,114 @@ sys_op! {
     ///
     /// See also: [&ime]
     (1(0), ImShow, "&ims", "image - show"),
+    /// Plot a numeric array as an image
+    ///
+    /// The first argument is a string of space-separated style words.
+    /// The second is the data to plot. The result is an image array that can be passed to [&ims], [&imw], or [&ime].
+    ///
+    /// The plot kind is one of the following:
+    /// `line` (the default) plots a list as a line. Each row of a rank 2 array is plotted as a separate line.
+    /// `scatter` plots a list as points. A list of `[x y]` pairs is plotted as points at those coordinates.
+    /// `heatmap` plots a rank 2 array as a grid of colors, from dark blue for low values to yellow for high values.
+    ///
+    /// The size of the image may be given as `WIDTHxHEIGHT`, like `800x600`.
+    /// A color may be given as one of `blue`, `red`, `green`, `yellow`, `magenta`, `cyan`, `black`, or `white`.
+    /// Further lines are colored automatically.
+    ///
+    /// ex: &plot "line" ○÷10⇡100
+    /// ex: &plot "scatter red 300x300" ⍉⊟○∶○+η. ÷10⇡63
+    /// ex: &plot "heatmap" ⊞×.⇡10
+    (2, Plot, "&plot", "plot"),
     /// Encode a gif into a byte array
     ///
-    /// The first argument is a framerate in seconds.
+    /// The first argument is a frame rate in frames per second.
     /// The second argument is the gif data and must be a rank 3 or 4 numeric array.
     /// The rows of the array are the frames of the gif, and their format must conform to that of [&ime].
+    /// The gif loops forever.
+    ///
+    /// See also: [&gifs] [&gifw]
+    (2, GifEncode, "&gife", "gif - encode"),
+    /// Write a gif to a file
     ///
-    /// See also: [&gifs]
-    (1, GifEncode, "&gife", "gif - encode"),
+    /// Expects a path, a frame rate in frames per second, and the gif data.
+    /// The gif data must conform to the format of [&gife].
+    ///
+    /// See also: [&gife]
+    (3(0), GifWrite, "&gifw", "gif - write file"),
     /// Show a gif
     ///
-    /// The first argument is a framerate in seconds.
+    /// The first argument is a frame rate in frames per second.
     /// The second argument is the gif data and must be a rank 3 or 4 numeric array.
     /// The rows of the array are the frames of the gif, and their format must conform to that of [&ime].
     ///
+    /// In the default backend, the gif is written to a temporary file and opened in the default image viewer.
+    /// On the web, the gif is shown in the output area.
+    ///
     /// See also: [&gife]
-    (1(0), GifShow, "&gifs", "gif - show"),
+    (2(0), GifShow, "&gifs", "gif - show"),
+    /// Compress bytes with gzip
+    ///
+    /// Expects a string or a list of bytes. Returns a list of bytes.
+    /// ex: ⧻&gzc ↯1000 "a"
+    ///
+    /// See also: [&gzd]
+    (1, GzipCompress, "&gzc", "gzip - compress"),
+    /// Decompress gzip bytes
+    ///
+    /// Expects a list of bytes, like those returned by [&frab]. Returns a list of bytes.
+    /// ex: &gzd &gzc "hello"
+    ///
+    /// See also: [&gzc]
+    (1, GzipDecompress, "&gzd", "gzip - decompress"),
+    /// Decode an array from NumPy's `.npy` format
+    ///
+    /// Expects a list of bytes, like those returned by [&frab].
+    /// Booleans and unsigned bytes become byte arrays, and other numeric types become number arrays.
+    /// Unicode strings become character arrays. Strings of more than one character add an axis for the characters.
+    /// ex: &npyd &npye ↯2_3⇡6
+    ///
+    /// See also: [&npye] [&npzd]
+    (1, NpyDecode, "&npyd", "npy - decode"),
+    /// Encode an array in NumPy's `.npy` format
+    ///
+    /// Numbers are encoded as 64-bit floats, bytes as unsigned bytes, and characters as 1-character Unicode strings.
+    /// Returns a list of bytes, which can be written to a file with [&fwa].
+    /// ex: ⧻&npye [1 2 3]
+    ///
+    /// See also: [&npyd] [&npze]
+    (1, NpyEncode, "&npye", "npy - encode"),
+    /// Decode named arrays from NumPy's `.npz` format
+    ///
+    /// Expects a list of bytes. Returns a list of boxed names and a list of boxed arrays, with the names on top.
+    /// ex: &npzd &npze {"xs" "name"} {[1 2 3] "Uiua"}
+    ///
+    /// See also: [&npze] [&npyd]
+    (1(2), NpzDecode, "&npzd", "npz - decode"),
+    /// Encode named arrays in NumPy's `.npz` format
+    ///
+    /// Expects a list of boxed names and a list of boxed arrays. Returns a list of bytes.
+    /// The archive is not compressed.
+    ///
+    /// See also: [&npzd] [&npye]
+    (2, NpzEncode, "&npze", "npz - encode"),
+    /// Compute the digest of some bytes
+    ///
+    /// The first argument is the name of the algorithm, which is one of `sha256`, `sha1`, `md5`, or `crc32`.
+    /// The second argument is a string or a list of bytes. Returns a list of bytes.
+    /// ex: &hash "sha256" "abc"
+    /// ex: &hash "crc32" "abc"
+    ///
+    /// See also: [&hmac]
+    (2, Hash, "&hash", "hash - digest"),
+    /// Compute the HMAC of some bytes
+    ///
+    /// The first argument is the name of the algorithm, which is one of `sha256`, `sha1`, or `md5`.
+    /// The second argument is the key and the third is the message. Both are strings or lists of bytes.
+    /// Returns a list of bytes.
+    /// ex: &hmac "sha256" "key" "message"
+    ///
+    /// See also: [&hash]
+    (3, Hmac, "&hmac", "hash - hmac"),
     /// Decode audio from a byte array
     ///
     /// Only the `wav` format is supported.
@@ -270,6 +618,25 @@ sys_op! {
     ///
     /// See also: [&ap] [&ad]
     (2, AudioEncode, "&ae", "audio - encode"),
+    /// Read audio from a file
+    ///
+    /// Expects a path. Returns the audio samples and the sample rate, with the sample rate on top.
+    /// The samples are in the same format as [&ad].
+    /// This means that [&apr] can play the result directly.
+    ///
+    /// Only the `wav` format is supported.
+    ///
+    /// See also: [&aw]
+    (1(2), AudioRead, "&ar", "audio - read file"),
+    /// Write audio to a file
+    ///
+    /// Expects a path, a sample rate, and the audio samples.
+    /// The samples must conform to the format of [&ae].
+    ///
+    /// Only the `wav` format is supported.
+    ///
+    /// See also: [&ar]
+    (3(0), AudioWrite, "&aw", "audio - write file"),
     /// Play some audio
     ///
     /// The audio must be a rank 1 or 2 numeric array.
@@ -280,8 +647,17 @@ sys_op! {
     /// The samples must be between -1 and 1.
     /// The sample rate is [&asr].
     ///
-    /// See also: [&ae]
+    /// See also: [&ae] [&apr]
     (1(0), AudioPlay, "&ap", "audio - play"),
+    /// Play some audio at a specific sample rate
+    ///
+    /// The first argument is the sample rate in samples per second.
+    /// The second argument is the audio, which must conform to the format of [&ap].
+    ///
+    /// The audio is resampled to the output's sample rate if necessary.
+    ///
+    /// See also: [&ap] [&asr]
+    (2(0), AudioPlayRate, "&apr", "audio - play at sample rate"),
     /// Get the sample rate of the audio output backend
     ///
     /// ex: &asr
@@ -304,11 +680,50 @@ sys_op! {
     /// Set a TCP socket to non-blocking mode
     (1, TcpSetNonBlocking, "&tcpsnb", "tcp - set non-blocking"),
     /// Set the read timeout of a TCP socket in seconds
+    ///
+    /// If a read times out after some bytes have been received, those bytes are returned.
+    /// Pass [infinity] to remove the timeout.
     (2(0), TcpSetReadTimeout, "&tcpsrt", "tcp - set read timeout"),
     /// Set the write timeout of a TCP socket in seconds
+    ///
+    /// Pass [infinity] to remove the timeout.
     (2(0), TcpSetWriteTimeout, "&tcpswt", "tcp - set write timeout"),
     /// Get the connection address of a TCP socket
+    ///
+    /// For a TCP listener, this is the local address it is bound to.
+    /// This is useful for finding the port when listening on port `0`.
     (1, TcpAddr, "&tcpaddr", "tcp - address"),
+    /// Create a UDP socket and bind it to an address
+    (1, UdpBind, "&udpb", "udp - bind"),
+    /// Send a datagram with a UDP socket
+    ///
+    /// Expects a byte array or string to send, an address to send it to, and a UDP socket handle.
+    (3(0), UdpSend, "&udps", "udp - send"),
+    /// Receive a datagram with a UDP socket
+    ///
+    /// Expects a maximum number of bytes and a UDP socket handle.
+    /// Returns the received bytes and the address of the sender.
+    /// Bytes past the maximum in a datagram are discarded.
+    (2(2), UdpReceive, "&udpr", "udp - receive"),
+    /// Set the read timeout of a UDP socket in seconds
+    ///
+    /// Pass [infinity] to remove the timeout.
+    (2(0), UdpSetReadTimeout, "&udpsrt", "udp - set read timeout"),
+    /// Connect to a websocket server
+    ///
+    /// Expects a `ws://` or `wss://` URL and returns a handle.
+    /// Close the connection with [&cl].
+    (1, WsConnect, "&wsc", "websocket - connect"),
+    /// Send a message over a websocket
+    ///
+    /// Expects a message and a websocket handle.
+    /// Strings are sent as text frames, and byte arrays are sent as binary frames.
+    (2(0), WsSend, "&wss", "websocket - send"),
+    /// Receive a message from a websocket
+    ///
+    /// Text messages are returned as strings, and binary messages as byte arrays.
+    /// If the server closes the connection, the number `0` is returned instead.
+    (1, WsReceive, "&wsr", "websocket - receive"),
     /// Make an HTTP request
     ///
     /// Takes in an 1.x HTTP request and returns an HTTP response.
@@ -330,6 +745,17 @@ sys_op! {
     /// - The HTTP version
     /// - The `Host` header (if not defined)
     (2, HttpsWrite, "&httpsw", "http - Make an HTTP request"),
+    /// Send an HTTP or HTTPS request to a URL
+    ///
+    /// Expects a method, a URL, a list of headers, and a body.
+    /// Headers are boxed strings of the form `"Name: value"`. An empty list sends no extra headers.
+    /// The body may be a string or a byte array.
+    ///
+    /// Returns the status code, the response headers as boxed strings, and the body.
+    /// The body is a string if it is valid UTF-8, and a byte array otherwise.
+    ///
+    /// The `Host`, `Connection`, and `Content-Length` headers are filled in automatically.
+    (4(3), HttpRequest, "&http", "http - request"),
 }
 
 /// A handle to an IO stream
@@ -359,11 +785,18 @@ impl From<Handle> for Value {
     }
 }
 
-type AudioStreamFn = Box<dyn FnMut(Vec<f64>) -> UiuaResult<Vec<[f64; 2]>> + Send>;
+pub(crate) type AudioStreamFn = Box<dyn FnMut(Vec<f64>) -> UiuaResult<Vec<[f64; 2]>> + Send>;
 
 #[allow(unused_variables)]
 pub trait SysBackend: Any + Send + Sync + 'static {
     fn any(&self) -> &dyn Any;
+    /// Whether this backend supports a system function
+    ///
+    /// System functions that are not supported fail with an error before touching the backend.
+    /// Embedders can use this with [`SysOp::ALL`] to discover what a backend can do.
+    fn supports(&self, op: SysOp) -> bool {
+        true
+    }
     /// Save a color-formatted version of an error message for later printing
     fn save_error_color(&self, error: &UiuaError) {}
     fn print_str_stdout(&self, s: &str) -> Result<(), String> {
@@ -372,22 +805,72 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn print_str_stderr(&self, s: &str) -> Result<(), String> {
         Err("Printing to stderr is not supported in this environment".into())
     }
+    /// Print a string to stdout with a style
+    ///
+    /// Backends that cannot show styles should print the string plainly.
+    fn print_styled_stdout(&self, s: &str, style: &TextStyle) -> Result<(), String> {
+        self.print_str_stdout(s)
+    }
+    /// Whether stdout is a terminal
+    ///
+    /// When it is not, values are printed without box-drawing characters.
+    fn stdout_is_terminal(&self) -> bool {
+        false
+    }
+    /// Whether stderr is a terminal
+    ///
+    /// When it is not, errors and diagnostics are printed without color.
+    fn stderr_is_terminal(&self) -> bool {
+        false
+    }
     fn print_str_trace(&self, s: &str) {
         eprint!("{s}");
         _ = stderr().flush();
     }
+    /// Write anything buffered for stdout and stderr
+    fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+    /// Set whether stdout is only flushed at newlines
+    ///
+    /// Backends that do not buffer output can ignore this.
+    fn set_line_buffered(&self, line_buffered: bool) -> Result<(), String> {
+        Ok(())
+    }
     /// Read a line from stdin
     ///
     /// Should return `Ok(None)` if EOF is reached.
     fn scan_line_stdin(&self) -> Result<Option<String>, String> {
         Err("Reading from stdin is not supported in this environment".into())
     }
+    /// Read bytes from stdin
+    ///
+    /// If `count` is `None`, read until EOF.
+    fn read_stdin(&self, count: Option<usize>) -> Result<Vec<u8>, String> {
+        Err("Reading from stdin is not supported in this environment".into())
+    }
+    fn stdin_eof(&self) -> Result<bool, String> {
+        Err("Reading from stdin is not supported in this environment".into())
+    }
     fn var(&self, name: &str) -> Option<String> {
         None
     }
+    fn set_var(&self, name: &str, value: &str) -> Result<(), String> {
+        Err("Setting environment variables is not supported in this environment".into())
+    }
+    fn vars(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
     fn term_size(&self) -> Result<(usize, usize), String> {
         Err("Getting the terminal size is not supported in this environment".into())
     }
+    fn set_raw_mode(&self, raw: bool) -> Result<(), String> {
+        Err("Raw mode is not supported in this environment".into())
+    }
+    /// Get the next key press if there is one, without blocking
+    fn poll_key(&self) -> Result<Option<String>, String> {
+        Err("Reading key presses is not supported in this environment".into())
+    }
     fn file_exists(&self, path: &str) -> bool {
         false
     }
@@ -397,6 +880,44 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn is_file(&self, path: &str) -> Result<bool, String> {
         Err("This IO operation is not supported in this environment".into())
     }
+    fn file_size(&self, path: &str) -> Result<u64, String> {
+        Err("This IO operation is not supported in this environment".into())
+    }
+    /// Get the time a file was last modified in seconds since the Unix epoch
+    fn file_modified(&self, path: &str) -> Result<f64, String> {
+        Err("This IO operation is not supported in this environment".into())
+    }
+    /// Wait for a path to change and return the paths that changed
+    ///
+    /// A `timeout` of `None` waits forever. Should return an empty list if the timeout is reached.
+    fn watch_path(&self, path: &str, timeout: Option<f64>) -> Result<Vec<String>, String> {
+        Err("Watching files is not supported in this environment".into())
+    }
+    fn make_dir(&self, path: &str) -> Result<(), String> {
+        Err("This IO operation is not supported in this environment".into())
+    }
+    fn remove_dir(&self, path: &str) -> Result<(), String> {
+        Err("This IO operation is not supported in this environment".into())
+    }
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        Err("This IO operation is not supported in this environment".into())
+    }
+    /// Create a new empty temporary file and return its path
+    fn create_temp_file(&self) -> Result<String, String> {
+        Err("Temporary files are not supported in this environment".into())
+    }
+    /// Create a new empty temporary directory and return its path
+    fn create_temp_dir(&self) -> Result<String, String> {
+        Err("Temporary directories are not supported in this environment".into())
+    }
+    /// Get the current working directory
+    fn current_dir(&self) -> Result<String, String> {
+        Err("Getting the current directory is not supported in this environment".into())
+    }
+    /// Delete the temporary files and directories that have been created
+    fn clean_up_temp(&self) -> Result<(), String> {
+        Ok(())
+    }
     fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
         Err("This IO operation is not supported in this environment".into())
     }
@@ -417,6 +938,9 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
         Err("This IO operation is not supported in this environment".into())
     }
+    fn seek(&self, handle: Handle, pos: SeekFrom) -> Result<(), String> {
+        Err("This IO operation is not supported in this environment".into())
+    }
     fn create_file(&self, path: &str) -> Result<Handle, String> {
         Err("This IO operation is not supported in this environment".into())
     }
@@ -438,6 +962,20 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     fn sleep(&self, seconds: f64) -> Result<(), String> {
         Err("Sleeping is not supported in this environment".into())
     }
+    /// Get the current time in seconds since the Unix epoch
+    ///
+    /// Backends that fast-forward [`SysBackend::sleep`] should advance this time as well.
+    fn now(&self) -> f64 {
+        instant::now() / 1000.0
+    }
+    /// Get the time in seconds from a monotonic clock
+    ///
+    /// Backends that fast-forward [`SysBackend::sleep`] should advance this time as well.
+    fn monotonic_time(&self) -> f64 {
+        static START: Lazy<instant::Instant> = Lazy::new(instant::Instant::now);
+        START.elapsed().as_secs_f64()
+    }
+    #[cfg(feature = "image")]
     fn show_image(&self, image: DynamicImage) -> Result<(), String> {
         Err("Showing images not supported in this environment".into())
     }
@@ -482,6 +1020,31 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     ) -> Result<(), String> {
         Err("TCP sockets are not supported in this environment".into())
     }
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_send(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_receive(&self, handle: Handle, max: usize) -> Result<(Vec<u8>, String), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn udp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        Err("UDP sockets are not supported in this environment".into())
+    }
+    fn ws_connect(&self, url: &str) -> Result<Handle, String> {
+        Err("Websockets are not supported in this environment".into())
+    }
+    fn ws_send(&self, handle: Handle, message: WebSocketMessage) -> Result<(), String> {
+        Err("Websockets are not supported in this environment".into())
+    }
+    fn ws_receive(&self, handle: Handle) -> Result<Option<WebSocketMessage>, String> {
+        Err("Websockets are not supported in this environment".into())
+    }
     fn close(&self, handle: Handle) -> Result<(), String> {
         Ok(())
     }
@@ -507,12 +1070,117 @@ pub trait SysBackend: Any + Send + Sync + 'static {
     ) -> Result<(String, String), String> {
         Err("Running commands is not supported in this environment".into())
     }
+    fn run_command_piped(
+        &self,
+        command: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> Result<(String, String, i32), String> {
+        Err("Running commands is not supported in this environment".into())
+    }
+    fn run_command_stream(&self, command: &str, args: &[&str]) -> Result<Handle, String> {
+        Err("Running commands is not supported in this environment".into())
+    }
+    fn wait_command(&self, handle: Handle) -> Result<i32, String> {
+        Err("Running commands is not supported in this environment".into())
+    }
     fn change_directory(&self, path: &str) -> Result<(), String> {
         Err("Changing directories is not supported in this environment".into())
     }
+    fn clipboard(&self) -> Result<String, String> {
+        Err("The clipboard is not supported in this environment".into())
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        Err("The clipboard is not supported in this environment".into())
+    }
     fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
         Err("Making HTTPS requests is not supported in this environment".into())
     }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[String],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        Err("Making HTTP requests is not supported in this environment".into())
+    }
+}
+
+/// A style for printed text
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextStyle {
+    /// The foreground color name, like `red` or `bright_red`
+    pub color: Option<String>,
+    /// The background color name
+    pub background: Option<String>,
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+}
+
+impl TextStyle {
+    const COLORS: [&'static str; 8] = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+    fn is_color(name: &str) -> bool {
+        Self::COLORS.contains(&name.strip_prefix("bright_").unwrap_or(name))
+    }
+    /// Parse a style from space-separated words
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let mut style = TextStyle::default();
+        for word in s.split_whitespace() {
+            match word {
+                "bold" => style.bold = true,
+                "dim" => style.dim = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                word if Self::is_color(word) => style.color = Some(word.into()),
+                word if word.strip_prefix("on_").is_some_and(Self::is_color) => {
+                    style.background = Some(word["on_".len()..].into())
+                }
+                word => return Err(format!("Unknown text style {word:?}")),
+            }
+        }
+        Ok(style)
+    }
+    /// Apply the style to a string with ANSI escape codes
+    pub fn apply(&self, s: &str) -> String {
+        use colored::{Color, Colorize};
+        let color = |name: &str| -> Color { name.replace('_', " ").parse().unwrap() };
+        let mut styled = s.normal();
+        if let Some(name) = &self.color {
+            styled = styled.color(color(name));
+        }
+        if let Some(name) = &self.background {
+            styled = styled.on_color(color(name));
+        }
+        if self.bold {
+            styled = styled.bold();
+        }
+        if self.dim {
+            styled = styled.dimmed();
+        }
+        if self.italic {
+            styled = styled.italic();
+        }
+        if self.underline {
+            styled = styled.underline();
+        }
+        styled.to_string()
+    }
+}
+
+/// A response to an HTTP request
+#[derive(Debug, Clone, Default)]
+pub struct HttpResponse {
+    /// The status code
+    pub status: u16,
+    /// The headers, each of the form `Name: value`
+    pub headers: Vec<String>,
+    /// The body
+    pub body: Vec<u8>,
 }
 
 #[derive(Default)]
@@ -525,6 +1193,10 @@ struct GlobalNativeSys {
     files: DashMap<Handle, Buffered<File>>,
     tcp_listeners: DashMap<Handle, TcpListener>,
     tcp_sockets: DashMap<Handle, Buffered<TcpStream>>,
+    udp_sockets: DashMap<Handle, UdpSocket>,
+    processes: DashMap<Handle, ChildProcess>,
+    #[cfg(feature = "https")]
+    websockets: DashMap<Handle, WebSocket>,
     hostnames: DashMap<Handle, String>,
     threads: DashMap<Handle, JoinHandle<UiuaResult<Vec<Value>>>>,
     #[cfg(feature = "audio")]
@@ -532,12 +1204,31 @@ struct GlobalNativeSys {
     #[cfg(feature = "audio")]
     audio_time_socket: Mutex<Option<Arc<std::net::UdpSocket>>>,
     colored_errors: DashMap<String, String>,
+    stdout_line_buffered: AtomicBool,
+    temp_paths: Mutex<Vec<PathBuf>>,
+    #[cfg(feature = "file_watch")]
+    watchers: DashMap<PathBuf, Arc<FileWatcher>>,
+}
+
+/// A path being watched with [`SysOp::FWatch`]
+#[cfg(feature = "file_watch")]
+struct FileWatcher {
+    _watcher: notify::RecommendedWatcher,
+    events: Mutex<std::sync::mpsc::Receiver<notify::Result<notify::Event>>>,
 }
 
 enum SysStream<'a> {
     File(dashmap::mapref::one::RefMut<'a, Handle, Buffered<File>>),
     TcpListener(dashmap::mapref::one::RefMut<'a, Handle, TcpListener>),
     TcpSocket(dashmap::mapref::one::RefMut<'a, Handle, Buffered<TcpStream>>),
+    Process(dashmap::mapref::one::RefMut<'a, Handle, ChildProcess>),
+}
+
+/// A process started with [`SysOp::RunStream`]
+struct ChildProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: ChildStdout,
 }
 
 impl Default for GlobalNativeSys {
@@ -547,6 +1238,10 @@ impl Default for GlobalNativeSys {
             files: DashMap::new(),
             tcp_listeners: DashMap::new(),
             tcp_sockets: DashMap::new(),
+            udp_sockets: DashMap::new(),
+            processes: DashMap::new(),
+            #[cfg(feature = "https")]
+            websockets: DashMap::new(),
             hostnames: DashMap::new(),
             threads: DashMap::new(),
             #[cfg(feature = "audio")]
@@ -554,6 +1249,10 @@ impl Default for GlobalNativeSys {
             #[cfg(feature = "audio")]
             audio_time_socket: Mutex::new(None),
             colored_errors: DashMap::new(),
+            stdout_line_buffered: AtomicBool::new(false),
+            temp_paths: Mutex::new(Vec::new()),
+            #[cfg(feature = "file_watch")]
+            watchers: DashMap::new(),
         }
     }
 }
@@ -565,12 +1264,37 @@ impl GlobalNativeSys {
             if !self.files.contains_key(&handle)
                 && !self.tcp_listeners.contains_key(&handle)
                 && !self.tcp_sockets.contains_key(&handle)
+                && !self.udp_sockets.contains_key(&handle)
+                && !self.processes.contains_key(&handle)
+                && !self.has_websocket(handle)
             {
                 return handle;
             }
         }
         panic!("Ran out of file handles");
     }
+    fn has_websocket(&self, handle: Handle) -> bool {
+        #[cfg(feature = "https")]
+        {
+            self.websockets.contains_key(&handle)
+        }
+        #[cfg(not(feature = "https"))]
+        {
+            _ = handle;
+            false
+        }
+    }
+    fn remove_websocket(&self, handle: Handle) -> bool {
+        #[cfg(feature = "https")]
+        {
+            self.websockets.remove(&handle).is_some()
+        }
+        #[cfg(not(feature = "https"))]
+        {
+            _ = handle;
+            false
+        }
+    }
     fn get_stream(&self, handle: Handle) -> Result<SysStream, String> {
         Ok(if let Some(file) = self.files.get_mut(&handle) {
             SysStream::File(file)
@@ -578,6 +1302,8 @@ impl GlobalNativeSys {
             SysStream::TcpListener(listener)
         } else if let Some(socket) = self.tcp_sockets.get_mut(&handle) {
             SysStream::TcpSocket(socket)
+        } else if let Some(process) = self.processes.get_mut(&handle) {
+            SysStream::Process(process)
         } else {
             return Err("Invalid file handle".to_string());
         })
@@ -606,13 +1332,46 @@ impl SysBackend for NativeSys {
     fn print_str_stdout(&self, s: &str) -> Result<(), String> {
         let mut stdout = stdout().lock();
         stdout.write_all(s.as_bytes()).map_err(|e| e.to_string())?;
+        // Stdout is always flushed at newlines
+        if NATIVE_SYS
+            .stdout_line_buffered
+            .load(atomic::Ordering::Relaxed)
+        {
+            return Ok(());
+        }
         stdout.flush().map_err(|e| e.to_string())
     }
+    fn print_styled_stdout(&self, s: &str, style: &TextStyle) -> Result<(), String> {
+        if self.stdout_is_terminal() {
+            self.print_str_stdout(&style.apply(s))
+        } else {
+            self.print_str_stdout(s)
+        }
+    }
+    fn stdout_is_terminal(&self) -> bool {
+        use std::io::IsTerminal;
+        stdout().is_terminal()
+    }
+    fn stderr_is_terminal(&self) -> bool {
+        use std::io::IsTerminal;
+        stderr().is_terminal()
+    }
     fn print_str_stderr(&self, s: &str) -> Result<(), String> {
         let mut stderr = stderr().lock();
         stderr.write_all(s.as_bytes()).map_err(|e| e.to_string())?;
         stderr.flush().map_err(|e| e.to_string())
     }
+    fn flush(&self) -> Result<(), String> {
+        stdout().flush().map_err(|e| e.to_string())?;
+        stderr().flush().map_err(|e| e.to_string())
+    }
+    fn set_line_buffered(&self, line_buffered: bool) -> Result<(), String> {
+        (NATIVE_SYS.stdout_line_buffered).store(line_buffered, atomic::Ordering::Relaxed);
+        if !line_buffered {
+            self.flush()?;
+        }
+        Ok(())
+    }
     fn scan_line_stdin(&self) -> Result<Option<String>, String> {
         stdin()
             .lock()
@@ -621,11 +1380,93 @@ impl SysBackend for NativeSys {
             .transpose()
             .map_err(|e| e.to_string())
     }
+    fn read_stdin(&self, count: Option<usize>) -> Result<Vec<u8>, String> {
+        let mut buf = Vec::new();
+        let mut stdin = stdin().lock();
+        match count {
+            Some(count) => Read::by_ref(&mut stdin)
+                .take(count as u64)
+                .read_to_end(&mut buf),
+            None => stdin.read_to_end(&mut buf),
+        }
+        .map_err(|e| e.to_string())?;
+        Ok(buf)
+    }
+    fn stdin_eof(&self) -> Result<bool, String> {
+        let mut stdin = stdin().lock();
+        let buf = stdin.fill_buf().map_err(|e| e.to_string())?;
+        Ok(buf.is_empty())
+    }
     fn save_error_color(&self, error: &UiuaError) {
         NATIVE_SYS
             .colored_errors
             .insert(error.message(), error.show(true));
     }
+    #[cfg(feature = "raw_mode")]
+    fn set_raw_mode(&self, raw: bool) -> Result<(), String> {
+        use crossterm::terminal;
+        use std::io::IsTerminal;
+        if raw {
+            if !stdin().is_terminal() {
+                return Err("Raw mode requires stdin to be a terminal".into());
+            }
+            terminal::enable_raw_mode()
+        } else {
+            terminal::disable_raw_mode()
+        }
+        .map_err(|e| e.to_string())
+    }
+    #[cfg(feature = "raw_mode")]
+    fn poll_key(&self) -> Result<Option<String>, String> {
+        use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+        use std::io::IsTerminal;
+        if !stdin().is_terminal() {
+            return Err("Reading key presses requires stdin to be a terminal".into());
+        }
+        while event::poll(Duration::ZERO).map_err(|e| e.to_string())? {
+            let Event::Key(key) = event::read().map_err(|e| e.to_string())? else {
+                continue;
+            };
+            if key.kind == KeyEventKind::Release {
+                continue;
+            }
+            let name = match key.code {
+                KeyCode::Char(c) => c.to_string(),
+                KeyCode::Enter => "enter".into(),
+                KeyCode::Tab => "tab".into(),
+                KeyCode::BackTab => "shift+tab".into(),
+                KeyCode::Backspace => "backspace".into(),
+                KeyCode::Esc => "esc".into(),
+                KeyCode::Up => "up".into(),
+                KeyCode::Down => "down".into(),
+                KeyCode::Left => "left".into(),
+                KeyCode::Right => "right".into(),
+                KeyCode::Home => "home".into(),
+                KeyCode::End => "end".into(),
+                KeyCode::PageUp => "pageup".into(),
+                KeyCode::PageDown => "pagedown".into(),
+                KeyCode::Insert => "insert".into(),
+                KeyCode::Delete => "delete".into(),
+                KeyCode::F(n) => format!("f{n}"),
+                _ => continue,
+            };
+            let mut prefix = String::new();
+            if key.modifiers.contains(KeyModifiers::CONTROL) {
+                prefix.push_str("ctrl+");
+            }
+            if key.modifiers.contains(KeyModifiers::ALT) {
+                prefix.push_str("alt+");
+            }
+            // Shifted characters are already reflected in the character itself
+            if key.modifiers.contains(KeyModifiers::SHIFT)
+                && !matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab)
+            {
+                prefix.push_str("shift+");
+            }
+            return Ok(Some(prefix + &name));
+        }
+        Ok(None)
+    }
     fn term_size(&self) -> Result<(usize, usize), String> {
         let (w, h) = term_size::dimensions().ok_or("Failed to get terminal size")?;
         Ok((w, h.saturating_sub(1)))
@@ -633,6 +1474,18 @@ impl SysBackend for NativeSys {
     fn var(&self, name: &str) -> Option<String> {
         env::var(name).ok()
     }
+    fn set_var(&self, name: &str, value: &str) -> Result<(), String> {
+        if name.is_empty() || name.contains(['=', '\0']) || value.contains('\0') {
+            return Err(format!("Invalid environment variable {name:?}"));
+        }
+        env::set_var(name, value);
+        Ok(())
+    }
+    fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<_> = env::vars().collect();
+        vars.sort();
+        vars
+    }
     fn file_exists(&self, path: &str) -> bool {
         fs::metadata(path).is_ok()
     }
@@ -649,22 +1502,146 @@ impl SysBackend for NativeSys {
         }
         Ok(paths)
     }
-    fn open_file(&self, path: &str) -> Result<Handle, String> {
-        let handle = NATIVE_SYS.new_handle();
-        let file = File::open(path).map_err(|e| e.to_string())?;
-        NATIVE_SYS.files.insert(handle, Buffered::new_reader(file));
-        Ok(handle)
+    fn file_size(&self, path: &str) -> Result<u64, String> {
+        fs::metadata(path)
+            .map(|m| m.len())
+            .map_err(|e| e.to_string())
     }
-    fn create_file(&self, path: &str) -> Result<Handle, String> {
-        let handle = NATIVE_SYS.new_handle();
-        let file = File::create(path).map_err(|e| e.to_string())?;
-        NATIVE_SYS.files.insert(handle, Buffered::new_writer(file));
-        Ok(handle)
+    fn file_modified(&self, path: &str) -> Result<f64, String> {
+        let modified = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .map_err(|e| e.to_string())?;
+        Ok(match modified.duration_since(UNIX_EPOCH) {
+            Ok(since) => since.as_secs_f64(),
+            Err(e) => -e.duration().as_secs_f64(),
+        })
     }
-    fn read(&self, handle: Handle, len: usize) -> Result<Vec<u8>, String> {
-        Ok(match NATIVE_SYS.get_stream(handle)? {
-            SysStream::File(mut file) => {
-                let mut buf = Vec::new();
+    #[cfg(feature = "file_watch")]
+    fn watch_path(&self, path: &str, timeout: Option<f64>) -> Result<Vec<String>, String> {
+        use notify::{EventKind, RecursiveMode, Watcher};
+        use std::{collections::BTreeSet, sync::mpsc::RecvTimeoutError, time::Instant};
+        let canonical = fs::canonicalize(path).map_err(|e| format!("Cannot watch {path}: {e}"))?;
+        let watcher = NATIVE_SYS
+            .watchers
+            .entry(canonical.clone())
+            .or_try_insert_with(|| {
+                let (send, recv) = std::sync::mpsc::channel();
+                let mut watcher = notify::recommended_watcher(send).map_err(|e| e.to_string())?;
+                watcher
+                    .watch(&canonical, RecursiveMode::Recursive)
+                    .map_err(|e| format!("Cannot watch {path}: {e}"))?;
+                Ok::<_, String>(Arc::new(FileWatcher {
+                    _watcher: watcher,
+                    events: Mutex::new(recv),
+                }))
+            })?
+            .clone();
+        let events = watcher.events.lock();
+        let deadline = timeout.and_then(|timeout| {
+            Instant::now().checked_add(Duration::try_from_secs_f64(timeout).ok()?)
+        });
+        let mut changed = BTreeSet::new();
+        let changes = |event: notify::Result<notify::Event>| -> Result<Vec<_>, String> {
+            let event = event.map_err(|e| format!("Failed to watch {path}: {e}"))?;
+            Ok(match event.kind {
+                EventKind::Access(_) => Vec::new(),
+                _ => event.paths,
+            })
+        };
+        // Wait for the first change
+        while changed.is_empty() {
+            let event = if let Some(deadline) = deadline {
+                match events.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(format!("Stopped watching {path}"))
+                    }
+                }
+            } else {
+                events
+                    .recv()
+                    .map_err(|_| format!("Stopped watching {path}"))?
+            };
+            changed.extend(changes(event)?);
+        }
+        // Changes often come in bursts, so collect the rest of the burst
+        if !changed.is_empty() {
+            while let Ok(event) = events.recv_timeout(Duration::from_millis(50)) {
+                changed.extend(changes(event)?);
+            }
+        }
+        Ok(changed
+            .into_iter()
+            .map(|changed| {
+                let relative = changed.strip_prefix(&canonical).unwrap_or(&changed);
+                if relative.as_os_str().is_empty() {
+                    path.into()
+                } else {
+                    Path::new(path).join(relative).to_string_lossy().into()
+                }
+            })
+            .collect())
+    }
+    fn make_dir(&self, path: &str) -> Result<(), String> {
+        fs::create_dir_all(path).map_err(|e| e.to_string())
+    }
+    fn remove_dir(&self, path: &str) -> Result<(), String> {
+        fs::remove_dir(path).map_err(|e| e.to_string())
+    }
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        fs::remove_file(path).map_err(|e| e.to_string())
+    }
+    fn current_dir(&self) -> Result<String, String> {
+        env::current_dir()
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .map_err(|e| format!("Failed to get the current directory: {e}"))
+    }
+    fn create_temp_file(&self) -> Result<String, String> {
+        create_temp_path(|path| {
+            File::options()
+                .write(true)
+                .create_new(true)
+                .open(path)
+                .map(drop)
+        })
+    }
+    fn create_temp_dir(&self) -> Result<String, String> {
+        create_temp_path(|path| fs::create_dir(path))
+    }
+    fn clean_up_temp(&self) -> Result<(), String> {
+        let mut error = None;
+        for path in NATIVE_SYS.temp_paths.lock().drain(..) {
+            let res = if path.is_dir() {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_file(&path)
+            };
+            match res {
+                Err(e) if e.kind() != ErrorKind::NotFound => {
+                    error.get_or_insert(format!("Failed to delete {}: {e}", path.display()));
+                }
+                _ => {}
+            }
+        }
+        error.map_or(Ok(()), Err)
+    }
+    fn open_file(&self, path: &str) -> Result<Handle, String> {
+        let handle = NATIVE_SYS.new_handle();
+        let file = File::open(path).map_err(|e| e.to_string())?;
+        NATIVE_SYS.files.insert(handle, Buffered::new_reader(file));
+        Ok(handle)
+    }
+    fn create_file(&self, path: &str) -> Result<Handle, String> {
+        let handle = NATIVE_SYS.new_handle();
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        NATIVE_SYS.files.insert(handle, Buffered::new_writer(file));
+        Ok(handle)
+    }
+    fn read(&self, handle: Handle, len: usize) -> Result<Vec<u8>, String> {
+        Ok(match NATIVE_SYS.get_stream(handle)? {
+            SysStream::File(mut file) => {
+                let mut buf = Vec::new();
                 Write::by_ref(&mut *file)
                     .take(len as u64)
                     .read_to_end(&mut buf)
@@ -674,7 +1651,22 @@ impl SysBackend for NativeSys {
             SysStream::TcpListener(_) => return Err("Cannot read from a tcp listener".to_string()),
             SysStream::TcpSocket(mut socket) => {
                 let mut buf = Vec::new();
-                Write::by_ref(&mut *socket)
+                match Write::by_ref(&mut *socket)
+                    .take(len as u64)
+                    .read_to_end(&mut buf)
+                {
+                    Ok(_) => {}
+                    // Return what was read before a read timeout
+                    Err(e)
+                        if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+                            && !buf.is_empty() => {}
+                    Err(e) => return Err(e.to_string()),
+                }
+                buf
+            }
+            SysStream::Process(mut process) => {
+                let mut buf = Vec::new();
+                Read::by_ref(&mut process.stdout)
                     .take(len as u64)
                     .read_to_end(&mut buf)
                     .map_err(|e| e.to_string())?;
@@ -697,41 +1689,49 @@ impl SysBackend for NativeSys {
         match NATIVE_SYS.get_stream(handle)? {
             SysStream::File(mut file) => file.write_all(conts).map_err(|e| e.to_string()),
             SysStream::TcpListener(_) => Err("Cannot write to a tcp listener".to_string()),
-            SysStream::TcpSocket(mut socket) => socket.write_all(conts).map_err(|e| e.to_string()),
+            SysStream::TcpSocket(mut socket) => socket
+                .write_all(conts)
+                .and_then(|_| socket.flush())
+                .map_err(|e| e.to_string()),
+            SysStream::Process(mut process) => match &mut process.stdin {
+                Some(stdin) => (stdin.write_all(conts))
+                    .and_then(|_| stdin.flush())
+                    .map_err(|e| e.to_string()),
+                None => Err("Process stdin is closed".to_string()),
+            },
+        }
+    }
+    fn seek(&self, handle: Handle, pos: SeekFrom) -> Result<(), String> {
+        match NATIVE_SYS.get_stream(handle)? {
+            SysStream::File(mut file) => {
+                let res = if let Some(reader) = file.get_bufreader_mut() {
+                    reader.seek(pos)
+                } else {
+                    file.get_bufwriter_mut().unwrap().seek(pos)
+                };
+                res.map(drop).map_err(|e| e.to_string())
+            }
+            _ => Err("Only files can be seeked".to_string()),
         }
     }
     fn sleep(&self, seconds: f64) -> Result<(), String> {
-        sleep(Duration::from_secs_f64(seconds));
+        sleep(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX));
         Ok(())
     }
-    #[cfg(feature = "terminal_image")]
+    #[cfg(feature = "image")]
     fn show_image(&self, image: DynamicImage) -> Result<(), String> {
-        let (width, height) = if let Some((w, h)) = term_size::dimensions() {
-            let (tw, th) = (w as u32, h.saturating_sub(1) as u32);
-            let (iw, ih) = (image.width(), image.height() / 2);
-            let scaled_to_height = (iw * th / ih.max(1), th);
-            let scaled_to_width = (tw, ih * tw / iw.max(1));
-            let (w, h) = if scaled_to_height.0 <= tw {
-                scaled_to_height
-            } else {
-                scaled_to_width
-            };
-            (Some(w), Some(h))
-        } else {
-            (None, None)
-        };
-        viuer::print(
-            &image,
-            &viuer::Config {
-                width,
-                height,
-                absolute_offset: false,
-                transparent: true,
-                ..Default::default()
-            },
-        )
-        .map(drop)
-        .map_err(|e| format!("Failed to show image: {e}"))
+        #[cfg(feature = "terminal_image")]
+        {
+            use std::io::IsTerminal;
+            if stdout().is_terminal() {
+                return show_image_in_terminal(image);
+            }
+        }
+        let bytes = image_to_bytes(&image, ImageOutputFormat::Png)?;
+        open_in_viewer(&bytes, "png")
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        open_in_viewer(&gif_bytes, "gif")
     }
     #[cfg(feature = "audio")]
     fn play_audio(&self, wav_bytes: Vec<u8>) -> Result<(), String> {
@@ -830,21 +1830,22 @@ impl SysBackend for NativeSys {
         Ok(handle)
     }
     fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
-        let handle = NATIVE_SYS.new_handle();
+        let hostname = addr.rsplit_once(':').ok_or("No colon in address")?.0;
         let stream = TcpStream::connect(addr).map_err(|e| e.to_string())?;
+        let handle = NATIVE_SYS.new_handle();
         NATIVE_SYS
             .tcp_sockets
             .insert(handle, Buffered::new_writer(stream));
-        NATIVE_SYS.hostnames.insert(
-            handle,
-            addr.split_once(':')
-                .ok_or("No colon in address")?
-                .0
-                .to_string(),
-        );
+        NATIVE_SYS.hostnames.insert(handle, hostname.to_string());
         Ok(handle)
     }
     fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        if let Some(listener) = NATIVE_SYS.tcp_listeners.get(&handle) {
+            return Ok(listener
+                .local_addr()
+                .map_err(|e| e.to_string())?
+                .to_string());
+        }
         let socket = NATIVE_SYS
             .tcp_sockets
             .get(&handle)
@@ -896,12 +1897,59 @@ impl SysBackend for NativeSys {
             .map_err(|e| e.to_string())?;
         Ok(())
     }
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        let socket = UdpSocket::bind(addr).map_err(|e| e.to_string())?;
+        let handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.udp_sockets.insert(handle, socket);
+        Ok(handle)
+    }
+    fn udp_send(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        let sent = socket.send_to(data, addr).map_err(|e| e.to_string())?;
+        if sent < data.len() {
+            return Err(format!(
+                "Only {sent} of {} bytes were sent in the datagram",
+                data.len()
+            ));
+        }
+        Ok(())
+    }
+    fn udp_receive(&self, handle: Handle, max: usize) -> Result<(Vec<u8>, String), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        let mut buf = vec![0; max];
+        let (len, addr) = socket.recv_from(&mut buf).map_err(|e| e.to_string())?;
+        buf.truncate(len);
+        Ok((buf, addr.to_string()))
+    }
+    fn udp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        let socket = NATIVE_SYS
+            .udp_sockets
+            .get(&handle)
+            .ok_or_else(|| "Invalid udp socket handle".to_string())?;
+        socket
+            .set_read_timeout(timeout)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
     fn close(&self, handle: Handle) -> Result<(), String> {
         if NATIVE_SYS.files.remove(&handle).is_some()
             || NATIVE_SYS.tcp_listeners.remove(&handle).is_some()
-            || (NATIVE_SYS.tcp_sockets.remove(&handle).is_some()
-                && NATIVE_SYS.hostnames.remove(&handle).is_some())
+            || NATIVE_SYS.tcp_sockets.remove(&handle).is_some()
+            || NATIVE_SYS.udp_sockets.remove(&handle).is_some()
+            || NATIVE_SYS.processes.remove(&handle).is_some()
+            || NATIVE_SYS.remove_websocket(handle)
         {
+            NATIVE_SYS.hostnames.remove(&handle);
             Ok(())
         } else {
             Err("Invalid stream handle".to_string())
@@ -912,10 +1960,15 @@ impl SysBackend for NativeSys {
         mut env: Uiua,
         f: Box<dyn FnOnce(&mut Uiua) -> UiuaResult + Send>,
     ) -> Result<Handle, String> {
-        let thread = spawn(move || {
-            f(&mut env)?;
-            Ok(env.take_stack())
-        });
+        // Functions called by modifiers run on the native stack, so spawned threads get
+        // as much of it as the main thread usually has
+        let thread = thread::Builder::new()
+            .stack_size(8 << 20)
+            .spawn(move || {
+                f(&mut env)?;
+                Ok(env.take_stack())
+            })
+            .map_err(|e| e.to_string())?;
         let handle = NATIVE_SYS.new_handle();
         NATIVE_SYS.threads.insert(handle, thread);
         Ok(handle)
@@ -954,9 +2007,129 @@ impl SysBackend for NativeSys {
             String::from_utf8_lossy(&output.stderr).into(),
         ))
     }
+    fn run_command_piped(
+        &self,
+        command: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> Result<(String, String, i32), String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        // Write on another thread so a process that fills its stdout before
+        // reading all its input does not deadlock
+        let mut stdin = child.stdin.take().unwrap();
+        let input = input.to_vec();
+        let writer = spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output().map_err(|e| e.to_string())?;
+        match writer.join() {
+            Ok(Ok(())) => {}
+            // The process may exit without reading all its input
+            Ok(Err(e)) if e.kind() == ErrorKind::BrokenPipe => {}
+            Ok(Err(e)) => return Err(e.to_string()),
+            Err(_) => return Err("Failed to write to process stdin".into()),
+        }
+        Ok((
+            String::from_utf8_lossy(&output.stdout).into(),
+            String::from_utf8_lossy(&output.stderr).into(),
+            output.status.code().unwrap_or(-1),
+        ))
+    }
+    fn run_command_stream(&self, command: &str, args: &[&str]) -> Result<Handle, String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        let process = ChildProcess {
+            stdin: child.stdin.take(),
+            stdout: child.stdout.take().unwrap(),
+            child,
+        };
+        let handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.processes.insert(handle, process);
+        Ok(handle)
+    }
+    fn wait_command(&self, handle: Handle) -> Result<i32, String> {
+        let (_, mut process) = NATIVE_SYS
+            .processes
+            .remove(&handle)
+            .ok_or_else(|| "Invalid process handle".to_string())?;
+        drop(process.stdin.take());
+        let status = process.child.wait().map_err(|e| e.to_string())?;
+        Ok(status.code().unwrap_or(-1))
+    }
     fn change_directory(&self, path: &str) -> Result<(), String> {
         env::set_current_dir(path).map_err(|e| e.to_string())
     }
+    #[cfg(feature = "clipboard")]
+    fn clipboard(&self) -> Result<String, String> {
+        let commands: &[&[&str]] = if cfg!(target_os = "macos") {
+            &[&["pbpaste"]]
+        } else if cfg!(windows) {
+            &[&["powershell", "-NoProfile", "-Command", "Get-Clipboard -Raw"]]
+        } else {
+            &[
+                &["wl-paste", "--no-newline"],
+                &["xclip", "-selection", "clipboard", "-out"],
+                &["xsel", "--clipboard", "--output"],
+            ]
+        };
+        for command in commands {
+            match Command::new(command[0]).args(&command[1..]).output() {
+                Ok(output) if output.status.success() => {
+                    return Ok(String::from_utf8_lossy(&output.stdout).into())
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Ok(output) => return Err(String::from_utf8_lossy(&output.stderr).trim().into()),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Err(no_clipboard_program())
+    }
+    #[cfg(feature = "clipboard")]
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        let commands: &[&[&str]] = if cfg!(target_os = "macos") {
+            &[&["pbcopy"]]
+        } else if cfg!(windows) {
+            &[&["clip"]]
+        } else {
+            &[
+                &["wl-copy"],
+                &["xclip", "-selection", "clipboard", "-in"],
+                &["xsel", "--clipboard", "--input"],
+            ]
+        };
+        for command in commands {
+            let mut child = match Command::new(command[0])
+                .args(&command[1..])
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn()
+            {
+                Ok(child) => child,
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.to_string()),
+            };
+            let mut stdin = child.stdin.take().unwrap();
+            stdin
+                .write_all(contents.as_bytes())
+                .map_err(|e| e.to_string())?;
+            drop(stdin);
+            let status = child.wait().map_err(|e| e.to_string())?;
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(format!("{} failed with {status}", command[0]))
+            };
+        }
+        Err(no_clipboard_program())
+    }
     #[cfg(feature = "https")]
     fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
         let host = NATIVE_SYS
@@ -965,23 +2138,6 @@ impl SysBackend for NativeSys {
             .ok_or_else(|| "Invalid tcp socket handle".to_string())?;
         let request = check_http(request.to_string(), &host)?;
 
-        // https://github.com/rustls/rustls/blob/c9cfe3499681361372351a57a00ccd793837ae9c/examples/src/bin/simpleclient.rs
-        static CLIENT_CONFIG: Lazy<Arc<rustls::ClientConfig>> = Lazy::new(|| {
-            let mut store = rustls::RootCertStore::empty();
-            store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
-                rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
-                    ta.subject,
-                    ta.spki,
-                    ta.name_constraints,
-                )
-            }));
-            rustls::ClientConfig::builder()
-                .with_safe_defaults()
-                .with_root_certificates(store)
-                .with_no_client_auth()
-                .into()
-        });
-
         let mut socket = NATIVE_SYS
             .tcp_sockets
             .get_mut(&handle)
@@ -990,7 +2146,7 @@ impl SysBackend for NativeSys {
         let server_name = rustls::ServerName::try_from(host.as_str()).map_err(|e| e.to_string())?;
         let tcp_stream = socket.get_mut();
 
-        let mut conn = rustls::ClientConnection::new(CLIENT_CONFIG.clone(), server_name)
+        let mut conn = rustls::ClientConnection::new(TLS_CLIENT_CONFIG.clone(), server_name)
             .map_err(|e| e.to_string())?;
         let mut tls = rustls::Stream::new(&mut conn, tcp_stream);
 
@@ -1004,6 +2160,522 @@ impl SysBackend for NativeSys {
 
         Ok(s)
     }
+    #[cfg(feature = "https")]
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[String],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        let (tls, host, port, path) = parse_url(url, "http")?;
+        let mut request = format!(
+            "{method} {path} HTTP/1.1\r\n\
+            Host: {host}\r\n\
+            Connection: close\r\n\
+            Content-Length: {}\r\n",
+            body.len()
+        );
+        for header in headers {
+            if !header.contains(':') {
+                return Err(format!("Invalid HTTP header {header:?}"));
+            }
+            request.push_str(header);
+            request.push_str("\r\n");
+        }
+        request.push_str("\r\n");
+        let mut request = request.into_bytes();
+        request.extend_from_slice(body);
+
+        let mut stream = NetStream::connect(tls, &host, port)?;
+        let mut buffer = Vec::new();
+        let res = (stream.write_all(&request)).and_then(|_| stream.read_to_end(&mut buffer));
+        match res {
+            Ok(_) => {}
+            // Some servers close the connection without a TLS close_notify
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof && !buffer.is_empty() => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        parse_http_response(&buffer)
+    }
+    #[cfg(feature = "https")]
+    fn ws_connect(&self, url: &str) -> Result<Handle, String> {
+        let socket = WebSocket::connect(url)?;
+        let handle = NATIVE_SYS.new_handle();
+        NATIVE_SYS.websockets.insert(handle, socket);
+        Ok(handle)
+    }
+    #[cfg(feature = "https")]
+    fn ws_send(&self, handle: Handle, message: WebSocketMessage) -> Result<(), String> {
+        let mut socket = NATIVE_SYS
+            .websockets
+            .get_mut(&handle)
+            .ok_or_else(|| "Invalid websocket handle".to_string())?;
+        let (opcode, payload) = match &message {
+            WebSocketMessage::Text(text) => (WebSocket::TEXT, text.as_bytes()),
+            WebSocketMessage::Binary(bytes) => (WebSocket::BINARY, bytes.as_slice()),
+        };
+        socket.send_frame(opcode, payload)
+    }
+    #[cfg(feature = "https")]
+    fn ws_receive(&self, handle: Handle) -> Result<Option<WebSocketMessage>, String> {
+        let mut socket = NATIVE_SYS
+            .websockets
+            .get_mut(&handle)
+            .ok_or_else(|| "Invalid websocket handle".to_string())?;
+        socket.receive()
+    }
+}
+
+#[cfg(feature = "terminal_image")]
+fn show_image_in_terminal(image: DynamicImage) -> Result<(), String> {
+    let (width, height) = if let Some((w, h)) = term_size::dimensions() {
+        let (tw, th) = (w as u32, h.saturating_sub(1) as u32);
+        let (iw, ih) = (image.width(), image.height() / 2);
+        let scaled_to_height = (iw * th / ih.max(1), th);
+        let scaled_to_width = (tw, ih * tw / iw.max(1));
+        let (w, h) = if scaled_to_height.0 <= tw {
+            scaled_to_height
+        } else {
+            scaled_to_width
+        };
+        (Some(w), Some(h))
+    } else {
+        (None, None)
+    };
+    viuer::print(
+        &image,
+        &viuer::Config {
+            width,
+            height,
+            absolute_offset: false,
+            transparent: true,
+            ..Default::default()
+        },
+    )
+    .map(drop)
+    .map_err(|e| format!("Failed to show image: {e}"))
+}
+
+/// Remove `.` and `..` components from a path without accessing the file system
+pub(crate) fn normalize_path(path: &Path) -> String {
+    let mut normal = PathBuf::new();
+    let mut depth = 0;
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if depth > 0 => {
+                normal.pop();
+                depth -= 1;
+            }
+            Component::ParentDir if normal.has_root() => {}
+            Component::ParentDir => normal.push(".."),
+            Component::Normal(name) => {
+                normal.push(name);
+                depth += 1;
+            }
+            Component::RootDir | Component::Prefix(_) => normal.push(component),
+        }
+    }
+    if normal.as_os_str().is_empty() {
+        ".".into()
+    } else {
+        normal.to_string_lossy().into_owned()
+    }
+}
+
+/// Create a uniquely named path in the temporary directory and remember it for cleanup
+fn create_temp_path(create: impl Fn(&Path) -> std::io::Result<()>) -> Result<String, String> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let dir = env::temp_dir();
+    loop {
+        let n = COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        let path = dir.join(format!("uiua-{}-{nanos:x}-{n}", std::process::id()));
+        match create(&path) {
+            Ok(()) => {
+                let path_str = path.to_string_lossy().into_owned();
+                NATIVE_SYS.temp_paths.lock().push(path);
+                return Ok(path_str);
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create temporary path: {e}")),
+        }
+    }
+}
+
+/// Write bytes to a temporary file and open it with the platform's default program
+fn open_in_viewer(bytes: &[u8], extension: &str) -> Result<(), String> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let n = COUNTER.fetch_add(1, atomic::Ordering::Relaxed);
+    let path = env::temp_dir().join(format!("uiua-{}-{n}.{extension}", std::process::id()));
+    fs::write(&path, bytes).map_err(|e| format!("Failed to write temporary file: {e}"))?;
+    // The file is deleted by `clean_up_temp` like other temporary paths
+    NATIVE_SYS.temp_paths.lock().push(path.clone());
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    command
+        .arg(&path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(drop)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))
+}
+
+#[cfg(feature = "clipboard")]
+fn no_clipboard_program() -> String {
+    if cfg!(any(target_os = "macos", windows)) {
+        "Unable to access the clipboard".into()
+    } else {
+        "Unable to access the clipboard. Install wl-clipboard, xclip, or xsel.".into()
+    }
+}
+
+/// The TLS configuration used for HTTPS requests
+// https://github.com/rustls/rustls/blob/c9cfe3499681361372351a57a00ccd793837ae9c/examples/src/bin/simpleclient.rs
+#[cfg(feature = "https")]
+static TLS_CLIENT_CONFIG: Lazy<Arc<rustls::ClientConfig>> = Lazy::new(|| {
+    let mut store = rustls::RootCertStore::empty();
+    store.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|ta| {
+        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+    rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(store)
+        .with_no_client_auth()
+        .into()
+});
+
+/// Splits a URL into whether it uses TLS, its host, its port, and its path
+///
+/// The URL must use either `scheme` or its secure variant, like `http` and `https`.
+#[cfg(feature = "https")]
+fn parse_url(url: &str, scheme: &str) -> Result<(bool, String, u16, String), String> {
+    let (tls, rest) = if let Some(rest) = url.strip_prefix(&format!("{scheme}s://")) {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix(&format!("{scheme}://")) {
+        (false, rest)
+    } else {
+        return Err(format!(
+            "URL {url:?} must start with {scheme}:// or {scheme}s://"
+        ));
+    };
+    let (authority, path) = match rest.find(['/', '?']) {
+        Some(i) if rest[i..].starts_with('/') => (&rest[..i], rest[i..].to_string()),
+        Some(i) => (&rest[..i], format!("/{}", &rest[i..])),
+        None => (rest, "/".to_string()),
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port
+                .parse()
+                .map_err(|_| format!("Invalid port in URL {url:?}"))?;
+            (host, port)
+        }
+        None => (authority, if tls { 443 } else { 80 }),
+    };
+    if host.is_empty() {
+        return Err(format!("URL {url:?} has no host"));
+    }
+    Ok((tls, host.into(), port, path))
+}
+
+/// Parses a raw HTTP response, decoding a chunked body if necessary
+#[cfg(feature = "https")]
+fn parse_http_response(bytes: &[u8]) -> Result<HttpResponse, String> {
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut res = httparse::Response::new(&mut headers);
+    let body_start = match res
+        .parse(bytes)
+        .map_err(|e| format!("Failed to parse HTTP response: {e}"))?
+    {
+        httparse::Status::Complete(len) => len,
+        httparse::Status::Partial => return Err("Incomplete HTTP response".into()),
+    };
+    let status = res.code.ok_or("No status in HTTP response")?;
+    let mut chunked = false;
+    let headers: Vec<String> = res
+        .headers
+        .iter()
+        .map(|h| {
+            let value = String::from_utf8_lossy(h.value);
+            if h.name.eq_ignore_ascii_case("transfer-encoding")
+                && value.to_ascii_lowercase().contains("chunked")
+            {
+                chunked = true;
+            }
+            format!("{}: {value}", h.name)
+        })
+        .collect();
+    let mut body = &bytes[body_start..];
+    let body = if chunked {
+        let mut decoded = Vec::new();
+        loop {
+            let line_end = (body.windows(2).position(|w| w == b"\r\n"))
+                .ok_or("Invalid chunk in HTTP response")?;
+            let size = String::from_utf8_lossy(&body[..line_end]);
+            let size = size.split(';').next().unwrap_or_default().trim();
+            let size = usize::from_str_radix(size, 16)
+                .map_err(|_| format!("Invalid chunk size {size:?} in HTTP response"))?;
+            body = &body[line_end + 2..];
+            if size == 0 {
+                break;
+            }
+            if body.len() < size {
+                return Err("Incomplete chunk in HTTP response".into());
+            }
+            decoded.extend_from_slice(&body[..size]);
+            body = body[size..].strip_prefix(b"\r\n").unwrap_or(&body[size..]);
+        }
+        decoded
+    } else {
+        body.to_vec()
+    };
+    Ok(HttpResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+/// A TCP stream that may be wrapped in TLS
+#[cfg(feature = "https")]
+enum NetStream {
+    Plain(TcpStream),
+    Tls(Box<rustls::StreamOwned<rustls::ClientConnection, TcpStream>>),
+}
+
+#[cfg(feature = "https")]
+impl NetStream {
+    fn connect(tls: bool, host: &str, port: u16) -> Result<Self, String> {
+        let tcp_stream = TcpStream::connect((host, port)).map_err(|e| e.to_string())?;
+        Ok(if tls {
+            let server_name = rustls::ServerName::try_from(host).map_err(|e| e.to_string())?;
+            let conn = rustls::ClientConnection::new(TLS_CLIENT_CONFIG.clone(), server_name)
+                .map_err(|e| e.to_string())?;
+            NetStream::Tls(Box::new(rustls::StreamOwned::new(conn, tcp_stream)))
+        } else {
+            NetStream::Plain(tcp_stream)
+        })
+    }
+}
+
+#[cfg(feature = "https")]
+impl Read for NetStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            NetStream::Plain(stream) => stream.read(buf),
+            NetStream::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+#[cfg(feature = "https")]
+impl Write for NetStream {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            NetStream::Plain(stream) => stream.write(buf),
+            NetStream::Tls(stream) => stream.write(buf),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            NetStream::Plain(stream) => stream.flush(),
+            NetStream::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A message sent or received over a websocket
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    /// A text frame
+    Text(String),
+    /// A binary frame
+    Binary(Vec<u8>),
+}
+
+/// A client websocket connection
+///
+/// See [RFC 6455](https://datatracker.ietf.org/doc/html/rfc6455)
+#[cfg(feature = "https")]
+struct WebSocket {
+    stream: NetStream,
+    closed: bool,
+}
+
+#[cfg(feature = "https")]
+impl WebSocket {
+    const CONTINUATION: u8 = 0x0;
+    const TEXT: u8 = 0x1;
+    const BINARY: u8 = 0x2;
+    const CLOSE: u8 = 0x8;
+    const PING: u8 = 0x9;
+    const PONG: u8 = 0xA;
+    fn connect(url: &str) -> Result<Self, String> {
+        let (tls, host, port, path) = parse_url(url, "ws")?;
+        let mut stream = NetStream::connect(tls, &host, port)?;
+        let key: [u8; 16] = rand::random();
+        let request = format!(
+            "GET {path} HTTP/1.1\r\n\
+            Host: {host}\r\n\
+            Upgrade: websocket\r\n\
+            Connection: Upgrade\r\n\
+            Sec-WebSocket-Key: {}\r\n\
+            Sec-WebSocket-Version: 13\r\n\r\n",
+            base64(&key)
+        );
+        stream
+            .write_all(request.as_bytes())
+            .map_err(|e| e.to_string())?;
+        // Read the response one byte at a time so that no frames are consumed
+        let mut response = Vec::new();
+        let mut byte = [0];
+        while !response.ends_with(b"\r\n\r\n") {
+            match stream.read(&mut byte).map_err(|e| e.to_string())? {
+                0 => return Err("Websocket connection closed during handshake".into()),
+                _ => response.push(byte[0]),
+            }
+        }
+        let response = parse_http_response(&response)?;
+        if response.status != 101 {
+            return Err(format!(
+                "Websocket handshake failed with status {}",
+                response.status
+            ));
+        }
+        Ok(WebSocket {
+            stream,
+            closed: false,
+        })
+    }
+    fn send_frame(&mut self, opcode: u8, payload: &[u8]) -> Result<(), String> {
+        if self.closed {
+            return Err("Websocket is closed".into());
+        }
+        let mut frame = vec![0x80 | opcode];
+        match payload.len() {
+            len @ 0..=125 => frame.push(0x80 | len as u8),
+            len @ 126..=0xFFFF => {
+                frame.push(0x80 | 126);
+                frame.extend((len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0x80 | 127);
+                frame.extend((len as u64).to_be_bytes());
+            }
+        }
+        // Clients must mask every frame they send
+        let mask: [u8; 4] = rand::random();
+        frame.extend(mask);
+        frame.extend(payload.iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+        (self.stream.write_all(&frame))
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| e.to_string())
+    }
+    fn read_frame(&mut self) -> Result<(bool, u8, Vec<u8>), String> {
+        let mut read = |len: usize| {
+            let mut buf = vec![0; len];
+            self.stream
+                .read_exact(&mut buf)
+                .map(|_| buf)
+                .map_err(|e| e.to_string())
+        };
+        let header = read(2)?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0F;
+        let masked = header[1] & 0x80 != 0;
+        let len = match header[1] & 0x7F {
+            126 => u16::from_be_bytes(read(2)?.try_into().unwrap()) as usize,
+            127 => u64::from_be_bytes(read(8)?.try_into().unwrap()) as usize,
+            len => len as usize,
+        };
+        let mask = if masked { Some(read(4)?) } else { None };
+        let mut payload = read(len)?;
+        if let Some(mask) = mask {
+            for (b, m) in payload.iter_mut().zip(mask.iter().cycle()) {
+                *b ^= m;
+            }
+        }
+        Ok((fin, opcode, payload))
+    }
+    /// Receive the next message, or `None` if the connection was closed
+    fn receive(&mut self) -> Result<Option<WebSocketMessage>, String> {
+        if self.closed {
+            return Ok(None);
+        }
+        let mut message: Option<(u8, Vec<u8>)> = None;
+        loop {
+            let (fin, opcode, payload) = self.read_frame()?;
+            match opcode {
+                WebSocket::PING => self.send_frame(WebSocket::PONG, &payload)?,
+                WebSocket::PONG => {}
+                WebSocket::CLOSE => {
+                    _ = self.send_frame(WebSocket::CLOSE, &[]);
+                    self.closed = true;
+                    return Ok(None);
+                }
+                WebSocket::CONTINUATION => match &mut message {
+                    Some((_, data)) => data.extend(payload),
+                    None => return Err("Unexpected websocket continuation frame".into()),
+                },
+                WebSocket::TEXT | WebSocket::BINARY => message = Some((opcode, payload)),
+                _ => return Err(format!("Unknown websocket opcode {opcode}")),
+            }
+            if fin {
+                if let Some((opcode, data)) = message.take() {
+                    return Ok(Some(if opcode == WebSocket::TEXT {
+                        WebSocketMessage::Text(String::from_utf8(data).map_err(|e| e.to_string())?)
+                    } else {
+                        WebSocketMessage::Binary(data)
+                    }));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "https")]
+impl Drop for WebSocket {
+    fn drop(&mut self) {
+        _ = self.send_frame(WebSocket::CLOSE, &[]);
+    }
+}
+
+/// Standard base64 encoding with padding
+#[cfg(feature = "https")]
+fn base64(bytes: &[u8]) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut s = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(CHARS[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
 }
 
 /// Takes an HTTP request, validates it, and fixes it (if possible) by adding
@@ -1096,6 +2768,9 @@ fn check_http(mut request: String, hostname: &str) -> Result<String, String> {
 
 impl SysOp {
     pub(crate) fn run(&self, env: &mut Uiua) -> UiuaResult {
+        if !env.backend.supports(*self) {
+            return Err(env.error(format!("{} is not supported by this backend", self.name())));
+        }
         match self {
             SysOp::Show => {
                 let s = env.pop(1)?.grid_string();
@@ -1110,6 +2785,14 @@ impl SysOp {
                     .print_str_stdout(&val.to_string())
                     .map_err(|e| env.error(e))?;
             }
+            SysOp::PrintStyled => {
+                let style = env.pop(1)?.as_string(env, "Style must be a string")?;
+                let style = TextStyle::parse(&style).map_err(|e| env.error(e))?;
+                let val = env.pop(2)?;
+                env.backend
+                    .print_styled_stdout(&val.to_string(), &style)
+                    .map_err(|e| env.error(e))?;
+            }
             SysOp::Print => {
                 let val = env.pop(1)?;
                 env.backend
@@ -1119,6 +2802,18 @@ impl SysOp {
                     .print_str_stdout("\n")
                     .map_err(|e| env.error(e))?;
             }
+            SysOp::EPrint => {
+                let val = env.pop(1)?;
+                env.backend
+                    .print_str_stderr(&format!("{val}\n"))
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::Flush => env.backend.flush().map_err(|e| env.error(e))?,
+            SysOp::LineBuffered => {
+                let line_buffered =
+                    (env.pop(1)?).as_bool(env, "Line buffering must be a boolean")?;
+                (env.backend.set_line_buffered(line_buffered)).map_err(|e| env.error(e))?;
+            }
             SysOp::ScanLine => {
                 if let Some(line) = env.backend.scan_line_stdin().map_err(|e| env.error(e))? {
                     env.push(line);
@@ -1126,6 +2821,23 @@ impl SysOp {
                     env.push(0u8);
                 }
             }
+            SysOp::ScanAll => {
+                let bytes = env.backend.read_stdin(None).map_err(|e| env.error(e))?;
+                let s = String::from_utf8(bytes).map_err(|e| env.error(e))?;
+                env.push(s);
+            }
+            SysOp::StdinEof => {
+                let eof = env.backend.stdin_eof().map_err(|e| env.error(e))?;
+                env.push(eof);
+            }
+            SysOp::RawMode => {
+                let raw = env.pop(1)?.as_bool(env, "Raw mode must be a boolean")?;
+                env.backend.set_raw_mode(raw).map_err(|e| env.error(e))?;
+            }
+            SysOp::Key => match env.backend.poll_key().map_err(|e| env.error(e))? {
+                Some(key) => env.push(key),
+                None => env.push(0u8),
+            },
             SysOp::TermSize => {
                 let (width, height) = env.backend.term_size().map_err(|e| env.error(e))?;
                 env.push(vec![height as f64, width as f64])
@@ -1136,6 +2848,12 @@ impl SysOp {
                 args.extend(env.args().to_owned());
                 env.push(Array::<Arc<Function>>::from_iter(args));
             }
+            SysOp::Exit => {
+                let code = env.pop(1)?.as_int(env, "Exit code must be an integer")?;
+                let code = i32::try_from(code)
+                    .map_err(|_| env.error(format!("Exit code {code} is out of range")))?;
+                return Err(UiuaError::Exit(code, env.span()));
+            }
             SysOp::Var => {
                 let key = env
                     .pop(1)?
@@ -1143,6 +2861,22 @@ impl SysOp {
                 let var = env.backend.var(&key).unwrap_or_default();
                 env.push(var);
             }
+            SysOp::SetVar => {
+                let name = env
+                    .pop(1)?
+                    .as_string(env, "Variable name must be a string")?;
+                let value = env
+                    .pop(2)?
+                    .as_string(env, "Variable value must be a string")?;
+                env.backend
+                    .set_var(&name, &value)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::Vars => {
+                let (names, values): (Vec<_>, Vec<_>) = env.backend.vars().into_iter().unzip();
+                env.push(Array::<Arc<Function>>::from_iter(values));
+                env.push(Array::<Arc<Function>>::from_iter(names));
+            }
             SysOp::FOpen => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 let handle = env.backend.open_file(&path).map_err(|e| env.error(e))?;
@@ -1162,11 +2896,8 @@ impl SysOp {
                 let bytes = match handle {
                     Handle::STDOUT => return Err(env.error("Cannot read from stdout")),
                     Handle::STDERR => return Err(env.error("Cannot read from stderr")),
-                    Handle::STDIN => stdin()
-                        .lock()
-                        .bytes()
-                        .take(count)
-                        .collect::<Result<Vec<_>, _>>()
+                    Handle::STDIN => (env.backend)
+                        .read_stdin(Some(count))
                         .map_err(|e| env.error(e))?,
                     _ => env.backend.read(handle, count).map_err(|e| env.error(e))?,
                 };
@@ -1182,16 +2913,45 @@ impl SysOp {
                 let bytes = match handle {
                     Handle::STDOUT => return Err(env.error("Cannot read from stdout")),
                     Handle::STDERR => return Err(env.error("Cannot read from stderr")),
-                    Handle::STDIN => stdin()
-                        .lock()
-                        .bytes()
-                        .take(count)
-                        .collect::<Result<Vec<_>, _>>()
+                    Handle::STDIN => (env.backend)
+                        .read_stdin(Some(count))
                         .map_err(|e| env.error(e))?,
                     _ => env.backend.read(handle, count).map_err(|e| env.error(e))?,
                 };
                 env.push(bytes);
             }
+            SysOp::ReadLine => {
+                let handle = env
+                    .pop(1)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let line = match handle {
+                    Handle::STDOUT => return Err(env.error("Cannot read from stdout")),
+                    Handle::STDERR => return Err(env.error("Cannot read from stderr")),
+                    Handle::STDIN => env.backend.scan_line_stdin().map_err(|e| env.error(e))?,
+                    _ => {
+                        let mut bytes = (env.backend)
+                            .read_until(handle, b"\n")
+                            .map_err(|e| env.error(e))?;
+                        if bytes.is_empty() {
+                            None
+                        } else {
+                            if bytes.ends_with(b"\n") {
+                                bytes.pop();
+                                if bytes.ends_with(b"\r") {
+                                    bytes.pop();
+                                }
+                            }
+                            Some(String::from_utf8(bytes).map_err(|e| env.error(e))?)
+                        }
+                    }
+                };
+                if let Some(line) = line {
+                    env.push(line);
+                } else {
+                    env.push(0u8);
+                }
+            }
             SysOp::ReadUntil => {
                 let delim = env.pop(1)?;
                 let handle = env
@@ -1267,12 +3027,7 @@ impl SysOp {
                     .pop(2)?
                     .as_nat(env, "Handle must be an natural number")?
                     .into();
-                let bytes: Vec<u8> = match data {
-                    Value::Num(arr) => arr.data.iter().map(|&x| x as u8).collect(),
-                    Value::Byte(arr) => arr.data.into(),
-                    Value::Char(arr) => arr.data.iter().collect::<String>().into(),
-                    Value::Func(_) => return Err(env.error("Cannot write function array to file")),
-                };
+                let bytes = value_to_io_bytes(data, env)?;
                 match handle {
                     Handle::STDOUT => env
                         .backend
@@ -1302,7 +3057,13 @@ impl SysOp {
                         }
                     })
                     .map_err(|e| env.error(e))?;
-                let s = String::from_utf8(bytes).map_err(|e| env.error(e))?;
+                let s = String::from_utf8(bytes).map_err(|e| {
+                    env.error(format!(
+                        "{path} is not valid UTF-8 ({e}). \
+                        Use {} to read it as bytes.",
+                        SysOp::FReadAllBytes.name()
+                    ))
+                })?;
                 env.push(s);
             }
             SysOp::FReadAllBytes => {
@@ -1324,12 +3085,7 @@ impl SysOp {
             SysOp::FWriteAll => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 let data = env.pop(2)?;
-                let bytes: Vec<u8> = match data {
-                    Value::Num(arr) => arr.data.iter().map(|&x| x as u8).collect(),
-                    Value::Byte(arr) => arr.data.into(),
-                    Value::Char(arr) => arr.data.iter().collect::<String>().into(),
-                    Value::Func(_) => return Err(env.error("Cannot write function array to file")),
-                };
+                let bytes = value_to_io_bytes(data, env)?;
                 env.backend
                     .file_write_all(&path, &bytes)
                     .or_else(|e| {
@@ -1358,6 +3114,95 @@ impl SysOp {
                 let is_file = env.backend.is_file(&path).map_err(|e| env.error(e))?;
                 env.push(is_file);
             }
+            SysOp::FSize => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let size = env.backend.file_size(&path).map_err(|e| env.error(e))?;
+                env.push(size as f64);
+            }
+            SysOp::FModified => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let time = env.backend.file_modified(&path).map_err(|e| env.error(e))?;
+                env.push(time);
+            }
+            SysOp::FWatch => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let timeout = env.pop(2)?.as_num(env, "Timeout must be a number")?;
+                let timeout = if timeout.is_infinite() {
+                    None
+                } else {
+                    Some(timeout.max(0.0))
+                };
+                let paths = env
+                    .backend
+                    .watch_path(&path, timeout)
+                    .map_err(|e| env.error(e))?;
+                env.push(Array::<Arc<Function>>::from_iter(paths));
+            }
+            SysOp::FMakeDir => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                env.backend.make_dir(&path).map_err(|e| env.error(e))?;
+            }
+            SysOp::FRemoveDir => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                env.backend.remove_dir(&path).map_err(|e| env.error(e))?;
+            }
+            SysOp::FDelete => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                env.backend.delete_file(&path).map_err(|e| env.error(e))?;
+            }
+            SysOp::TempFile => {
+                let path = env.backend.create_temp_file().map_err(|e| env.error(e))?;
+                env.push(path);
+            }
+            SysOp::TempDir => {
+                let path = env.backend.create_temp_dir().map_err(|e| env.error(e))?;
+                env.push(path);
+            }
+            SysOp::PathJoin => {
+                let base = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let path = env.pop(2)?.as_string(env, "Path must be a string")?;
+                env.push(Path::new(&base).join(path).to_string_lossy().into_owned());
+            }
+            SysOp::PathSplit => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let path = Path::new(&path);
+                let string = |s: Option<&std::ffi::OsStr>| {
+                    s.map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                };
+                let parent = path.parent().map(Path::as_os_str);
+                env.push(string(path.extension()));
+                env.push(string(path.file_stem()));
+                env.push(string(parent));
+            }
+            SysOp::PathNormalize => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                env.push(normalize_path(Path::new(&path)));
+            }
+            SysOp::PathAbsolute => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let path = Path::new(&path);
+                let path = if path.is_absolute() {
+                    normalize_path(path)
+                } else {
+                    let dir = env.backend.current_dir().map_err(|e| env.error(e))?;
+                    normalize_path(&Path::new(&dir).join(path))
+                };
+                env.push(path);
+            }
+            SysOp::FSeek => {
+                let pos = env.pop(1)?.as_int(env, "Position must be an integer")?;
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let pos = if pos < 0 {
+                    SeekFrom::End(pos as i64 + 1)
+                } else {
+                    SeekFrom::Start(pos as u64)
+                };
+                env.backend.seek(handle, pos).map_err(|e| env.error(e))?;
+            }
             SysOp::Import => {
                 let path = env.pop(1)?.as_string(env, "Import path must be a string")?;
                 let input = String::from_utf8(
@@ -1375,6 +3220,7 @@ impl SysOp {
                 .map_err(|e| env.error(format!("Failed to read file: {e}")))?;
                 env.import(&input, path.as_ref())?;
             }
+            #[cfg(feature = "image")]
             SysOp::ImDecode => {
                 let bytes = match env.pop(1)? {
                     Value::Byte(arr) => {
@@ -1397,53 +3243,163 @@ impl SysOp {
                     }
                     _ => return Err(env.error("Image bytes must be a numeric array")),
                 };
-                let image = image::load_from_memory(&bytes)
-                    .map_err(|e| env.error(format!("Failed to read image: {}", e)))?
-                    .into_rgba8();
-                let shape = tiny_vec![image.height() as usize, image.width() as usize, 4];
-                let array = Array::<f64>::new(
-                    shape,
-                    image
-                        .into_raw()
-                        .into_iter()
-                        .map(|b| b as f64 / 255.0)
-                        .collect::<CowSlice<_>>(),
-                );
+                let array = image_bytes_to_array(&bytes).map_err(|e| env.error(e))?;
                 env.push(array);
             }
+            #[cfg(feature = "image")]
             SysOp::ImEncode => {
                 let format = env
                     .pop(1)?
                     .as_string(env, "Image format must be a string")?;
                 let value = env.pop(2)?;
-                let output_format = match format.as_str() {
-                    "jpg" | "jpeg" => ImageOutputFormat::Jpeg(100),
-                    "png" => ImageOutputFormat::Png,
-                    "bmp" => ImageOutputFormat::Bmp,
-                    "gif" => ImageOutputFormat::Gif,
-                    "ico" => ImageOutputFormat::Ico,
-                    format => return Err(env.error(format!("Invalid image format: {}", format))),
-                };
+                let output_format = image_format(&format).map_err(|e| env.error(e))?;
                 let bytes =
                     value_to_image_bytes(&value, output_format).map_err(|e| env.error(e))?;
                 env.push(Array::<u8>::from(bytes));
             }
+            #[cfg(feature = "image")]
+            SysOp::ImRead => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let bytes = env.backend.file_read_all(&path).map_err(|e| env.error(e))?;
+                let array = image_bytes_to_array(&bytes).map_err(|e| env.error(e))?;
+                env.push(array);
+            }
+            #[cfg(feature = "image")]
+            SysOp::ImWrite => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let value = env.pop(2)?;
+                let extension = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+                let output_format = image_format(&extension.to_lowercase()).map_err(|_| {
+                    env.error(format!(
+                        "Cannot write image to {path:?} because its extension \
+                        is not jpg, jpeg, png, bmp, gif, or ico"
+                    ))
+                })?;
+                let bytes =
+                    value_to_image_bytes(&value, output_format).map_err(|e| env.error(e))?;
+                env.backend
+                    .file_write_all(&path, &bytes)
+                    .map_err(|e| env.error(e))?;
+            }
+            #[cfg(feature = "image")]
             SysOp::ImShow => {
                 let value = env.pop(1)?;
                 let image = value_to_image(&value).map_err(|e| env.error(e))?;
                 env.backend.show_image(image).map_err(|e| env.error(e))?;
             }
-            SysOp::GifEncode => {
-                let delay = env.pop(1)?.as_num(env, "Delay must be a number")?;
-                let value = env.pop(2)?;
-                let bytes = value_to_gif_bytes(&value, delay).map_err(|e| env.error(e))?;
+            #[cfg(feature = "image")]
+            SysOp::GifEncode => {
+                let frame_rate = env.pop(1)?.as_num(env, "Frame rate must be a number")?;
+                let value = env.pop(2)?;
+                let bytes = value_to_gif_bytes(&value, frame_rate).map_err(|e| env.error(e))?;
+                env.push(Array::<u8>::from(bytes));
+            }
+            #[cfg(feature = "image")]
+            SysOp::GifWrite => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let frame_rate = env.pop(2)?.as_num(env, "Frame rate must be a number")?;
+                let value = env.pop(3)?;
+                let bytes = value_to_gif_bytes(&value, frame_rate).map_err(|e| env.error(e))?;
+                env.backend
+                    .file_write_all(&path, &bytes)
+                    .map_err(|e| env.error(e))?;
+            }
+            #[cfg(feature = "image")]
+            SysOp::GifShow => {
+                let frame_rate = env.pop(1)?.as_num(env, "Frame rate must be a number")?;
+                let value = env.pop(2)?;
+                let bytes = value_to_gif_bytes(&value, frame_rate).map_err(|e| env.error(e))?;
+                env.backend.show_gif(bytes).map_err(|e| env.error(e))?;
+            }
+            #[cfg(not(feature = "image"))]
+            SysOp::ImDecode
+            | SysOp::ImEncode
+            | SysOp::ImRead
+            | SysOp::ImWrite
+            | SysOp::ImShow
+            | SysOp::GifEncode
+            | SysOp::GifWrite
+            | SysOp::GifShow => {
+                return Err(env.error("Images are not supported in this build of Uiua"));
+            }
+            #[cfg(feature = "gzip")]
+            SysOp::GzipCompress => {
+                use flate2::{write::GzEncoder, Compression};
+                let bytes = value_to_io_bytes(env.pop(1)?, env)?;
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                let compressed = encoder
+                    .write_all(&bytes)
+                    .and_then(|_| encoder.finish())
+                    .map_err(|e| env.error(format!("Failed to compress: {e}")))?;
+                env.push(Array::<u8>::from(compressed));
+            }
+            #[cfg(feature = "gzip")]
+            SysOp::GzipDecompress => {
+                let bytes = value_to_io_bytes(env.pop(1)?, env)?;
+                let mut decompressed = Vec::new();
+                flate2::read::MultiGzDecoder::new(bytes.as_slice())
+                    .read_to_end(&mut decompressed)
+                    .map_err(|e| env.error(format!("Failed to decompress: {e}")))?;
+                env.push(Array::<u8>::from(decompressed));
+            }
+            #[cfg(not(feature = "gzip"))]
+            SysOp::GzipCompress | SysOp::GzipDecompress => {
+                return Err(env.error("Gzip is not supported in this build of Uiua"));
+            }
+            SysOp::NpyDecode => {
+                let bytes = value_to_io_bytes(env.pop(1)?, env)?;
+                let value = npy_bytes_to_value(&bytes).map_err(|e| env.error(e))?;
+                env.push(value);
+            }
+            SysOp::NpyEncode => {
+                let value = env.pop(1)?;
+                let bytes = value_to_npy_bytes(&value).map_err(|e| env.error(e))?;
+                env.push(Array::<u8>::from(bytes));
+            }
+            SysOp::NpzDecode => {
+                let bytes = value_to_io_bytes(env.pop(1)?, env)?;
+                let arrays = npz_bytes_to_values(&bytes).map_err(|e| env.error(e))?;
+                let (names, values): (Vec<_>, Vec<_>) = (arrays.into_iter())
+                    .map(|(name, value)| (Value::from(name), value))
+                    .unzip();
+                env.push(boxed_list(values));
+                env.push(boxed_list(names));
+            }
+            SysOp::NpzEncode => {
+                let names = env.pop(1)?;
+                let values = env.pop(2)?;
+                if names.row_count() != values.row_count() {
+                    return Err(env.error(format!(
+                        "There are {} names but {} arrays",
+                        names.row_count(),
+                        values.row_count()
+                    )));
+                }
+                let mut arrays = Vec::with_capacity(names.row_count());
+                for (name, value) in names.into_rows().zip(values.into_rows()) {
+                    let name = unboxed(name).as_string(env, "Array names must be strings")?;
+                    arrays.push((name, unboxed(value)));
+                }
+                let bytes = values_to_npz_bytes(&arrays).map_err(|e| env.error(e))?;
                 env.push(Array::<u8>::from(bytes));
             }
-            SysOp::GifShow => {
-                let delay = env.pop(1)?.as_num(env, "Delay must be a number")?;
-                let value = env.pop(2)?;
-                let bytes = value_to_gif_bytes(&value, delay).map_err(|e| env.error(e))?;
-                env.backend.show_gif(bytes).map_err(|e| env.error(e))?;
+            SysOp::Hash => {
+                let algorithm = env
+                    .pop(1)?
+                    .as_string(env, "Hash algorithm must be a string")?;
+                let algorithm = HashAlgorithm::parse(&algorithm).map_err(|e| env.error(e))?;
+                let bytes = value_to_io_bytes(env.pop(2)?, env)?;
+                env.push(Array::<u8>::from(algorithm.digest(&bytes)));
+            }
+            SysOp::Hmac => {
+                let algorithm = env
+                    .pop(1)?
+                    .as_string(env, "HMAC algorithm must be a string")?;
+                let algorithm = HashAlgorithm::parse(&algorithm).map_err(|e| env.error(e))?;
+                let key = value_to_io_bytes(env.pop(2)?, env)?;
+                let message = value_to_io_bytes(env.pop(3)?, env)?;
+                let mac = algorithm.hmac(&key, &message).map_err(|e| env.error(e))?;
+                env.push(Array::<u8>::from(mac));
             }
             SysOp::AudioDecode => {
                 let bytes = match env.pop(1)? {
@@ -1467,8 +3423,24 @@ impl SysOp {
                     }
                     _ => return Err(env.error("Audio bytes be a numeric array")),
                 };
-                let array = array_from_wav_bytes(&bytes, env).map_err(|e| env.error(e))?;
+                let (array, _) = array_from_wav_bytes(&bytes, env)?;
+                env.push(array);
+            }
+            SysOp::AudioRead => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let bytes = env.backend.file_read_all(&path).map_err(|e| env.error(e))?;
+                let (array, sample_rate) = array_from_wav_bytes(&bytes, env)?;
                 env.push(array);
+                env.push(f64::from(sample_rate));
+            }
+            SysOp::AudioWrite => {
+                let path = env.pop(1)?.as_string(env, "Path must be a string")?;
+                let sample_rate = sample_rate_arg(env.pop(2)?, env)?;
+                let value = env.pop(3)?;
+                let bytes = value_to_wav_bytes(&value, sample_rate).map_err(|e| env.error(e))?;
+                env.backend
+                    .file_write_all(&path, &bytes)
+                    .map_err(|e| env.error(e))?;
             }
             SysOp::AudioEncode => {
                 let format = env
@@ -1488,6 +3460,12 @@ impl SysOp {
                     .map_err(|e| env.error(e))?;
                 env.backend.play_audio(bytes).map_err(|e| env.error(e))?;
             }
+            SysOp::AudioPlayRate => {
+                let sample_rate = sample_rate_arg(env.pop(1)?, env)?;
+                let value = env.pop(2)?;
+                let bytes = value_to_wav_bytes(&value, sample_rate).map_err(|e| env.error(e))?;
+                env.backend.play_audio(bytes).map_err(|e| env.error(e))?;
+            }
             SysOp::AudioSampleRate => {
                 let sample_rate = env.backend.audio_sample_rate();
                 env.push(f64::from(sample_rate));
@@ -1529,6 +3507,55 @@ impl SysOp {
                     .max(0.0);
                 env.backend.sleep(seconds).map_err(|e| env.error(e))?;
             }
+            SysOp::Monotonic => env.push(env.backend.monotonic_time()),
+            SysOp::DateTime => {
+                let times = value_to_nums(env.pop(1)?, env, "Time")?;
+                let mut shape = times.shape.clone();
+                shape.push(6);
+                let mut data = Vec::with_capacity(times.data.len() * 6);
+                for &t in &times.data {
+                    data.extend(time::time_to_components(t).map_err(|e| env.error(e))?);
+                }
+                env.push(Array::new(shape, data));
+            }
+            SysOp::DateTimeSeconds => {
+                let dates = value_to_nums(env.pop(1)?, env, "Date")?;
+                let mut shape = dates.shape.clone();
+                let width = shape.pop().unwrap_or(1);
+                if width == 0 || width > 6 {
+                    return Err(env.error(format!(
+                        "Date must have between 1 and 6 components, but it has {width}"
+                    )));
+                }
+                let mut data = Vec::with_capacity(dates.data.len() / width);
+                for date in dates.data.chunks_exact(width) {
+                    data.push(time::components_to_time(date).map_err(|e| env.error(e))?);
+                }
+                env.push(Array::new(shape, data));
+            }
+            SysOp::Plot => {
+                let style = env.pop(1)?.as_string(env, "Plot style must be a string")?;
+                let style = PlotStyle::parse(&style).map_err(|e| env.error(e))?;
+                let data = value_to_nums(env.pop(2)?, env, "Plot data")?;
+                let image = plot::plot(&style, &data).map_err(|e| env.error(e))?;
+                env.push(image);
+            }
+            SysOp::FormatTime => {
+                let pattern = env
+                    .pop(1)?
+                    .as_string(env, "Time pattern must be a string")?;
+                let seconds = env.pop(2)?.as_num(env, "Time must be a number")?;
+                let s = time::format_time(&pattern, seconds).map_err(|e| env.error(e))?;
+                env.push(s);
+            }
+            SysOp::ParseTime => {
+                let pattern = env
+                    .pop(1)?
+                    .as_string(env, "Time pattern must be a string")?;
+                let s = env.pop(2)?.as_string(env, "Date must be a string")?;
+                let seconds = time::parse_time(&pattern, &s).map_err(|e| env.error(e))?;
+                env.push(seconds);
+            }
             SysOp::TcpListen => {
                 let addr = env.pop(1)?.as_string(env, "Address must be a string")?;
                 let handle = env.backend.tcp_listen(&addr).map_err(|e| env.error(e))?;
@@ -1555,6 +3582,83 @@ impl SysOp {
                 let addr = env.backend.tcp_addr(handle).map_err(|e| env.error(e))?;
                 env.push(addr);
             }
+            SysOp::UdpBind => {
+                let addr = env.pop(1)?.as_string(env, "Address must be a string")?;
+                let handle = env.backend.udp_bind(&addr).map_err(|e| env.error(e))?;
+                env.push(handle);
+            }
+            SysOp::UdpSend => {
+                let data = value_to_io_bytes(env.pop(1)?, env)?;
+                let addr = env.pop(2)?.as_string(env, "Address must be a string")?;
+                let handle = env
+                    .pop(3)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .udp_send(handle, &data, &addr)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::UdpReceive => {
+                let max = env.pop(1)?.as_nat(env, "Count must be an integer")?;
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let (bytes, addr) = env
+                    .backend
+                    .udp_receive(handle, max)
+                    .map_err(|e| env.error(e))?;
+                env.push(addr);
+                env.push(bytes);
+            }
+            SysOp::UdpSetReadTimeout => {
+                let timeout = env.pop(1)?.as_num(env, "Timeout must be a number")?.abs();
+                let timeout = if timeout.is_infinite() {
+                    None
+                } else {
+                    Some(Duration::try_from_secs_f64(timeout).map_err(|_| {
+                        env.error(format!(
+                            "Timeout must be a number of seconds or infinity, but it is {timeout:e}"
+                        ))
+                    })?)
+                };
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .udp_set_read_timeout(handle, timeout)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::WsConnect => {
+                let url = env.pop(1)?.as_string(env, "URL must be a string")?;
+                let handle = env.backend.ws_connect(&url).map_err(|e| env.error(e))?;
+                env.push(handle);
+            }
+            SysOp::WsSend => {
+                let message = match env.pop(1)? {
+                    Value::Char(arr) => WebSocketMessage::Text(arr.data.iter().collect()),
+                    value => WebSocketMessage::Binary(value_to_io_bytes(value, env)?),
+                };
+                let handle = env
+                    .pop(2)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                env.backend
+                    .ws_send(handle, message)
+                    .map_err(|e| env.error(e))?;
+            }
+            SysOp::WsReceive => {
+                let handle = env
+                    .pop(1)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                match env.backend.ws_receive(handle).map_err(|e| env.error(e))? {
+                    Some(WebSocketMessage::Text(text)) => env.push(text),
+                    Some(WebSocketMessage::Binary(bytes)) => env.push(bytes),
+                    None => env.push(0u8),
+                }
+            }
             SysOp::TcpSetNonBlocking => {
                 let handle = env
                     .pop(1)?
@@ -1608,6 +3712,22 @@ impl SysOp {
                     .map_err(|e| env.error(e))?;
                 env.push(res);
             }
+            SysOp::HttpRequest => {
+                let method = env.pop(1)?.as_string(env, "HTTP method must be a string")?;
+                let url = env.pop(2)?.as_string(env, "URL must be a string")?;
+                let headers = value_to_strings(&env.pop(3)?, env, "HTTP headers")?;
+                let body = value_to_io_bytes(env.pop(4)?, env)?;
+                let res = env
+                    .backend
+                    .http_request(&method, &url, &headers, &body)
+                    .map_err(|e| env.error(e))?;
+                match String::from_utf8(res.body) {
+                    Ok(body) => env.push(body),
+                    Err(e) => env.push(e.into_bytes()),
+                }
+                env.push(Array::<Arc<Function>>::from_iter(res.headers));
+                env.push(res.status as f64);
+            }
             SysOp::Close => {
                 let handle = env
                     .pop(1)?
@@ -1632,6 +3752,45 @@ impl SysOp {
                 env.push(stdout);
                 env.push(stderr);
             }
+            SysOp::RunPiped => {
+                let (command, args) = value_to_command(&env.pop(1)?, env)?;
+                let args: Vec<_> = args.iter().map(|s| s.as_str()).collect();
+                let input = value_to_io_bytes(env.pop(2)?, env)?;
+                let (stdout, stderr, code) = env
+                    .backend
+                    .run_command_piped(&command, &args, &input)
+                    .map_err(|e| env.error(e))?;
+                env.push(stdout);
+                env.push(stderr);
+                env.push(code);
+            }
+            SysOp::RunStream => {
+                let (command, args) = value_to_command(&env.pop(1)?, env)?;
+                let args: Vec<_> = args.iter().map(|s| s.as_str()).collect();
+                let handle = env
+                    .backend
+                    .run_command_stream(&command, &args)
+                    .map_err(|e| env.error(e))?;
+                env.push(handle);
+            }
+            SysOp::RunWait => {
+                let handle = env
+                    .pop(1)?
+                    .as_nat(env, "Handle must be an natural number")?
+                    .into();
+                let code = env.backend.wait_command(handle).map_err(|e| env.error(e))?;
+                env.push(code);
+            }
+            SysOp::Clipboard => {
+                let contents = env.backend.clipboard().map_err(|e| env.error(e))?;
+                env.push(contents);
+            }
+            SysOp::SetClipboard => {
+                let contents = env.pop(1)?.to_string();
+                env.backend
+                    .set_clipboard(&contents)
+                    .map_err(|e| env.error(e))?;
+            }
             SysOp::ChangeDirectory => {
                 let path = env.pop(1)?.as_string(env, "Path must be a string")?;
                 env.backend
@@ -1643,7 +3802,66 @@ impl SysOp {
     }
 }
 
+/// Box each value
+fn boxed_list(values: impl IntoIterator<Item = Value>) -> Value {
+    (values.into_iter())
+        .map(|value| Arc::new(Function::constant(value)))
+        .collect()
+}
+
+/// Get the value in a box, or the value itself if it is not a box
+fn unboxed(value: Value) -> Value {
+    match value.as_function().and_then(|f| f.as_constant()) {
+        Some(inner) => inner.clone(),
+        None => value,
+    }
+}
+
+fn value_to_io_bytes(value: Value, env: &Uiua) -> UiuaResult<Vec<u8>> {
+    Ok(match value {
+        Value::Num(arr) => {
+            let mut bytes = Vec::with_capacity(arr.data.len());
+            for &n in &arr.data {
+                if n.fract() != 0.0 || !(0.0..=255.0).contains(&n) {
+                    return Err(env.error(format!(
+                        "Only whole numbers from 0 to 255 can be written as bytes, \
+                        but the array contains {n}"
+                    )));
+                }
+                bytes.push(n as u8);
+            }
+            bytes
+        }
+        Value::Byte(arr) => arr.data.into(),
+        Value::Char(arr) => arr.data.iter().collect::<String>().into(),
+        Value::Func(_) => return Err(env.error("Cannot write function array to file")),
+    })
+}
+
+fn value_to_nums(value: Value, env: &Uiua, what: &str) -> UiuaResult<Array<f64>> {
+    match value {
+        Value::Num(arr) => Ok(arr),
+        Value::Byte(arr) => Ok(arr.convert()),
+        value => Err(env.error(format!(
+            "{what} must be a number or array of numbers, but it is a {} array",
+            value.type_name()
+        ))),
+    }
+}
+
 fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String>)> {
+    let mut strings = value_to_strings(value, env, "command")?;
+    if strings.is_empty() {
+        return Err(env.error("Command array not be empty"));
+    }
+    let command = strings.remove(0);
+    Ok((command, strings))
+}
+
+/// Interprets a string, a character matrix, or a list of boxed strings as a list of strings
+///
+/// Empty arrays of any type are an empty list.
+fn value_to_strings(value: &Value, env: &Uiua, what: &str) -> UiuaResult<Vec<String>> {
     let mut strings = Vec::new();
     match value {
         Value::Char(arr) => match arr.rank() {
@@ -1655,7 +3873,7 @@ fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String
             }
             n => {
                 return Err(env.error(format!(
-                    "Character array as command must be rank 0, 1, \
+                    "Character array as {what} must be rank 0, 1, \
                     or 2, but its rank is {n}"
                 )))
             }
@@ -1669,45 +3887,74 @@ fn value_to_command(value: &Value, env: &Uiua) -> UiuaResult<(String, Vec<String
                         }
                         Some(val) => {
                             return Err(env.error(format!(
-                                "Function array as command must be all boxed strings, \
+                                "Function array as {what} must be all boxed strings, \
                                 but at least one is a {}",
                                 val.type_name()
                             )))
                         }
                         None => {
-                            return Err(env.error(
-                                "Function array as command must be all boxes, \
+                            return Err(env.error(format!(
+                                "Function array as {what} must be all boxes, \
                                 but at least one is not a box",
-                            ))
+                            )))
                         }
                     }
                 }
             }
             n => {
                 return Err(env.error(format!(
-                    "Function array as command must be rank 0 or 1, \
+                    "Function array as {what} must be rank 0 or 1, \
                     but its rank is {n}"
                 )))
             }
         },
+        Value::Num(_) | Value::Byte(_) if value.row_count() == 0 => {}
         Value::Num(_) | Value::Byte(_) => {
+            let mut what = what.to_string();
+            what[..1].make_ascii_uppercase();
             return Err(env.error(format!(
-                "Command must be a string or function array, but it is {}s",
+                "{what} must be a string or function array, but it is {}s",
                 value.type_name()
-            )))
+            )));
         }
     }
-    if strings.is_empty() {
-        return Err(env.error("Command array not be empty"));
-    }
-    let command = strings.remove(0);
-    Ok((command, strings))
+    Ok(strings)
+}
+
+#[cfg(feature = "image")]
+fn image_format(format: &str) -> Result<ImageOutputFormat, String> {
+    Ok(match format {
+        "jpg" | "jpeg" => ImageOutputFormat::Jpeg(100),
+        "png" => ImageOutputFormat::Png,
+        "bmp" => ImageOutputFormat::Bmp,
+        "gif" => ImageOutputFormat::Gif,
+        "ico" => ImageOutputFormat::Ico,
+        format => return Err(format!("Invalid image format: {}", format)),
+    })
+}
+
+#[cfg(feature = "image")]
+fn image_bytes_to_array(bytes: &[u8]) -> Result<Array<f64>, String> {
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| format!("Failed to read image: {}", e))?
+        .into_rgba8();
+    let shape = tiny_vec![image.height() as usize, image.width() as usize, 4];
+    Ok(Array::new(
+        shape,
+        image
+            .into_raw()
+            .into_iter()
+            .map(|b| b as f64 / 255.0)
+            .collect::<Vec<_>>(),
+    ))
 }
 
+#[cfg(feature = "image")]
 pub fn value_to_image_bytes(value: &Value, format: ImageOutputFormat) -> Result<Vec<u8>, String> {
     image_to_bytes(&value_to_image(value)?, format)
 }
 
+#[cfg(feature = "image")]
 pub fn image_to_bytes(image: &DynamicImage, format: ImageOutputFormat) -> Result<Vec<u8>, String> {
     let mut bytes = Cursor::new(Vec::new());
     image
@@ -1716,6 +3963,7 @@ pub fn image_to_bytes(image: &DynamicImage, format: ImageOutputFormat) -> Result
     Ok(bytes.into_inner())
 }
 
+#[cfg(feature = "image")]
 pub fn value_to_image(value: &Value) -> Result<DynamicImage, String> {
     if ![2, 3].contains(&value.rank()) {
         return Err("Image must be a rank 2 or 3 numeric array".into());
@@ -1880,14 +4128,32 @@ fn value_to_wav_bytes_impl<T: hound::Sample + Copy>(
     Ok(bytes.into_inner())
 }
 
-fn array_from_wav_bytes(bytes: &[u8], env: &Uiua) -> UiuaResult<Array<f64>> {
+fn sample_rate_arg(value: Value, env: &Uiua) -> UiuaResult<u32> {
+    let sample_rate = value.as_nat(env, "Sample rate must be a natural number")?;
+    if sample_rate == 0 {
+        return Err(env.error("Sample rate must be positive"));
+    }
+    u32::try_from(sample_rate)
+        .map_err(|_| env.error(format!("Sample rate {sample_rate} is too large")))
+}
+
+/// Decode wav bytes into samples and a sample rate
+fn array_from_wav_bytes(bytes: &[u8], env: &Uiua) -> UiuaResult<(Array<f64>, u32)> {
     let mut reader: WavReader<Cursor<&[u8]>> =
         WavReader::new(Cursor::new(bytes)).map_err(|e| env.error(e.to_string()))?;
     let spec = reader.spec();
-    match (spec.sample_format, spec.bits_per_sample) {
+    let array = match (spec.sample_format, spec.bits_per_sample) {
+        (SampleFormat::Int, 8) => {
+            array_from_wav_bytes_impl::<i8>(&mut reader, |i| i as f64 / i8::MAX as f64, env)
+        }
         (SampleFormat::Int, 16) => {
             array_from_wav_bytes_impl::<i16>(&mut reader, |i| i as f64 / i16::MAX as f64, env)
         }
+        (SampleFormat::Int, 24) => array_from_wav_bytes_impl::<i32>(
+            &mut reader,
+            |i| i as f64 / ((1 << 23) - 1) as f64,
+            env,
+        ),
         (SampleFormat::Int, 32) => {
             array_from_wav_bytes_impl::<i32>(&mut reader, |i| i as f64 / i32::MAX as f64, env)
         }
@@ -1898,7 +4164,8 @@ fn array_from_wav_bytes(bytes: &[u8], env: &Uiua) -> UiuaResult<Array<f64>> {
             "Unsupported sample format: {:?} {} bits per sample",
             sample_format, bits_per_sample
         ))),
-    }
+    }?;
+    Ok((array, spec.sample_rate))
 }
 
 fn array_from_wav_bytes_impl<T: hound::Sample>(
@@ -1922,6 +4189,7 @@ fn array_from_wav_bytes_impl<T: hound::Sample>(
     }
 }
 
+#[cfg(feature = "image")]
 pub fn value_to_gif_bytes(value: &Value, frame_rate: f64) -> Result<Vec<u8>, String> {
     if value.row_count() == 0 {
         return Err("Cannot convert empty array into GIF".into());
@@ -1986,3 +4254,609 @@ pub fn value_to_gif_bytes(value: &Value, frame_rate: f64) -> Result<Vec<u8>, Str
     drop(encoder);
     Ok(bytes.into_inner())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fmt;
+
+    use super::*;
+    use crate::TestBackend;
+
+    /// A path in the system's temporary directory that is deleted when it is dropped
+    struct TempPath(String);
+
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            let path = env::temp_dir().join(format!("uiua-test-{}-{name}", std::process::id()));
+            TempPath(path.to_string_lossy().replace('\\', "/"))
+        }
+    }
+
+    impl fmt::Display for TempPath {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            self.0.fmt(f)
+        }
+    }
+
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let path = Path::new(&self.0);
+            _ = if path.is_dir() {
+                fs::remove_dir_all(path)
+            } else {
+                fs::remove_file(path)
+            };
+        }
+    }
+
+    #[test]
+    fn file_system_ops() {
+        let dir = TempPath::new("fs");
+        let code = format!(
+            "Dir ← \"{dir}/sub\"\n\
+            File ← $\"_/a.txt\" Dir\n\
+            &fmd Dir\n\
+            &fwa File \"hello\"\n\
+            &fsz File\n\
+            >0 &fmod File\n\
+            ⧻&fld Dir\n\
+            &fde File\n\
+            &fe File\n\
+            &frd Dir\n\
+            &fe Dir"
+        );
+        let stack = Uiua::with_native_sys().run(&code).unwrap();
+        let expected: [Value; 5] = [5.into(), 1.into(), 1.into(), 0.into(), 0.into()];
+        assert_eq!(stack, expected);
+    }
+
+    #[test]
+    fn file_handle_lines() {
+        let path = TempPath::new("lines.txt");
+        let code = format!(
+            "File ← \"{path}\"\n\
+            &fwa File \"ab\\r\\ncd\\nef\"\n\
+            H ← &fo File\n\
+            &rl H\n\
+            &rl H\n\
+            &rl H\n\
+            &rl H\n\
+            &fsk ¯3 H\n\
+            &rl H\n\
+            &fsk 0 H\n\
+            &rl H\n\
+            &cl H"
+        );
+        let stack = Uiua::with_native_sys().run(&code).unwrap();
+        let expected: [Value; 6] = [
+            "ab".into(),
+            "cd".into(),
+            "ef".into(),
+            0u8.into(),
+            "ef".into(),
+            "ab".into(),
+        ];
+        assert_eq!(stack, expected);
+    }
+
+    #[test]
+    fn binary_file_round_trip() {
+        let path = TempPath::new("binary.bin");
+        let code = format!(
+            "File ← \"{path}\"\n\
+            &fwa File ⇡256\n\
+            &fwa File &frab File\n\
+            ≅⇡256 &frab File"
+        );
+        let stack = Uiua::with_native_sys().run(&code).unwrap();
+        assert_eq!(stack, [Value::from(1)]);
+        let invalid = Uiua::with_native_sys().load_str(&format!("&fwa \"{path}\" [1 256]"));
+        assert!(invalid.is_err());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn image_file_round_trip() {
+        let path = TempPath::new("image.png");
+        let code = format!(
+            "File ← \"{path}\"\n\
+            Img ← ◿2 ↯2_3_4 ⇡24\n\
+            &imw File Img\n\
+            ≅Img &imr File\n\
+            &imw File ÷3 ↯2_3 ⇡6\n\
+            △ &imr File"
+        );
+        let stack = Uiua::with_native_sys().run(&code).unwrap();
+        assert_eq!(stack, [Value::from(1), Value::from(vec![2.0, 3.0, 4.0])]);
+        let invalid = TempPath::new("image.txt");
+        let invalid = Uiua::with_native_sys().load_str(&format!("&imw \"{invalid}\" ↯2_2 0"));
+        assert!(invalid.is_err());
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn gif_file() {
+        let path = TempPath::new("frames.gif");
+        let code = format!(
+            "Frames ← ◿2 ↯5_4_3_3 ⇡180\n\
+            &gifw \"{path}\" 10 Frames\n\
+            △ &imr \"{path}\"\n\
+            ≅ -@\\0 \"GIF89a\" ↙6 &frab \"{path}\""
+        );
+        let stack = Uiua::with_native_sys().run(&code).unwrap();
+        assert_eq!(stack, [Value::from(vec![4.0, 3.0, 4.0]), Value::from(1)]);
+    }
+
+    #[test]
+    fn audio_file_round_trip() {
+        let path = TempPath::new("audio.wav");
+        let code = format!(
+            "File ← \"{path}\"\n\
+            Samples ← ×0.5 ○×τ ⊞× 220_330 ÷8000 ⇡800\n\
+            &aw File 8000 Samples\n\
+            △ ∶ <0.001 /↥♭⌵-Samples . ∶ &ar File"
+        );
+        let stack = Uiua::with_native_sys().run(&code).unwrap();
+        let expected: [Value; 3] = [8000.into(), 1.into(), vec![2.0, 800.0].into()];
+        assert_eq!(stack, expected);
+    }
+
+    #[cfg(feature = "file_watch")]
+    #[test]
+    fn watch_files() {
+        let dir = TempPath::new("watch");
+        fs::create_dir_all(&dir.0).unwrap();
+        let file = Path::new(&dir.0).join("a.txt");
+        let mut env = Uiua::with_native_sys();
+        let res = env.load_str(&format!("&fw \"{dir}\" 0.1"));
+        let writer = {
+            let file = file.clone();
+            spawn(move || {
+                sleep(Duration::from_millis(100));
+                fs::write(file, "hi").unwrap();
+            })
+        };
+        let res = res.and_then(|_| env.load_str(&format!("&fw \"{dir}\" 5")));
+        writer.join().unwrap();
+        res.unwrap();
+        assert_eq!(
+            env.take_stack(),
+            [
+                Value::from(Array::<Arc<Function>>::from_iter(Vec::<String>::new())),
+                Value::from(Array::<Arc<Function>>::from_iter(vec![file
+                    .to_string_lossy()
+                    .into_owned()])),
+            ]
+        );
+    }
+
+    #[test]
+    fn temp_files() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str("&tmpf\n&tmpd\n&fwa ⊂∶\"/a.txt\" ∶\"hi\" . &tmpd")
+            .unwrap();
+        let paths: Vec<String> = env
+            .take_stack()
+            .iter()
+            .map(|val| val.as_string(&env, "").unwrap())
+            .collect();
+        assert_eq!(paths.len(), 3);
+        assert!(Path::new(&paths[0]).is_file());
+        assert!(Path::new(&paths[1]).is_dir());
+        assert_eq!(
+            fs::read_to_string(Path::new(&paths[2]).join("a.txt")).unwrap(),
+            "hi"
+        );
+        assert!(paths[0] != paths[1] && paths[1] != paths[2]);
+        NativeSys.clean_up_temp().unwrap();
+        assert!(paths.iter().all(|path| !Path::new(path).exists()));
+    }
+
+    #[test]
+    fn paths() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str(
+            r#"
+    &pj &pj "a" "b" "c.ua"
+    &psp "dir/file.tar.gz"
+    &psp "file"
+    &pn "a/./b/../../../c.txt"
+    &pn "a/.."
+    &pa "x/../y"
+    "#,
+        )
+        .unwrap();
+        let stack: Vec<String> = env
+            .take_stack()
+            .iter()
+            .map(|val| val.as_string(&env, "").unwrap())
+            .collect();
+        let path = |s: &str| Path::new(s).components().collect::<PathBuf>();
+        let absolute = env::current_dir().unwrap().join("y");
+        let expected = [
+            path("a/b/c.ua"),
+            "gz".into(),
+            "file.tar".into(),
+            "dir".into(),
+            "".into(),
+            "file".into(),
+            "".into(),
+            path("../c.txt"),
+            ".".into(),
+            absolute,
+        ]
+        .map(|path| path.to_string_lossy().into_owned());
+        assert_eq!(stack, expected);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str(
+            r#"
+    ↙2 &gzc "hello"
+    ≅ -@\0 "hello" &gzd &gzc "hello"
+    ≅ ⇡256 &gzd &gzc ⇡256
+    <1000 ⧻&gzc ↯10000 "a"
+    "#,
+        )
+        .unwrap();
+        assert_eq!(
+            env.take_stack(),
+            [
+                Value::from(vec![31u8, 139]),
+                Value::from(1),
+                Value::from(1),
+                Value::from(1)
+            ]
+        );
+        assert!(Uiua::with_native_sys().load_str("&gzd [1 2 3]").is_err());
+    }
+
+    #[cfg(feature = "https")]
+    #[test]
+    fn http_request() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 256];
+            while !request.ends_with(b"hello") {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            stream
+                .write_all(
+                    b"HTTP/1.1 201 Created\r\n\
+                    Transfer-Encoding: chunked\r\n\
+                    \r\n\
+                    3\r\n{\"a\r\n4\r\n\":1}\r\n0\r\n\r\n",
+                )
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        let mut env = Uiua::with_native_sys();
+        env.load_str(&format!(
+            "&http \"POST\" \"http://127.0.0.1:{port}/api?x=1\" {{\"X-Test: yes\"}} \"hello\""
+        ))
+        .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /api?x=1 HTTP/1.1\r\n"));
+        assert!(request.contains("X-Test: yes\r\n"));
+        assert!(request.contains("Content-Length: 5\r\n"));
+        let stack = env.take_stack();
+        assert_eq!(stack[0], Value::from("{\"a\":1}"));
+        assert_eq!(stack[2], Value::from(201));
+    }
+
+    #[test]
+    fn tcp_sockets() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str(
+            "L ← &tcpl \"127.0.0.1:0\"\n\
+            C ← &tcpc &tcpaddr L\n\
+            S ← &tcpa L\n\
+            &w \"ping\" C\n\
+            &rb 4 S\n\
+            &tcpsrt 0.2 C\n\
+            &w \"pong\" S\n\
+            &rb 100 C\n\
+            &cl S\n\
+            &cl C\n\
+            &cl L",
+        )
+        .unwrap();
+        let expected: [Value; 2] = [b"ping".to_vec().into(), b"pong".to_vec().into()];
+        assert_eq!(env.take_stack(), expected);
+    }
+
+    #[test]
+    fn udp_sockets() {
+        let port = || {
+            let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+            socket.local_addr().unwrap().port()
+        };
+        let (a, b) = (port(), port());
+        let mut env = Uiua::with_native_sys();
+        env.load_str(&format!(
+            "A ← &udpb \"127.0.0.1:{a}\"\n\
+            B ← &udpb \"127.0.0.1:{b}\"\n\
+            &udpsrt 5 B\n\
+            &udps \"hello\" \"127.0.0.1:{b}\" A\n\
+            &udpr 3 B\n\
+            &cl A\n\
+            &cl B"
+        ))
+        .unwrap();
+        let expected: [Value; 2] = [format!("127.0.0.1:{a}").into(), b"hel".to_vec().into()];
+        assert_eq!(env.take_stack(), expected);
+        for timeout in ["NaN", "1e300"] {
+            let code = format!("&udpsrt {timeout} &udpb \"127.0.0.1:0\"");
+            let err = Uiua::with_native_sys().load_str(&code).unwrap_err();
+            assert!(err.message().contains("Timeout must be"), "{err}");
+        }
+    }
+
+    #[cfg(feature = "https")]
+    #[test]
+    fn websocket() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut byte = [0];
+            while !request.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).unwrap();
+                request.push(byte[0]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n")
+                .unwrap();
+            // Echo the client's masked text frame back unmasked
+            let mut header = [0; 6];
+            stream.read_exact(&mut header).unwrap();
+            assert_eq!(header[0], 0x81);
+            let mut payload = vec![0; (header[1] & 0x7F) as usize];
+            stream.read_exact(&mut payload).unwrap();
+            for (i, b) in payload.iter_mut().enumerate() {
+                *b ^= header[2 + i % 4];
+            }
+            let mut frame = vec![0x81, payload.len() as u8];
+            frame.extend(payload);
+            frame.extend([0x02, 2, 1, 2, 0x80, 1, 3, 0x88, 0]);
+            stream.write_all(&frame).unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        let mut env = Uiua::with_native_sys();
+        env.load_str(&format!(
+            "H ← &wsc \"ws://127.0.0.1:{port}/chat\"\n\
+            &wss \"hello\" H\n\
+            &wsr H\n\
+            &wsr H\n\
+            &wsr H\n\
+            &cl H"
+        ))
+        .unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /chat HTTP/1.1\r\n"));
+        assert!(request.contains("Sec-WebSocket-Key: "));
+        let expected: [Value; 3] = ["hello".into(), vec![1u8, 2, 3].into(), 0u8.into()];
+        assert_eq!(env.take_stack(), expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn subprocesses() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str(
+            "&runp \"cat\" \"hello\"\n\
+            &runp {\"sh\" \"-c\" \"echo oops >&2; exit 3\"} \"\"\n\
+            H ← &runs \"cat\"\n\
+            &w \"a\\nb\\n\" H\n\
+            &rl H\n\
+            &rl H\n\
+            &runw H",
+        )
+        .unwrap();
+        let expected: [Value; 9] = [
+            "hello".into(),
+            "".into(),
+            0.into(),
+            "".into(),
+            "oops\n".into(),
+            3.into(),
+            "a".into(),
+            "b".into(),
+            0.into(),
+        ];
+        assert_eq!(env.take_stack(), expected);
+    }
+
+    #[test]
+    fn script_arguments() {
+        let mut env = Uiua::with_native_sys()
+            .with_file_path("script.ua")
+            .with_args(vec!["-n".into(), "5".into()]);
+        env.load_str("&args").unwrap();
+        let args = ["script.ua", "-n", "5"].map(String::from);
+        let expected = Value::from(args.into_iter().collect::<Array<Arc<Function>>>());
+        assert_eq!(env.take_stack(), [expected]);
+    }
+
+    #[test]
+    fn exit_code() {
+        let mut env = Uiua::with_native_sys();
+        let err = env.load_str("1\n⍣(&exit 3)(0)\n2").unwrap_err();
+        assert_eq!(err.exit_code(), Some(3));
+        assert_eq!(env.take_stack().len(), 1);
+        let err = Uiua::with_native_sys().load_str("&exit 1e10").unwrap_err();
+        assert_eq!(err.exit_code(), None);
+    }
+
+    #[test]
+    fn stderr_output() {
+        let mut env = Uiua::with_backend(TestBackend::default()).print_diagnostics(true);
+        env.load_str(
+            r#"
+    &lbuf 1
+    &p "data"
+    &ep "oops"
+    ;;;:,1 2
+    &flush
+    "#,
+        )
+        .unwrap();
+        let backend = env.downcast_backend::<TestBackend>().unwrap();
+        assert_eq!(backend.stdout(), "data\n");
+        let stderr = backend.stderr();
+        assert!(stderr.starts_with("oops\n"), "{stderr}");
+        assert!(stderr.contains("Prefer"), "{stderr}");
+    }
+
+    #[test]
+    fn backend_capabilities() {
+        let backend = Arc::new(TestBackend::default().with_supported([SysOp::Prin, SysOp::Print]));
+        let supported: Vec<SysOp> = SysOp::ALL
+            .into_iter()
+            .filter(|&op| backend.supports(op))
+            .collect();
+        assert_eq!(supported, [SysOp::Prin, SysOp::Print]);
+        for code in ["&p \"a\"", "&pf \"b\""] {
+            Uiua::with_shared_backend(backend.clone())
+                .load_str(code)
+                .unwrap();
+        }
+        assert_eq!(backend.stdout(), "a\nb");
+        let err = Uiua::with_shared_backend(backend.clone())
+            .load_str("&s 1")
+            .unwrap_err();
+        assert!(
+            err.message()
+                .contains("&s is not supported by this backend"),
+            "{err}"
+        );
+        assert_eq!(backend.stdout(), "a\nb");
+    }
+
+    #[test]
+    fn text_styles() {
+        let style = TextStyle::parse("bold bright_red on_blue").unwrap();
+        assert_eq!(style.color.as_deref(), Some("bright_red"));
+        assert_eq!(style.background.as_deref(), Some("blue"));
+        assert!(style.bold && !style.italic);
+        colored::control::set_override(true);
+        assert_eq!(style.apply("x"), "\x1b[1;44;91mx\x1b[0m");
+        colored::control::unset_override();
+        assert!(TextStyle::parse("on_bold").is_err());
+        assert!(Uiua::with_native_sys()
+            .load_str("&ps \"sparkly\" 1")
+            .is_err());
+    }
+
+    #[test]
+    fn clipboard() {
+        let mut env = Uiua::with_backend(TestBackend::default().with_clipboard("x"));
+        env.load_str("&clip\n&setclip \"a,b\"\n⊜□≠@,.&clip")
+            .unwrap();
+        let expected = Value::from(
+            ["a", "b"]
+                .map(String::from)
+                .into_iter()
+                .collect::<Array<_>>(),
+        );
+        assert_eq!(env.take_stack(), [Value::from("x"), expected]);
+    }
+
+    #[test]
+    fn play_audio_at_sample_rate() {
+        let mut env = Uiua::with_backend(TestBackend::default());
+        env.load_str("&apr 8000 ÷4○×τ×220 ÷8000 ⇡8000\n&ap ↯100 0")
+            .unwrap();
+        let played = env
+            .downcast_backend::<TestBackend>()
+            .unwrap()
+            .played_audio();
+        let specs: Vec<_> = played
+            .iter()
+            .map(|bytes| {
+                let reader = hound::WavReader::new(bytes.as_slice()).unwrap();
+                (reader.spec().sample_rate, reader.duration())
+            })
+            .collect();
+        assert_eq!(specs, [(8000, 8000), (44100, 100)]);
+        assert!(Uiua::with_backend(TestBackend::default())
+            .load_str("&apr 0 [0 0]")
+            .is_err());
+    }
+
+    #[test]
+    fn monotonic_clock() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str("⍜&mono(&sl 0.01)").unwrap();
+        let elapsed = env.pop(1).unwrap();
+        let elapsed = elapsed.as_num(&env, "").unwrap();
+        assert!((0.01..1.0).contains(&elapsed), "{elapsed}");
+    }
+
+    #[test]
+    fn environment_variables() {
+        let mut env = Uiua::with_backend(TestBackend::default());
+        let stack = env
+            .run(
+                "&setvar \"B\" \"2\"\n\
+                &setvar \"A\" \"1\"\n\
+                &var \"A\"\n\
+                &var \"C\"\n\
+                &vars",
+            )
+            .unwrap();
+        let strings =
+            |s: [&str; 2]| Value::from(s.map(String::from).into_iter().collect::<Array<_>>());
+        let expected: [Value; 4] = [
+            "1".into(),
+            "".into(),
+            strings(["1", "2"]),
+            strings(["A", "B"]),
+        ];
+        assert_eq!(stack, expected);
+    }
+
+    #[test]
+    fn scripted_stdin() {
+        let mut env =
+            Uiua::with_backend(TestBackend::default().with_stdin("one\ntwo\nthree\nfour"));
+        let stack = env.run("&sc\n&rs 4 0\n&eof\n&sca\n&eof\n&sc").unwrap();
+        let expected: [Value; 6] = [
+            "one".into(),
+            "two\n".into(),
+            0.into(),
+            "three\nfour".into(),
+            1.into(),
+            0u8.into(),
+        ];
+        assert_eq!(stack, expected);
+    }
+
+    #[test]
+    fn key_presses_without_terminal() {
+        for code in ["&raw 1", "&key"] {
+            let err = Uiua::with_backend(TestBackend::default())
+                .load_str(code)
+                .unwrap_err();
+            assert!(err.message().contains("not supported"), "{err}");
+        }
+    }
+
+    #[test]
+    fn fast_forward_sleep() {
+        let mut env = Uiua::with_backend(TestBackend::default());
+        let stack = env
+            .run("⍜now(&sl 1000.5)\n⍜&mono(&sl 2)\n&sl ∞\nnow")
+            .unwrap();
+        let expected: [Value; 3] = [1000.5.into(), 2.into(), f64::INFINITY.into()];
+        assert_eq!(stack, expected);
+    }
+}
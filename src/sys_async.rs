@@ -0,0 +1,264 @@
+//! An async version of [`SysBackend`]
+//!
+//! The interpreter itself is synchronous, so an [`AsyncSysBackend`] is run by wrapping it
+//! in a [`BlockingBackend`], which polls each future to completion on the calling thread.
+//! This is only a blocking bridge. There is no async execution path yet, so the thread
+//! running the interpreter is parked while a future is pending. Don't run it on an async
+//! runtime's worker thread, and don't use it where threads cannot block, like on the web.
+
+use std::{
+    any::Any,
+    future::{ready, Future},
+    pin::{pin, Pin},
+    sync::Arc,
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+};
+
+use crate::{Handle, SysBackend, SysOp};
+
+/// A boxed future returned by [`AsyncSysBackend`] methods
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+fn unsupported<'a, T: Send + 'a>(what: &str) -> BoxFuture<'a, Result<T, String>> {
+    Box::pin(ready(Err(format!(
+        "{what} is not supported in this environment"
+    ))))
+}
+
+/// A system backend whose IO operations return futures
+///
+/// Like [`SysBackend`], every method has a default implementation that returns an error.
+#[allow(unused_variables)]
+pub trait AsyncSysBackend: Any + Send + Sync + 'static {
+    fn any(&self) -> &dyn Any;
+    /// Whether this backend supports a system function
+    ///
+    /// No system functions are supported by default, because every method's default
+    /// implementation returns an error. Backends should list the ones they implement.
+    fn supports(&self, op: SysOp) -> bool {
+        false
+    }
+    fn print_str_stdout<'a>(&'a self, s: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        unsupported("Printing to stdout")
+    }
+    fn print_str_stderr<'a>(&'a self, s: &'a str) -> BoxFuture<'a, Result<(), String>> {
+        unsupported("Printing to stderr")
+    }
+    fn flush(&self) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(ready(Ok(())))
+    }
+    /// Read a line from stdin
+    ///
+    /// Should resolve to `Ok(None)` if EOF is reached.
+    fn scan_line_stdin(&self) -> BoxFuture<'_, Result<Option<String>, String>> {
+        unsupported("Reading from stdin")
+    }
+    /// Read bytes from stdin
+    ///
+    /// If `count` is `None`, read until EOF.
+    fn read_stdin(&self, count: Option<usize>) -> BoxFuture<'_, Result<Vec<u8>, String>> {
+        unsupported("Reading from stdin")
+    }
+    fn file_exists<'a>(&'a self, path: &'a str) -> BoxFuture<'a, bool> {
+        Box::pin(ready(false))
+    }
+    fn list_dir<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Vec<String>, String>> {
+        unsupported("Listing directories")
+    }
+    fn file_read_all<'a>(&'a self, path: &'a str) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+        unsupported("Reading files")
+    }
+    fn file_write_all<'a>(
+        &'a self,
+        path: &'a str,
+        contents: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), String>> {
+        unsupported("Writing files")
+    }
+    fn read(&self, handle: Handle, count: usize) -> BoxFuture<'_, Result<Vec<u8>, String>> {
+        unsupported("Reading from streams")
+    }
+    fn write<'a>(
+        &'a self,
+        handle: Handle,
+        contents: &'a [u8],
+    ) -> BoxFuture<'a, Result<(), String>> {
+        unsupported("Writing to streams")
+    }
+    fn close(&self, handle: Handle) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(ready(Ok(())))
+    }
+    fn sleep(&self, seconds: f64) -> BoxFuture<'_, Result<(), String>> {
+        unsupported("Sleeping")
+    }
+    fn https_get<'a>(
+        &'a self,
+        request: &'a str,
+        handle: Handle,
+    ) -> BoxFuture<'a, Result<String, String>> {
+        unsupported("Making HTTPS requests")
+    }
+}
+
+/// A [`SysBackend`] that runs an [`AsyncSysBackend`] by blocking on its futures
+///
+/// Operations that the async trait does not have use the default [`SysBackend`] implementations.
+pub struct BlockingBackend<B>(pub B);
+
+impl<B: AsyncSysBackend> BlockingBackend<B> {
+    /// Get the async backend
+    pub fn inner(&self) -> &B {
+        &self.0
+    }
+}
+
+/// Poll a future to completion, parking the thread while it is pending
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+impl<B: AsyncSysBackend> SysBackend for BlockingBackend<B> {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn supports(&self, op: SysOp) -> bool {
+        self.0.supports(op)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        block_on(self.0.print_str_stdout(s))
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        block_on(self.0.print_str_stderr(s))
+    }
+    fn flush(&self) -> Result<(), String> {
+        block_on(self.0.flush())
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        block_on(self.0.scan_line_stdin())
+    }
+    fn read_stdin(&self, count: Option<usize>) -> Result<Vec<u8>, String> {
+        block_on(self.0.read_stdin(count))
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        block_on(self.0.file_exists(path))
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        block_on(self.0.list_dir(path))
+    }
+    fn file_read_all(&self, path: &str) -> Result<Vec<u8>, String> {
+        block_on(self.0.file_read_all(path))
+    }
+    fn file_write_all(&self, path: &str, contents: &[u8]) -> Result<(), String> {
+        block_on(self.0.file_write_all(path, contents))
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        block_on(self.0.read(handle, count))
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        block_on(self.0.write(handle, contents))
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        block_on(self.0.close(handle))
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        block_on(self.0.sleep(seconds))
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        block_on(self.0.https_get(request, handle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{value::Value, Uiua};
+
+    #[test]
+    fn async_backend() {
+        use std::{
+            any::Any,
+            collections::HashMap,
+            future::Future,
+            pin::Pin,
+            sync::Mutex,
+            task::{Context, Poll},
+        };
+        /// A future that is pending once before it is ready
+        struct Yield<T>(Option<T>, bool);
+        impl<T: Unpin> Future for Yield<T> {
+            type Output = T;
+            fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<T> {
+                if self.1 {
+                    Poll::Ready(self.0.take().unwrap())
+                } else {
+                    self.1 = true;
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+        #[derive(Default)]
+        struct AsyncFiles(Mutex<HashMap<String, Vec<u8>>>);
+        impl AsyncSysBackend for AsyncFiles {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+            fn supports(&self, op: SysOp) -> bool {
+                matches!(op, SysOp::FReadAllStr | SysOp::FWriteAll)
+            }
+            fn file_read_all<'a>(
+                &'a self,
+                path: &'a str,
+            ) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+                let contents = (self.0.lock().unwrap().get(path).cloned())
+                    .ok_or_else(|| format!("{path} not found"));
+                Box::pin(Yield(Some(contents), false))
+            }
+            fn file_write_all<'a>(
+                &'a self,
+                path: &'a str,
+                contents: &'a [u8],
+            ) -> BoxFuture<'a, Result<(), String>> {
+                Box::pin(async move {
+                    Yield(Some(()), false).await;
+                    (self.0.lock().unwrap()).insert(path.into(), contents.into());
+                    Ok(())
+                })
+            }
+        }
+        let mut env = Uiua::with_async_backend(AsyncFiles::default());
+        env.load_str("&fwa \"a.txt\" \"hello\"\n&fras \"a.txt\"")
+            .unwrap();
+        assert_eq!(env.take_stack(), [Value::from("hello")]);
+        let files = env.downcast_async_backend::<AsyncFiles>().unwrap();
+        assert_eq!(files.0.lock().unwrap()["a.txt"], b"hello");
+        let err = env.load_str(r#"&fras "b.txt""#).unwrap_err();
+        assert!(err.message().contains("b.txt not found"), "{err}");
+        let err = env.load_str("&sl 0").unwrap_err();
+        assert!(err.message().contains("not supported"), "{err}");
+
+        struct Nothing;
+        impl AsyncSysBackend for Nothing {
+            fn any(&self) -> &dyn Any {
+                self
+            }
+        }
+        let backend = BlockingBackend(Nothing);
+        assert!(SysOp::ALL.iter().all(|&op| !backend.supports(op)));
+    }
+}
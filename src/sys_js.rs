@@ -0,0 +1,155 @@
+//! A system backend for browsers that sends IO to JavaScript callbacks
+
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use js_sys::Function;
+use wasm_bindgen::JsValue;
+
+use crate::{value::Value, Handle, SysBackend, Uiua, UiuaError, UiuaResult};
+
+/// A JavaScript function
+///
+/// JavaScript values can only be used on the thread that created them.
+/// `wasm32-unknown-unknown` only has one thread, so it is fine for the backend to hold them.
+struct Callback(Function);
+
+unsafe impl Send for Callback {}
+unsafe impl Sync for Callback {}
+
+impl Callback {
+    fn call(&self, arg: Option<&str>) -> Result<JsValue, String> {
+        match arg {
+            Some(arg) => self.0.call1(&JsValue::NULL, &JsValue::from_str(arg)),
+            None => self.0.call0(&JsValue::NULL),
+        }
+        .map_err(|e| e.as_string().unwrap_or_else(|| format!("{e:?}")))
+    }
+}
+
+/// A [`SysBackend`] that prints and reads with JavaScript callbacks
+///
+/// It does not use the file system, the network, or OS threads, so it works on `wasm32-unknown-unknown`.
+/// Operations without a callback, like file and network IO, return an error.
+#[derive(Default)]
+pub struct JsBackend {
+    stdout: Option<Callback>,
+    stderr: Option<Callback>,
+    stdin: Option<Callback>,
+    stdin_buffer: Mutex<VecDeque<u8>>,
+    next_thread_id: AtomicU64,
+    thread_results: Mutex<HashMap<Handle, UiuaResult<Vec<Value>>>>,
+}
+
+impl JsBackend {
+    /// Set the function called with each string printed to stdout
+    pub fn with_stdout(mut self, f: Function) -> Self {
+        self.stdout = Some(Callback(f));
+        self
+    }
+    /// Set the function called with each string printed to stderr or traced
+    pub fn with_stderr(mut self, f: Function) -> Self {
+        self.stderr = Some(Callback(f));
+        self
+    }
+    /// Set the function called to read a line from stdin
+    ///
+    /// It should return a string without the trailing newline, or `null` at the end of input.
+    pub fn with_stdin(mut self, f: Function) -> Self {
+        self.stdin = Some(Callback(f));
+        self
+    }
+    fn next_line(&self) -> Result<Option<String>, String> {
+        let stdin = (self.stdin.as_ref())
+            .ok_or("Reading from stdin is not supported in this environment")?;
+        let line = stdin.call(None)?;
+        if line.is_null() || line.is_undefined() {
+            return Ok(None);
+        }
+        line.as_string()
+            .map(Some)
+            .ok_or_else(|| "The stdin callback must return a string or null".into())
+    }
+}
+
+impl SysBackend for JsBackend {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        let stdout = (self.stdout.as_ref())
+            .ok_or("Printing to stdout is not supported in this environment")?;
+        stdout.call(Some(s)).map(drop)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        let stderr = (self.stderr.as_ref())
+            .ok_or("Printing to stderr is not supported in this environment")?;
+        stderr.call(Some(s)).map(drop)
+    }
+    fn print_str_trace(&self, s: &str) {
+        _ = self.print_str_stderr(s);
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        let mut buffer = self.stdin_buffer.lock().unwrap();
+        if buffer.is_empty() {
+            return self.next_line();
+        }
+        let len = (buffer.iter().position(|&b| b == b'\n')).map_or(buffer.len(), |i| i + 1);
+        let mut line: Vec<u8> = buffer.drain(..len).collect();
+        if line.last() == Some(&b'\n') {
+            line.pop();
+        } else if let Some(rest) = self.next_line()? {
+            line.extend(rest.into_bytes());
+        }
+        Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+    }
+    fn read_stdin(&self, count: Option<usize>) -> Result<Vec<u8>, String> {
+        let mut buffer = self.stdin_buffer.lock().unwrap();
+        while count.is_none_or(|count| buffer.len() < count) {
+            let Some(line) = self.next_line()? else {
+                break;
+            };
+            buffer.extend(line.into_bytes());
+            buffer.push_back(b'\n');
+        }
+        let len = count.unwrap_or(buffer.len()).min(buffer.len());
+        Ok(buffer.drain(..len).collect())
+    }
+    fn stdin_eof(&self) -> Result<bool, String> {
+        let mut buffer = self.stdin_buffer.lock().unwrap();
+        if !buffer.is_empty() {
+            return Ok(false);
+        }
+        Ok(match self.next_line()? {
+            Some(line) => {
+                buffer.extend(line.into_bytes());
+                buffer.push_back(b'\n');
+                false
+            }
+            None => true,
+        })
+    }
+    fn spawn(
+        &self,
+        mut env: Uiua,
+        f: Box<dyn FnOnce(&mut Uiua) -> UiuaResult + Send>,
+    ) -> Result<Handle, String> {
+        let handle = Handle(self.next_thread_id.fetch_add(1, Ordering::SeqCst));
+        let res = f(&mut env).map(|_| env.take_stack());
+        self.thread_results.lock().unwrap().insert(handle, res);
+        Ok(handle)
+    }
+    fn wait(&self, handle: Handle) -> Result<Vec<Value>, Result<UiuaError, String>> {
+        match self.thread_results.lock().unwrap().remove(&handle) {
+            Some(Ok(stack)) => Ok(stack),
+            Some(Err(err)) => Err(Ok(err)),
+            None => Err(Err("Invalid thread handle".into())),
+        }
+    }
+}
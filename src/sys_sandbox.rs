@@ -0,0 +1,435 @@
+//! A system backend that limits what another backend may do
+
+use std::{any::Any, collections::HashSet, io::SeekFrom, time::Duration};
+
+#[cfg(feature = "image")]
+use image::DynamicImage;
+
+use crate::{
+    sys::AudioStreamFn, value::Value, Handle, HttpResponse, SysBackend, SysOp, TextStyle, Uiua,
+    UiuaError, UiuaResult, WebSocketMessage,
+};
+
+/// A group of system functions that a [`SandboxBackend`] can allow
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    /// Reading files and directories
+    FsRead,
+    /// Creating, changing, and deleting files and directories
+    FsWrite,
+    /// TCP, UDP, WebSockets, and HTTP
+    Network,
+    /// Running commands
+    Subprocess,
+    /// Environment variables
+    Env,
+    /// Reading and setting the clipboard
+    Clipboard,
+    /// Showing images and GIFs and playing audio
+    ///
+    /// Outside of a terminal, showing an image opens it in another program.
+    Media,
+}
+
+impl Capability {
+    pub const ALL: [Self; 7] = [
+        Capability::FsRead,
+        Capability::FsWrite,
+        Capability::Network,
+        Capability::Subprocess,
+        Capability::Env,
+        Capability::Clipboard,
+        Capability::Media,
+    ];
+}
+
+impl SysOp {
+    /// The capability a [`SandboxBackend`] must allow for this system function to run
+    ///
+    /// Returns `None` for system functions that are always allowed.
+    /// Reading and writing handles is always allowed, because handles can only come from allowed system functions.
+    pub fn capability(&self) -> Option<Capability> {
+        use SysOp::*;
+        Some(match self {
+            FOpen | FExists | FListDir | FIsFile | FSize | FModified | FWatch | FReadAllStr
+            | FReadAllBytes | Import | ImRead | AudioRead | PathAbsolute | ChangeDirectory => {
+                Capability::FsRead
+            }
+            FCreate | FMakeDir | FRemoveDir | FDelete | TempFile | TempDir | FWriteAll
+            | ImWrite | GifWrite | AudioWrite => Capability::FsWrite,
+            TcpListen | TcpAccept | TcpConnect | TcpSetNonBlocking | TcpSetReadTimeout
+            | TcpSetWriteTimeout | TcpAddr | UdpBind | UdpSend | UdpReceive | UdpSetReadTimeout
+            | WsConnect | WsSend | WsReceive | HttpsWrite | HttpRequest => Capability::Network,
+            RunInherit | RunCapture | RunPiped | RunStream | RunWait => Capability::Subprocess,
+            Var | SetVar | Vars => Capability::Env,
+            Clipboard | SetClipboard => Capability::Clipboard,
+            ImShow | GifShow | AudioPlay | AudioPlayRate | AudioStream => Capability::Media,
+            _ => return None,
+        })
+    }
+}
+
+/// A [`SysBackend`] that only lets another backend run system functions whose [`Capability`] is allowed
+///
+/// Other system functions fail with a catchable error before reaching the inner backend.
+/// No capabilities are allowed by default.
+pub struct SandboxBackend<B> {
+    inner: B,
+    allowed: HashSet<Capability>,
+}
+
+impl<B: SysBackend> SandboxBackend<B> {
+    /// Wrap a backend without allowing any capabilities
+    pub fn new(inner: B) -> Self {
+        SandboxBackend {
+            inner,
+            allowed: HashSet::new(),
+        }
+    }
+    /// Allow a capability
+    pub fn allow(mut self, capability: Capability) -> Self {
+        self.allowed.insert(capability);
+        self
+    }
+    /// Disallow a capability
+    pub fn deny(mut self, capability: Capability) -> Self {
+        self.allowed.remove(&capability);
+        self
+    }
+    /// Whether a capability is allowed
+    pub fn allows(&self, capability: Capability) -> bool {
+        self.allowed.contains(&capability)
+    }
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+impl<B: SysBackend> SysBackend for SandboxBackend<B> {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn supports(&self, op: SysOp) -> bool {
+        op.capability().is_none_or(|cap| self.allows(cap)) && self.inner.supports(op)
+    }
+    fn save_error_color(&self, error: &UiuaError) {
+        self.inner.save_error_color(error)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stdout(s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stderr(s)
+    }
+    fn print_styled_stdout(&self, s: &str, style: &TextStyle) -> Result<(), String> {
+        self.inner.print_styled_stdout(s, style)
+    }
+    fn stdout_is_terminal(&self) -> bool {
+        self.inner.stdout_is_terminal()
+    }
+    fn stderr_is_terminal(&self) -> bool {
+        self.inner.stderr_is_terminal()
+    }
+    fn print_str_trace(&self, s: &str) {
+        self.inner.print_str_trace(s)
+    }
+    fn flush(&self) -> Result<(), String> {
+        self.inner.flush()
+    }
+    fn set_line_buffered(&self, line_buffered: bool) -> Result<(), String> {
+        self.inner.set_line_buffered(line_buffered)
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        self.inner.scan_line_stdin()
+    }
+    fn read_stdin(&self, count: Option<usize>) -> Result<Vec<u8>, String> {
+        self.inner.read_stdin(count)
+    }
+    fn stdin_eof(&self) -> Result<bool, String> {
+        self.inner.stdin_eof()
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.inner.var(name)
+    }
+    fn set_var(&self, name: &str, value: &str) -> Result<(), String> {
+        self.inner.set_var(name, value)
+    }
+    fn vars(&self) -> Vec<(String, String)> {
+        self.inner.vars()
+    }
+    fn term_size(&self) -> Result<(usize, usize), String> {
+        self.inner.term_size()
+    }
+    fn set_raw_mode(&self, raw: bool) -> Result<(), String> {
+        self.inner.set_raw_mode(raw)
+    }
+    fn poll_key(&self) -> Result<Option<String>, String> {
+        self.inner.poll_key()
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        self.inner.file_exists(path)
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.inner.list_dir(path)
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        self.inner.is_file(path)
+    }
+    fn file_size(&self, path: &str) -> Result<u64, String> {
+        self.inner.file_size(path)
+    }
+    fn file_modified(&self, path: &str) -> Result<f64, String> {
+        self.inner.file_modified(path)
+    }
+    fn watch_path(&self, path: &str, timeout: Option<f64>) -> Result<Vec<String>, String> {
+        self.inner.watch_path(path, timeout)
+    }
+    fn make_dir(&self, path: &str) -> Result<(), String> {
+        self.inner.make_dir(path)
+    }
+    fn remove_dir(&self, path: &str) -> Result<(), String> {
+        self.inner.remove_dir(path)
+    }
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        self.inner.delete_file(path)
+    }
+    fn create_temp_file(&self) -> Result<String, String> {
+        self.inner.create_temp_file()
+    }
+    fn create_temp_dir(&self) -> Result<String, String> {
+        self.inner.create_temp_dir()
+    }
+    fn current_dir(&self) -> Result<String, String> {
+        self.inner.current_dir()
+    }
+    fn clean_up_temp(&self) -> Result<(), String> {
+        self.inner.clean_up_temp()
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        self.inner.read(handle, count)
+    }
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String> {
+        self.inner.read_until(handle, delim)
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        self.inner.write(handle, contents)
+    }
+    fn seek(&self, handle: Handle, pos: SeekFrom) -> Result<(), String> {
+        self.inner.seek(handle, pos)
+    }
+    fn create_file(&self, path: &str) -> Result<Handle, String> {
+        self.inner.create_file(path)
+    }
+    fn open_file(&self, path: &str) -> Result<Handle, String> {
+        self.inner.open_file(path)
+    }
+    fn file_read_all(&self, path: &str) -> Result<Vec<u8>, String> {
+        self.inner.file_read_all(path)
+    }
+    fn file_write_all(&self, path: &str, contents: &[u8]) -> Result<(), String> {
+        self.inner.file_write_all(path, contents)
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        self.inner.sleep(seconds)
+    }
+    fn now(&self) -> f64 {
+        self.inner.now()
+    }
+    fn monotonic_time(&self) -> f64 {
+        self.inner.monotonic_time()
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: DynamicImage) -> Result<(), String> {
+        self.inner.show_image(image)
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.show_gif(gif_bytes)
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.play_audio(wave_bytes)
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        self.inner.audio_sample_rate()
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        self.inner.stream_audio(f)
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.tcp_listen(addr)
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        self.inner.tcp_accept(handle)
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.tcp_connect(addr)
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        self.inner.tcp_addr(handle)
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        self.inner.tcp_set_non_blocking(handle, non_blocking)
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_read_timeout(handle, timeout)
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_write_timeout(handle, timeout)
+    }
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.udp_bind(addr)
+    }
+    fn udp_send(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        self.inner.udp_send(handle, data, addr)
+    }
+    fn udp_receive(&self, handle: Handle, max: usize) -> Result<(Vec<u8>, String), String> {
+        self.inner.udp_receive(handle, max)
+    }
+    fn udp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.udp_set_read_timeout(handle, timeout)
+    }
+    fn ws_connect(&self, url: &str) -> Result<Handle, String> {
+        self.inner.ws_connect(url)
+    }
+    fn ws_send(&self, handle: Handle, message: WebSocketMessage) -> Result<(), String> {
+        self.inner.ws_send(handle, message)
+    }
+    fn ws_receive(&self, handle: Handle) -> Result<Option<WebSocketMessage>, String> {
+        self.inner.ws_receive(handle)
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        self.inner.close(handle)
+    }
+    fn spawn(
+        &self,
+        env: Uiua,
+        f: Box<dyn FnOnce(&mut Uiua) -> UiuaResult + Send>,
+    ) -> Result<Handle, String> {
+        self.inner.spawn(env, f)
+    }
+    fn wait(&self, handle: Handle) -> Result<Vec<Value>, Result<UiuaError, String>> {
+        self.inner.wait(handle)
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<(), String> {
+        self.inner.run_command_inherit(command, args)
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(String, String), String> {
+        self.inner.run_command_capture(command, args)
+    }
+    fn run_command_piped(
+        &self,
+        command: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> Result<(String, String, i32), String> {
+        self.inner.run_command_piped(command, args, input)
+    }
+    fn run_command_stream(&self, command: &str, args: &[&str]) -> Result<Handle, String> {
+        self.inner.run_command_stream(command, args)
+    }
+    fn wait_command(&self, handle: Handle) -> Result<i32, String> {
+        self.inner.wait_command(handle)
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        self.inner.change_directory(path)
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        self.inner.clipboard()
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        self.inner.set_clipboard(contents)
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        self.inner.https_get(request, handle)
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[String],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        self.inner.http_request(method, url, headers, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestBackend;
+
+    #[test]
+    fn sandbox_backend() {
+        let backend = TestBackend::default()
+            .with_file("a.txt", "a")
+            .with_stdin_lines(["line"]);
+        let sandbox = SandboxBackend::new(backend)
+            .allow(Capability::FsRead)
+            .allow(Capability::Env)
+            .deny(Capability::Env);
+        assert!(sandbox.allows(Capability::FsRead));
+        assert!(!sandbox.allows(Capability::Env));
+        let mut env = Uiua::with_backend(sandbox);
+        env.load_str("&fras \"a.txt\"\n&sc\n⍣(&fwa \"b.txt\" \"b\")(∘)")
+            .unwrap();
+        assert_eq!(
+            env.take_stack(),
+            [
+                Value::from("a"),
+                Value::from("line"),
+                Value::from("3:3: &fwa is not supported by this backend")
+            ]
+        );
+        for code in ["&var \"HOME\"", "&runc \"ls\"", "&tcpc \"localhost:80\""] {
+            let err = env.load_str(code).unwrap_err();
+            assert!(
+                err.message().contains("not supported by this backend"),
+                "{err}"
+            );
+        }
+        let sandbox = env
+            .downcast_backend::<SandboxBackend<TestBackend>>()
+            .unwrap();
+        assert_eq!(sandbox.inner().file("b.txt"), None);
+        assert_eq!(SysOp::Import.capability(), Some(Capability::FsRead));
+        assert_eq!(SysOp::Print.capability(), None);
+    }
+
+    #[test]
+    fn media_and_clipboard_denied() {
+        let mut env = Uiua::with_backend(SandboxBackend::new(TestBackend::default()));
+        for code in [
+            "&clip",
+            "&setclip \"a\"",
+            "&ims [0_1 1_0]",
+            "&gifs 10 [[0_1] [1_0]]",
+            "&ap [0 0.5 0]",
+            "&apr 44100 [0 0.5 0]",
+        ] {
+            let err = env.load_str(code).unwrap_err();
+            assert!(
+                err.message().contains("not supported by this backend"),
+                "{code}: {err}"
+            );
+        }
+        let sandbox = SandboxBackend::new(TestBackend::default())
+            .allow(Capability::Clipboard)
+            .allow(Capability::Media);
+        assert!(sandbox.supports(SysOp::Clipboard) && sandbox.supports(SysOp::AudioPlay));
+    }
+}
@@ -0,0 +1,321 @@
+//! A scripted system backend for testing programs that do IO
+
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet, VecDeque},
+    io::SeekFrom,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{value::Value, Handle, SysBackend, SysOp, Uiua, UiuaError, UiuaResult, VirtualFs};
+
+/// A [`SysBackend`] with scripted stdin, captured output, an in-memory file system, and a virtual clock
+///
+/// The file system is a [`VirtualFs`].
+/// [`SysBackend::sleep`] returns immediately and advances the clock.
+/// The clipboard is a string, and played audio is recorded instead of played.
+pub struct TestBackend {
+    stdin: Mutex<VecDeque<u8>>,
+    stdout: Mutex<String>,
+    stderr: Mutex<String>,
+    fs: VirtualFs,
+    vars: Mutex<HashMap<String, String>>,
+    time: Mutex<f64>,
+    next_handle: AtomicU64,
+    thread_results: Mutex<HashMap<Handle, UiuaResult<Vec<Value>>>>,
+    clipboard: Mutex<String>,
+    audio: Mutex<Vec<Vec<u8>>>,
+    supported: Option<HashSet<SysOp>>,
+}
+
+impl Default for TestBackend {
+    fn default() -> Self {
+        Self {
+            stdin: VecDeque::new().into(),
+            stdout: String::new().into(),
+            stderr: String::new().into(),
+            fs: VirtualFs::default(),
+            vars: HashMap::new().into(),
+            time: 0.0.into(),
+            next_handle: Handle::FIRST_UNRESERVED.0.into(),
+            thread_results: HashMap::new().into(),
+            clipboard: String::new().into(),
+            audio: Vec::new().into(),
+            supported: None,
+        }
+    }
+}
+
+impl TestBackend {
+    /// Add bytes to the end of stdin
+    pub fn with_stdin(self, stdin: impl AsRef<[u8]>) -> Self {
+        self.stdin.lock().unwrap().extend(stdin.as_ref());
+        self
+    }
+    /// Add lines to the end of stdin
+    pub fn with_stdin_lines<S: AsRef<str>>(self, lines: impl IntoIterator<Item = S>) -> Self {
+        for line in lines {
+            let mut stdin = self.stdin.lock().unwrap();
+            stdin.extend(line.as_ref().as_bytes());
+            stdin.push_back(b'\n');
+        }
+        self
+    }
+    /// Add a file to the file system
+    ///
+    /// Its parent directories are created too.
+    pub fn with_file(mut self, path: &str, contents: impl AsRef<[u8]>) -> Self {
+        self.fs = self.fs.with_file(path, contents.as_ref());
+        self
+    }
+    /// Set the contents of the clipboard
+    pub fn with_clipboard(self, contents: &str) -> Self {
+        *self.clipboard.lock().unwrap() = contents.into();
+        self
+    }
+    /// Only support the given system functions
+    ///
+    /// By default, all system functions are supported.
+    pub fn with_supported(mut self, ops: impl IntoIterator<Item = SysOp>) -> Self {
+        self.supported = Some(ops.into_iter().collect());
+        self
+    }
+    /// Set the time in seconds since the Unix epoch
+    pub fn with_time(self, time: f64) -> Self {
+        *self.time.lock().unwrap() = time;
+        self
+    }
+    /// Get everything printed to stdout
+    pub fn stdout(&self) -> String {
+        self.stdout.lock().unwrap().clone()
+    }
+    /// Get everything printed to stderr
+    pub fn stderr(&self) -> String {
+        self.stderr.lock().unwrap().clone()
+    }
+    /// Get the contents of a file
+    pub fn file(&self, path: &str) -> Option<Vec<u8>> {
+        self.fs.file(path)
+    }
+    /// Get the file system
+    pub fn fs(&self) -> &VirtualFs {
+        &self.fs
+    }
+    /// Get the WAV bytes of all the audio that has been played
+    pub fn played_audio(&self) -> Vec<Vec<u8>> {
+        self.audio.lock().unwrap().clone()
+    }
+    /// Get the current virtual time
+    pub fn time(&self) -> f64 {
+        *self.time.lock().unwrap()
+    }
+    fn new_handle(&self) -> Handle {
+        Handle(self.next_handle.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl SysBackend for TestBackend {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn supports(&self, op: SysOp) -> bool {
+        (self.supported.as_ref()).is_none_or(|ops| ops.contains(&op))
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.stdout.lock().unwrap().push_str(s);
+        Ok(())
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.stderr.lock().unwrap().push_str(s);
+        Ok(())
+    }
+    fn print_str_trace(&self, s: &str) {
+        self.stderr.lock().unwrap().push_str(s);
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        let mut stdin = self.stdin.lock().unwrap();
+        if stdin.is_empty() {
+            return Ok(None);
+        }
+        let len = (stdin.iter().position(|&b| b == b'\n')).map_or(stdin.len(), |i| i + 1);
+        let line: Vec<u8> = stdin.drain(..len).collect();
+        let line = String::from_utf8_lossy(&line);
+        Ok(Some(line.trim_end_matches(['\n', '\r']).into()))
+    }
+    fn read_stdin(&self, count: Option<usize>) -> Result<Vec<u8>, String> {
+        let mut stdin = self.stdin.lock().unwrap();
+        let len = count.unwrap_or(stdin.len()).min(stdin.len());
+        Ok(stdin.drain(..len).collect())
+    }
+    fn stdin_eof(&self) -> Result<bool, String> {
+        Ok(self.stdin.lock().unwrap().is_empty())
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.vars.lock().unwrap().get(name).cloned()
+    }
+    fn set_var(&self, name: &str, value: &str) -> Result<(), String> {
+        (self.vars.lock().unwrap()).insert(name.into(), value.into());
+        Ok(())
+    }
+    fn vars(&self) -> Vec<(String, String)> {
+        let mut vars: Vec<_> = self.vars.lock().unwrap().clone().into_iter().collect();
+        vars.sort();
+        vars
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        self.fs.exists(path)
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.fs.list_dir(path)
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        self.fs.is_file(path)
+    }
+    fn file_size(&self, path: &str) -> Result<u64, String> {
+        self.fs.file_size(path)
+    }
+    fn make_dir(&self, path: &str) -> Result<(), String> {
+        self.fs.make_dir(path)
+    }
+    fn remove_dir(&self, path: &str) -> Result<(), String> {
+        self.fs.remove_dir(path)
+    }
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        self.fs.delete_file(path)
+    }
+    fn create_temp_file(&self) -> Result<String, String> {
+        Ok(self.fs.create_temp_file())
+    }
+    fn create_temp_dir(&self) -> Result<String, String> {
+        Ok(self.fs.create_temp_dir())
+    }
+    fn current_dir(&self) -> Result<String, String> {
+        Ok(self.fs.current_dir())
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        self.fs.change_directory(path)
+    }
+    fn clean_up_temp(&self) -> Result<(), String> {
+        self.fs.clean_up_temp();
+        Ok(())
+    }
+    fn create_file(&self, path: &str) -> Result<Handle, String> {
+        self.fs.create_file(path)
+    }
+    fn open_file(&self, path: &str) -> Result<Handle, String> {
+        self.fs.open_file(path)
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        self.fs.read(handle, count)
+    }
+    fn write(&self, handle: Handle, data: &[u8]) -> Result<(), String> {
+        self.fs.write(handle, data)
+    }
+    fn seek(&self, handle: Handle, seek: SeekFrom) -> Result<(), String> {
+        self.fs.seek(handle, seek)
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        self.fs.close(handle);
+        Ok(())
+    }
+    fn file_read_all(&self, path: &str) -> Result<Vec<u8>, String> {
+        self.fs.read_all(path)
+    }
+    fn file_write_all(&self, path: &str, contents: &[u8]) -> Result<(), String> {
+        self.fs.write_all(path, contents)
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        Ok(self.clipboard.lock().unwrap().clone())
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        *self.clipboard.lock().unwrap() = contents.into();
+        Ok(())
+    }
+    fn play_audio(&self, wav_bytes: Vec<u8>) -> Result<(), String> {
+        self.audio.lock().unwrap().push(wav_bytes);
+        Ok(())
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        *self.time.lock().unwrap() += seconds.max(0.0);
+        Ok(())
+    }
+    fn now(&self) -> f64 {
+        self.time()
+    }
+    fn monotonic_time(&self) -> f64 {
+        self.time()
+    }
+    fn spawn(
+        &self,
+        mut env: Uiua,
+        f: Box<dyn FnOnce(&mut Uiua) -> UiuaResult + Send>,
+    ) -> Result<Handle, String> {
+        let handle = self.new_handle();
+        let res = f(&mut env).map(|_| env.take_stack());
+        self.thread_results.lock().unwrap().insert(handle, res);
+        Ok(handle)
+    }
+    fn wait(&self, handle: Handle) -> Result<Vec<Value>, Result<UiuaError, String>> {
+        match self.thread_results.lock().unwrap().remove(&handle) {
+            Some(Ok(stack)) => Ok(stack),
+            Some(Err(err)) => Err(Ok(err)),
+            None => Err(Err("Invalid thread handle".into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{array::Array, function::Function};
+
+    #[test]
+    fn test_backend() {
+        let backend = TestBackend::default()
+            .with_stdin_lines(["world"])
+            .with_file("greeting.txt", "Hello")
+            .with_time(1000.0);
+        let mut env = Uiua::with_backend(backend);
+        env.load_str(
+            r#"
+    &p $"_, _!" &fras "greeting.txt" &sc
+    &w "oops" 2
+    &fwa "out.txt" "data"
+    &fmd "dir"
+    &fwa "dir/a.txt" "a"
+    H ← &fc "h.txt"
+    &w "abcd" H
+    &cl H
+    G ← &fo "h.txt"
+    &rs 3 G
+    &fe "dir/a.txt"
+    &fld "."
+    &sl 1.5
+    now
+    &sc
+    "#,
+        )
+        .unwrap();
+        let paths = ["./dir", "./greeting.txt", "./h.txt", "./out.txt"].map(String::from);
+        let expected: [Value; 5] = [
+            "abc".into(),
+            1.into(),
+            paths.into_iter().collect::<Array<Arc<Function>>>().into(),
+            1001.5.into(),
+            0u8.into(),
+        ];
+        assert_eq!(env.take_stack(), expected);
+        let backend = env.downcast_backend::<TestBackend>().unwrap();
+        assert_eq!(backend.stdout(), "Hello, world!\n");
+        assert_eq!(backend.stderr(), "oops");
+        assert_eq!(backend.file("out.txt").unwrap(), b"data");
+        assert_eq!(backend.file("h.txt").unwrap(), b"abcd");
+        assert_eq!(backend.time(), 1001.5);
+        assert!(env.load_str(r#"&fras "missing.txt""#).is_err());
+    }
+}
@@ -0,0 +1,712 @@
+//! An in-memory file system and a system backend that uses it
+
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::Duration,
+};
+
+#[cfg(feature = "image")]
+use image::DynamicImage;
+
+use crate::{
+    run::DEFAULT_ELEMENT_LIMIT,
+    sys::{normalize_path, AudioStreamFn},
+    value::Value,
+    Handle, HttpResponse, SysBackend, SysOp, TextStyle, Uiua, UiuaError, UiuaResult,
+    WebSocketMessage,
+};
+
+/// Handles of open virtual files start here so that they do not collide with the handles of a wrapped backend
+const FIRST_HANDLE: u64 = 1 << 48;
+
+/// The largest size in bytes of a virtual file, which is the same as the largest array
+const MAX_FILE_SIZE: usize = DEFAULT_ELEMENT_LIMIT;
+
+#[derive(Debug, Clone)]
+enum Entry {
+    File(Vec<u8>),
+    Dir,
+}
+
+#[derive(Debug)]
+struct VfsState {
+    entries: BTreeMap<PathBuf, Entry>,
+    current_dir: PathBuf,
+    open_files: HashMap<Handle, (PathBuf, usize)>,
+    temp_paths: Vec<PathBuf>,
+}
+
+/// An in-memory tree of files and directories
+///
+/// Paths are resolved like they are by the native backend.
+/// Relative paths are relative to the current directory, which starts at the root `/`,
+/// and `.` and `..` components are resolved, so `a/../b.txt`, `./b.txt`, and `/b.txt` are the same file.
+/// Like native file systems, a file can only be created in a directory that exists.
+#[derive(Debug)]
+pub struct VirtualFs {
+    state: Mutex<VfsState>,
+    next_handle: AtomicU64,
+    next_temp: AtomicU64,
+}
+
+impl Default for VirtualFs {
+    fn default() -> Self {
+        let root = PathBuf::from("/");
+        VirtualFs {
+            state: Mutex::new(VfsState {
+                entries: [(root.clone(), Entry::Dir)].into(),
+                current_dir: root,
+                open_files: HashMap::new(),
+                temp_paths: Vec::new(),
+            }),
+            next_handle: FIRST_HANDLE.into(),
+            next_temp: 0.into(),
+        }
+    }
+}
+
+fn not_found(path: &str) -> String {
+    format!("{path}: No such file or directory")
+}
+
+impl VfsState {
+    fn resolve(&self, path: &str) -> PathBuf {
+        normalize_path(&self.current_dir.join(path)).into()
+    }
+    fn get(&self, path: &str) -> Result<&Entry, String> {
+        self.entries
+            .get(&self.resolve(path))
+            .ok_or_else(|| not_found(path))
+    }
+    fn make_dir_all(&mut self, path: &Path, display: &str) -> Result<(), String> {
+        for dir in path.ancestors() {
+            match self.entries.get(dir) {
+                Some(Entry::Dir) => break,
+                Some(Entry::File(_)) => return Err(format!("{display}: Not a directory")),
+                None => {}
+            }
+        }
+        for dir in path.ancestors() {
+            self.entries.entry(dir.into()).or_insert(Entry::Dir);
+        }
+        Ok(())
+    }
+    fn write_file(&mut self, path: &str, contents: Vec<u8>) -> Result<PathBuf, String> {
+        let resolved = self.resolve(path);
+        match resolved.parent().map(|parent| self.entries.get(parent)) {
+            Some(Some(Entry::Dir)) => {}
+            Some(Some(Entry::File(_))) => return Err(format!("{path}: Not a directory")),
+            _ => return Err(not_found(path)),
+        }
+        if let Some(Entry::Dir) = self.entries.get(&resolved) {
+            return Err(format!("{path}: Is a directory"));
+        }
+        self.entries.insert(resolved.clone(), Entry::File(contents));
+        Ok(resolved)
+    }
+    fn children<'a>(&'a self, dir: &'a Path) -> impl Iterator<Item = &'a Path> + 'a {
+        (self.entries.keys())
+            .filter(move |path| path.parent() == Some(dir))
+            .map(PathBuf::as_path)
+    }
+}
+
+impl VirtualFs {
+    /// Add a file, creating its parent directories
+    pub fn with_file(self, path: &str, contents: impl Into<Vec<u8>>) -> Self {
+        {
+            let mut state = self.state.lock().unwrap();
+            let resolved = state.resolve(path);
+            if let Some(parent) = resolved.parent() {
+                _ = state.make_dir_all(parent, path);
+            }
+            state.entries.insert(resolved, Entry::File(contents.into()));
+        }
+        self
+    }
+    /// Add a directory, creating its parent directories
+    pub fn with_dir(self, path: &str) -> Self {
+        _ = self.make_dir(path);
+        self
+    }
+    /// Get the contents of a file
+    pub fn file(&self, path: &str) -> Option<Vec<u8>> {
+        match self.state.lock().unwrap().get(path) {
+            Ok(Entry::File(contents)) => Some(contents.clone()),
+            _ => None,
+        }
+    }
+    pub fn exists(&self, path: &str) -> bool {
+        self.state.lock().unwrap().get(path).is_ok()
+    }
+    pub fn is_file(&self, path: &str) -> Result<bool, String> {
+        let state = self.state.lock().unwrap();
+        Ok(matches!(state.get(path)?, Entry::File(_)))
+    }
+    pub fn file_size(&self, path: &str) -> Result<u64, String> {
+        match self.state.lock().unwrap().get(path)? {
+            Entry::File(contents) => Ok(contents.len() as u64),
+            Entry::Dir => Ok(0),
+        }
+    }
+    /// List the paths in a directory
+    ///
+    /// Like in the native backend, each path is the directory's path joined with the entry's name.
+    pub fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        let state = self.state.lock().unwrap();
+        if let Entry::File(_) = state.get(path)? {
+            return Err(format!("{path}: Not a directory"));
+        }
+        let dir = state.resolve(path);
+        Ok(state
+            .children(&dir)
+            .filter_map(Path::file_name)
+            .map(|name| Path::new(path).join(name).to_string_lossy().into_owned())
+            .collect())
+    }
+    /// Create a directory and its parents
+    pub fn make_dir(&self, path: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let resolved = state.resolve(path);
+        state.make_dir_all(&resolved, path)
+    }
+    /// Remove an empty directory
+    pub fn remove_dir(&self, path: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if let Entry::File(_) = state.get(path)? {
+            return Err(format!("{path}: Not a directory"));
+        }
+        let resolved = state.resolve(path);
+        if resolved.parent().is_none() || state.children(&resolved).next().is_some() {
+            return Err(format!("{path}: Directory not empty"));
+        }
+        state.entries.remove(&resolved);
+        Ok(())
+    }
+    pub fn delete_file(&self, path: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if let Entry::Dir = state.get(path)? {
+            return Err(format!("{path}: Is a directory"));
+        }
+        let resolved = state.resolve(path);
+        state.entries.remove(&resolved);
+        Ok(())
+    }
+    pub fn read_all(&self, path: &str) -> Result<Vec<u8>, String> {
+        match self.state.lock().unwrap().get(path)? {
+            Entry::File(contents) => Ok(contents.clone()),
+            Entry::Dir => Err(format!("{path}: Is a directory")),
+        }
+    }
+    /// Write a file, replacing it if it exists
+    pub fn write_all(&self, path: &str, contents: &[u8]) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        state.write_file(path, contents.to_vec()).map(drop)
+    }
+    pub fn current_dir(&self) -> String {
+        let state = self.state.lock().unwrap();
+        state.current_dir.to_string_lossy().into_owned()
+    }
+    pub fn change_directory(&self, path: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        if let Entry::File(_) = state.get(path)? {
+            return Err(format!("{path}: Not a directory"));
+        }
+        state.current_dir = state.resolve(path);
+        Ok(())
+    }
+    fn temp_path(&self, state: &mut VfsState) -> PathBuf {
+        _ = state.make_dir_all(Path::new("/tmp"), "/tmp");
+        let n = self.next_temp.fetch_add(1, Ordering::Relaxed);
+        let path = PathBuf::from(format!("/tmp/uiua-{n}"));
+        state.temp_paths.push(path.clone());
+        path
+    }
+    pub fn create_temp_file(&self) -> String {
+        let mut state = self.state.lock().unwrap();
+        let path = self.temp_path(&mut state);
+        state.entries.insert(path.clone(), Entry::File(Vec::new()));
+        path.to_string_lossy().into_owned()
+    }
+    pub fn create_temp_dir(&self) -> String {
+        let mut state = self.state.lock().unwrap();
+        let path = self.temp_path(&mut state);
+        state.entries.insert(path.clone(), Entry::Dir);
+        path.to_string_lossy().into_owned()
+    }
+    /// Delete the temporary files and directories that have been created
+    pub fn clean_up_temp(&self) {
+        let mut state = self.state.lock().unwrap();
+        for path in std::mem::take(&mut state.temp_paths) {
+            state.entries.retain(|entry, _| !entry.starts_with(&path));
+        }
+    }
+    fn open_handle(&self, state: &mut VfsState, path: PathBuf) -> Handle {
+        let handle = Handle(self.next_handle.fetch_add(1, Ordering::Relaxed));
+        state.open_files.insert(handle, (path, 0));
+        handle
+    }
+    /// Open a file for reading and writing
+    pub fn open_file(&self, path: &str) -> Result<Handle, String> {
+        let mut state = self.state.lock().unwrap();
+        if let Entry::Dir = state.get(path)? {
+            return Err(format!("{path}: Is a directory"));
+        }
+        let resolved = state.resolve(path);
+        Ok(self.open_handle(&mut state, resolved))
+    }
+    /// Create or truncate a file and open it
+    pub fn create_file(&self, path: &str) -> Result<Handle, String> {
+        let mut state = self.state.lock().unwrap();
+        let resolved = state.write_file(path, Vec::new())?;
+        Ok(self.open_handle(&mut state, resolved))
+    }
+    /// Whether a handle is an open virtual file
+    pub fn has_handle(&self, handle: Handle) -> bool {
+        self.state.lock().unwrap().open_files.contains_key(&handle)
+    }
+    pub fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let (path, pos) = (state.open_files.get_mut(&handle)).ok_or("Invalid file handle")?;
+        let contents = match state.entries.get(path) {
+            Some(Entry::File(contents)) => contents.as_slice(),
+            _ => &[],
+        };
+        let start = (*pos).min(contents.len());
+        let end = start.saturating_add(count).min(contents.len());
+        *pos = end;
+        Ok(contents[start..end].to_vec())
+    }
+    pub fn write(&self, handle: Handle, data: &[u8]) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let (path, pos) = (state.open_files.get_mut(&handle)).ok_or("Invalid file handle")?;
+        let Some(Entry::File(contents)) = state.entries.get_mut(path) else {
+            return Err("The file has been deleted".into());
+        };
+        let end = (pos.checked_add(data.len()))
+            .filter(|&end| end <= MAX_FILE_SIZE)
+            .ok_or_else(|| format!("Files cannot be larger than {MAX_FILE_SIZE} bytes"))?;
+        if contents.len() < end {
+            contents.resize(end, 0);
+        }
+        contents[*pos..end].copy_from_slice(data);
+        *pos = end;
+        Ok(())
+    }
+    pub fn seek(&self, handle: Handle, seek: SeekFrom) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        let (path, pos) = (state.open_files.get_mut(&handle)).ok_or("Invalid file handle")?;
+        let len = match state.entries.get(path) {
+            Some(Entry::File(contents)) => contents.len(),
+            _ => 0,
+        };
+        let offset = |base: usize, n: i64| {
+            isize::try_from(n)
+                .ok()
+                .and_then(|n| base.checked_add_signed(n))
+        };
+        let new_pos = match seek {
+            SeekFrom::Start(n) => usize::try_from(n).ok(),
+            SeekFrom::End(n) => offset(len, n),
+            SeekFrom::Current(n) => offset(*pos, n),
+        };
+        *pos = new_pos.filter(|&pos| pos <= MAX_FILE_SIZE).ok_or_else(|| {
+            format!("Cannot seek before the start of a file or past {MAX_FILE_SIZE} bytes")
+        })?;
+        Ok(())
+    }
+    pub fn close(&self, handle: Handle) {
+        self.state.lock().unwrap().open_files.remove(&handle);
+    }
+}
+
+/// A [`SysBackend`] whose file system operations use a [`VirtualFs`]
+///
+/// Everything else is done by the inner backend.
+pub struct VirtualFsBackend<B> {
+    inner: B,
+    fs: VirtualFs,
+}
+
+impl<B: SysBackend> VirtualFsBackend<B> {
+    pub fn new(inner: B, fs: VirtualFs) -> Self {
+        VirtualFsBackend { inner, fs }
+    }
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+    pub fn fs(&self) -> &VirtualFs {
+        &self.fs
+    }
+}
+
+impl<B: SysBackend> SysBackend for VirtualFsBackend<B> {
+    fn any(&self) -> &dyn Any {
+        self
+    }
+    fn supports(&self, op: SysOp) -> bool {
+        self.inner.supports(op)
+    }
+    fn file_exists(&self, path: &str) -> bool {
+        self.fs.exists(path)
+    }
+    fn list_dir(&self, path: &str) -> Result<Vec<String>, String> {
+        self.fs.list_dir(path)
+    }
+    fn is_file(&self, path: &str) -> Result<bool, String> {
+        self.fs.is_file(path)
+    }
+    fn file_size(&self, path: &str) -> Result<u64, String> {
+        self.fs.file_size(path)
+    }
+    fn file_modified(&self, _path: &str) -> Result<f64, String> {
+        Err("Modified times are not supported by the virtual file system".into())
+    }
+    fn watch_path(&self, _path: &str, _timeout: Option<f64>) -> Result<Vec<String>, String> {
+        Err("Watching files is not supported by the virtual file system".into())
+    }
+    fn make_dir(&self, path: &str) -> Result<(), String> {
+        self.fs.make_dir(path)
+    }
+    fn remove_dir(&self, path: &str) -> Result<(), String> {
+        self.fs.remove_dir(path)
+    }
+    fn delete_file(&self, path: &str) -> Result<(), String> {
+        self.fs.delete_file(path)
+    }
+    fn create_temp_file(&self) -> Result<String, String> {
+        Ok(self.fs.create_temp_file())
+    }
+    fn create_temp_dir(&self) -> Result<String, String> {
+        Ok(self.fs.create_temp_dir())
+    }
+    fn current_dir(&self) -> Result<String, String> {
+        Ok(self.fs.current_dir())
+    }
+    fn change_directory(&self, path: &str) -> Result<(), String> {
+        self.fs.change_directory(path)
+    }
+    fn clean_up_temp(&self) -> Result<(), String> {
+        self.fs.clean_up_temp();
+        self.inner.clean_up_temp()
+    }
+    fn create_file(&self, path: &str) -> Result<Handle, String> {
+        self.fs.create_file(path)
+    }
+    fn open_file(&self, path: &str) -> Result<Handle, String> {
+        self.fs.open_file(path)
+    }
+    fn file_read_all(&self, path: &str) -> Result<Vec<u8>, String> {
+        self.fs.read_all(path)
+    }
+    fn file_write_all(&self, path: &str, contents: &[u8]) -> Result<(), String> {
+        self.fs.write_all(path, contents)
+    }
+    fn read(&self, handle: Handle, count: usize) -> Result<Vec<u8>, String> {
+        if self.fs.has_handle(handle) {
+            self.fs.read(handle, count)
+        } else {
+            self.inner.read(handle, count)
+        }
+    }
+    fn read_until(&self, handle: Handle, delim: &[u8]) -> Result<Vec<u8>, String> {
+        if !self.fs.has_handle(handle) {
+            return self.inner.read_until(handle, delim);
+        }
+        let mut buffer = Vec::new();
+        loop {
+            let bytes = self.fs.read(handle, 1)?;
+            if bytes.is_empty() {
+                break;
+            }
+            buffer.extend_from_slice(&bytes);
+            if buffer.ends_with(delim) {
+                break;
+            }
+        }
+        Ok(buffer)
+    }
+    fn write(&self, handle: Handle, contents: &[u8]) -> Result<(), String> {
+        if self.fs.has_handle(handle) {
+            self.fs.write(handle, contents)
+        } else {
+            self.inner.write(handle, contents)
+        }
+    }
+    fn seek(&self, handle: Handle, pos: SeekFrom) -> Result<(), String> {
+        if self.fs.has_handle(handle) {
+            self.fs.seek(handle, pos)
+        } else {
+            self.inner.seek(handle, pos)
+        }
+    }
+    fn close(&self, handle: Handle) -> Result<(), String> {
+        if self.fs.has_handle(handle) {
+            self.fs.close(handle);
+            Ok(())
+        } else {
+            self.inner.close(handle)
+        }
+    }
+    fn save_error_color(&self, error: &UiuaError) {
+        self.inner.save_error_color(error)
+    }
+    fn print_str_stdout(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stdout(s)
+    }
+    fn print_str_stderr(&self, s: &str) -> Result<(), String> {
+        self.inner.print_str_stderr(s)
+    }
+    fn print_styled_stdout(&self, s: &str, style: &TextStyle) -> Result<(), String> {
+        self.inner.print_styled_stdout(s, style)
+    }
+    fn stdout_is_terminal(&self) -> bool {
+        self.inner.stdout_is_terminal()
+    }
+    fn stderr_is_terminal(&self) -> bool {
+        self.inner.stderr_is_terminal()
+    }
+    fn print_str_trace(&self, s: &str) {
+        self.inner.print_str_trace(s)
+    }
+    fn flush(&self) -> Result<(), String> {
+        self.inner.flush()
+    }
+    fn set_line_buffered(&self, line_buffered: bool) -> Result<(), String> {
+        self.inner.set_line_buffered(line_buffered)
+    }
+    fn scan_line_stdin(&self) -> Result<Option<String>, String> {
+        self.inner.scan_line_stdin()
+    }
+    fn read_stdin(&self, count: Option<usize>) -> Result<Vec<u8>, String> {
+        self.inner.read_stdin(count)
+    }
+    fn stdin_eof(&self) -> Result<bool, String> {
+        self.inner.stdin_eof()
+    }
+    fn var(&self, name: &str) -> Option<String> {
+        self.inner.var(name)
+    }
+    fn set_var(&self, name: &str, value: &str) -> Result<(), String> {
+        self.inner.set_var(name, value)
+    }
+    fn vars(&self) -> Vec<(String, String)> {
+        self.inner.vars()
+    }
+    fn term_size(&self) -> Result<(usize, usize), String> {
+        self.inner.term_size()
+    }
+    fn set_raw_mode(&self, raw: bool) -> Result<(), String> {
+        self.inner.set_raw_mode(raw)
+    }
+    fn poll_key(&self) -> Result<Option<String>, String> {
+        self.inner.poll_key()
+    }
+    fn sleep(&self, seconds: f64) -> Result<(), String> {
+        self.inner.sleep(seconds)
+    }
+    fn now(&self) -> f64 {
+        self.inner.now()
+    }
+    fn monotonic_time(&self) -> f64 {
+        self.inner.monotonic_time()
+    }
+    #[cfg(feature = "image")]
+    fn show_image(&self, image: DynamicImage) -> Result<(), String> {
+        self.inner.show_image(image)
+    }
+    fn show_gif(&self, gif_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.show_gif(gif_bytes)
+    }
+    fn play_audio(&self, wave_bytes: Vec<u8>) -> Result<(), String> {
+        self.inner.play_audio(wave_bytes)
+    }
+    fn audio_sample_rate(&self) -> u32 {
+        self.inner.audio_sample_rate()
+    }
+    fn stream_audio(&self, f: AudioStreamFn) -> Result<(), String> {
+        self.inner.stream_audio(f)
+    }
+    fn tcp_listen(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.tcp_listen(addr)
+    }
+    fn tcp_accept(&self, handle: Handle) -> Result<Handle, String> {
+        self.inner.tcp_accept(handle)
+    }
+    fn tcp_connect(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.tcp_connect(addr)
+    }
+    fn tcp_addr(&self, handle: Handle) -> Result<String, String> {
+        self.inner.tcp_addr(handle)
+    }
+    fn tcp_set_non_blocking(&self, handle: Handle, non_blocking: bool) -> Result<(), String> {
+        self.inner.tcp_set_non_blocking(handle, non_blocking)
+    }
+    fn tcp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_read_timeout(handle, timeout)
+    }
+    fn tcp_set_write_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.tcp_set_write_timeout(handle, timeout)
+    }
+    fn udp_bind(&self, addr: &str) -> Result<Handle, String> {
+        self.inner.udp_bind(addr)
+    }
+    fn udp_send(&self, handle: Handle, data: &[u8], addr: &str) -> Result<(), String> {
+        self.inner.udp_send(handle, data, addr)
+    }
+    fn udp_receive(&self, handle: Handle, max: usize) -> Result<(Vec<u8>, String), String> {
+        self.inner.udp_receive(handle, max)
+    }
+    fn udp_set_read_timeout(
+        &self,
+        handle: Handle,
+        timeout: Option<Duration>,
+    ) -> Result<(), String> {
+        self.inner.udp_set_read_timeout(handle, timeout)
+    }
+    fn ws_connect(&self, url: &str) -> Result<Handle, String> {
+        self.inner.ws_connect(url)
+    }
+    fn ws_send(&self, handle: Handle, message: WebSocketMessage) -> Result<(), String> {
+        self.inner.ws_send(handle, message)
+    }
+    fn ws_receive(&self, handle: Handle) -> Result<Option<WebSocketMessage>, String> {
+        self.inner.ws_receive(handle)
+    }
+    fn spawn(
+        &self,
+        env: Uiua,
+        f: Box<dyn FnOnce(&mut Uiua) -> UiuaResult + Send>,
+    ) -> Result<Handle, String> {
+        self.inner.spawn(env, f)
+    }
+    fn wait(&self, handle: Handle) -> Result<Vec<Value>, Result<UiuaError, String>> {
+        self.inner.wait(handle)
+    }
+    fn run_command_inherit(&self, command: &str, args: &[&str]) -> Result<(), String> {
+        self.inner.run_command_inherit(command, args)
+    }
+    fn run_command_capture(
+        &self,
+        command: &str,
+        args: &[&str],
+    ) -> Result<(String, String), String> {
+        self.inner.run_command_capture(command, args)
+    }
+    fn run_command_piped(
+        &self,
+        command: &str,
+        args: &[&str],
+        input: &[u8],
+    ) -> Result<(String, String, i32), String> {
+        self.inner.run_command_piped(command, args, input)
+    }
+    fn run_command_stream(&self, command: &str, args: &[&str]) -> Result<Handle, String> {
+        self.inner.run_command_stream(command, args)
+    }
+    fn wait_command(&self, handle: Handle) -> Result<i32, String> {
+        self.inner.wait_command(handle)
+    }
+    fn clipboard(&self) -> Result<String, String> {
+        self.inner.clipboard()
+    }
+    fn set_clipboard(&self, contents: &str) -> Result<(), String> {
+        self.inner.set_clipboard(contents)
+    }
+    fn https_get(&self, request: &str, handle: Handle) -> Result<String, String> {
+        self.inner.https_get(request, handle)
+    }
+    fn http_request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[String],
+        body: &[u8],
+    ) -> Result<HttpResponse, String> {
+        self.inner.http_request(method, url, headers, body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::{array::Array, function::Function, TestBackend};
+
+    #[test]
+    fn virtual_file_system() {
+        let fs = VirtualFs::default().with_file("data/nums.txt", "1 2 3");
+        let mut env = Uiua::with_backend(VirtualFsBackend::new(TestBackend::default(), fs));
+        env.load_str(
+            r#"
+    &fe "data/../data/nums.txt"
+    &fld "data"
+    &cd "data"
+    &fras "./nums.txt"
+    &pa "nums.txt"
+    &fmd "a/b"
+    &fwa "a/b/c.txt" "c"
+    &fif "a"
+    H ← &fc "h.txt"
+    &w "hi" H
+    &cl H
+    &p "done"
+    "#,
+        )
+        .unwrap();
+        let strings = |s: &[&str]| {
+            Value::from(
+                s.iter()
+                    .map(|s| s.to_string())
+                    .collect::<Array<Arc<Function>>>(),
+            )
+        };
+        let expected = [
+            Value::from(1),
+            strings(&["data/nums.txt"]),
+            "1 2 3".into(),
+            "/data/nums.txt".into(),
+            0.into(),
+        ];
+        assert_eq!(env.take_stack(), expected);
+        let backend = env
+            .downcast_backend::<VirtualFsBackend<TestBackend>>()
+            .unwrap();
+        assert_eq!(backend.fs().file("/data/a/b/c.txt").unwrap(), b"c");
+        assert_eq!(backend.fs().file("/data/h.txt").unwrap(), b"hi");
+        assert_eq!(backend.inner().stdout(), "done\n");
+        env.load_str("H ← &fo \"h.txt\"\n&fsk ¯1 H\n&w \"!\" H\n&fsk ¯3 H\n&rs 2 H")
+            .unwrap();
+        assert_eq!(env.take_stack(), [Value::from("i!")]);
+        for (code, message) in [
+            (r#"&fwa "missing/x.txt" "x""#, "No such file or directory"),
+            (r#"&frd "a""#, "Directory not empty"),
+            (r#"&fde "a""#, "Is a directory"),
+            (r#"&fras "/nums.txt""#, "No such file or directory"),
+            ("H ← &fo \"h.txt\"\n&fsk 1e15 H", "Cannot seek"),
+            ("H ← &fo \"h.txt\"\n&fsk ¯5 H", "Cannot seek"),
+        ] {
+            let err = env.load_str(code).unwrap_err();
+            assert!(err.message().contains(message), "{code}: {err}");
+        }
+    }
+}
@@ -0,0 +1,492 @@
+//! Conversions between Unix time and calendar dates
+//!
+//! All dates are in UTC and use the proleptic Gregorian calendar.
+//! The day algorithms are from <https://howardhinnant.github.io/date_algorithms.html>
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// The largest magnitude of a year, month, or day
+///
+/// This keeps the day arithmetic well within the range of `i64`.
+const MAX_DATE_COMPONENT: f64 = 1e9;
+/// The largest magnitude of a Unix time, which is about a billion years
+const MAX_SECONDS: f64 = MAX_DATE_COMPONENT * 365.25 * SECONDS_PER_DAY;
+
+/// Convert days since the Unix epoch to a year, month, and day
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Convert a year, month, and day to days since the Unix epoch
+///
+/// Months and days outside their normal ranges roll over into the next or previous year or month.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = year + (month - 1).div_euclid(12);
+    let month = (month - 1).rem_euclid(12) + 1;
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn check_finite(seconds: f64) -> Result<(), String> {
+    if seconds.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("Time must be a finite number, but it is {seconds}"))
+    }
+}
+
+fn check_time(seconds: f64) -> Result<(), String> {
+    check_finite(seconds)?;
+    if seconds.abs() > MAX_SECONDS {
+        return Err(format!(
+            "Time must be within about a billion years of the Unix epoch, \
+            but it is {seconds:e} seconds"
+        ));
+    }
+    Ok(())
+}
+
+/// The calendar date and time of a Unix time
+struct DateTime {
+    year: i64,
+    month: i64,
+    day: i64,
+    hour: i64,
+    minute: i64,
+    /// Includes the fractional part
+    second: f64,
+    weekday: usize,
+    day_of_year: i64,
+}
+
+impl DateTime {
+    fn new(seconds: f64) -> Result<Self, String> {
+        check_time(seconds)?;
+        let days = (seconds / SECONDS_PER_DAY).floor();
+        let time = seconds - days * SECONDS_PER_DAY;
+        let days = days as i64;
+        let (year, month, day) = civil_from_days(days);
+        let whole = time.floor() as i64;
+        Ok(DateTime {
+            year,
+            month,
+            day,
+            hour: whole / 3600,
+            minute: whole % 3600 / 60,
+            second: (whole % 60) as f64 + time.fract(),
+            weekday: (days + 4).rem_euclid(7) as usize,
+            day_of_year: days - days_from_civil(year, 1, 1) + 1,
+        })
+    }
+}
+
+/// Convert a Unix time to `[year month day hour minute second]`
+pub(crate) fn time_to_components(seconds: f64) -> Result<[f64; 6], String> {
+    let dt = DateTime::new(seconds)?;
+    Ok([
+        dt.year as f64,
+        dt.month as f64,
+        dt.day as f64,
+        dt.hour as f64,
+        dt.minute as f64,
+        dt.second,
+    ])
+}
+
+/// Convert `[year month day hour minute second]` to a Unix time
+///
+/// Missing trailing components default to the start of their period.
+pub(crate) fn components_to_time(components: &[f64]) -> Result<f64, String> {
+    if components.is_empty() || components.len() > 6 {
+        return Err(format!(
+            "Date must have between 1 and 6 components, but it has {}",
+            components.len()
+        ));
+    }
+    for &c in components {
+        check_finite(c)?;
+    }
+    for &c in components.iter().take(3) {
+        if c.abs() > MAX_DATE_COMPONENT {
+            return Err(format!(
+                "Years, months, and days must be at most {MAX_DATE_COMPONENT:e} \
+                in magnitude, but one is {c:e}"
+            ));
+        }
+    }
+    let get = |i: usize, default: f64| components.get(i).copied().unwrap_or(default);
+    let days = days_from_civil(
+        get(0, 1970.0) as i64,
+        get(1, 1.0) as i64,
+        get(2, 1.0) as i64,
+    );
+    Ok(days as f64 * SECONDS_PER_DAY + get(3, 0.0) * 3600.0 + get(4, 0.0) * 60.0 + get(5, 0.0))
+}
+
+/// Format a Unix time with a `strftime`-style pattern
+pub(crate) fn format_time(pattern: &str, seconds: f64) -> Result<String, String> {
+    let dt = DateTime::new(seconds)?;
+    let mut s = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            s.push(c);
+            continue;
+        }
+        let Some(directive) = chars.next() else {
+            return Err("Time pattern ends with an unfinished %".into());
+        };
+        match directive {
+            'Y' => s.push_str(&dt.year.to_string()),
+            'y' => s.push_str(&format!("{:02}", dt.year.rem_euclid(100))),
+            'm' => s.push_str(&format!("{:02}", dt.month)),
+            'd' => s.push_str(&format!("{:02}", dt.day)),
+            'e' => s.push_str(&format!("{:>2}", dt.day)),
+            'j' => s.push_str(&format!("{:03}", dt.day_of_year)),
+            'H' => s.push_str(&format!("{:02}", dt.hour)),
+            'I' => s.push_str(&format!("{:02}", (dt.hour + 11) % 12 + 1)),
+            'p' => s.push_str(if dt.hour < 12 { "AM" } else { "PM" }),
+            'M' => s.push_str(&format!("{:02}", dt.minute)),
+            'S' => s.push_str(&format!("{:02}", dt.second.floor())),
+            'f' => s.push_str(&format!("{:03}", (dt.second.fract() * 1000.0).floor())),
+            'B' => s.push_str(MONTHS[dt.month as usize - 1]),
+            'b' => s.push_str(&MONTHS[dt.month as usize - 1][..3]),
+            'A' => s.push_str(WEEKDAYS[dt.weekday]),
+            'a' => s.push_str(&WEEKDAYS[dt.weekday][..3]),
+            'u' => s.push_str(&((dt.weekday + 6) % 7 + 1).to_string()),
+            'F' => s.push_str(&format_time("%Y-%m-%d", seconds)?),
+            'T' => s.push_str(&format_time("%H:%M:%S", seconds)?),
+            's' => s.push_str(&seconds.floor().to_string()),
+            'z' => s.push_str("+0000"),
+            'Z' => s.push_str("UTC"),
+            '%' => s.push('%'),
+            c => return Err(format!("Unknown time pattern directive %{c}")),
+        }
+    }
+    Ok(s)
+}
+
+/// Parse a Unix time from a string with a `strftime`-style pattern
+pub(crate) fn parse_time(pattern: &str, input: &str) -> Result<f64, String> {
+    struct Parser<'a> {
+        input: &'a str,
+        pos: usize,
+    }
+    impl Parser<'_> {
+        fn rest(&self) -> &str {
+            &self.input[self.pos..]
+        }
+        fn error(&self, expected: &str) -> String {
+            if self.rest().is_empty() {
+                format!("Expected {expected} at the end of {:?}", self.input)
+            } else {
+                format!(
+                    "Expected {expected} at {:?} in {:?}",
+                    self.rest(),
+                    self.input
+                )
+            }
+        }
+        fn number(&mut self, max_digits: usize, what: &str) -> Result<i64, String> {
+            let rest = self.rest();
+            let sign = rest.starts_with(['-', '+']) as usize;
+            let digits = rest[sign..]
+                .chars()
+                .take(max_digits)
+                .take_while(char::is_ascii_digit)
+                .count();
+            if digits == 0 {
+                return Err(self.error(what));
+            }
+            let n = rest[..sign + digits]
+                .parse()
+                .map_err(|_| self.error(what))?;
+            self.pos += sign + digits;
+            Ok(n)
+        }
+        fn unsigned(&mut self, max_digits: usize, what: &str) -> Result<i64, String> {
+            if self.rest().starts_with(['-', '+']) {
+                return Err(self.error(what));
+            }
+            self.number(max_digits, what)
+        }
+        fn name(&mut self, names: &[&str], what: &str) -> Result<usize, String> {
+            let rest = self.rest().to_lowercase();
+            for (i, name) in names.iter().enumerate() {
+                for name in [name.to_string(), name[..3].to_string()] {
+                    if rest.starts_with(&name.to_lowercase()) {
+                        self.pos += name.len();
+                        return Ok(i);
+                    }
+                }
+            }
+            Err(self.error(what))
+        }
+    }
+    let mut parser = Parser { input, pos: 0 };
+    let (mut year, mut month, mut day) = (1970, 1, 1);
+    let (mut hour, mut minute, mut second) = (0, 0, 0.0);
+    let mut day_of_year = None;
+    let mut pm = None;
+    let mut offset = 0;
+    let mut unix = None;
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            let rest = parser.rest();
+            parser.pos += rest.len() - rest.trim_start().len();
+            continue;
+        }
+        if c != '%' {
+            if !parser.rest().starts_with(c) {
+                return Err(parser.error(&format!("{c:?}")));
+            }
+            parser.pos += c.len_utf8();
+            continue;
+        }
+        let Some(directive) = chars.next() else {
+            return Err("Time pattern ends with an unfinished %".into());
+        };
+        match directive {
+            'Y' => year = parser.number(9, "a year")?,
+            'y' => {
+                let y = parser.unsigned(2, "a 2-digit year")?;
+                year = if y < 69 { 2000 + y } else { 1900 + y };
+            }
+            'm' => month = parser.unsigned(2, "a month")?,
+            'd' | 'e' => {
+                let rest = parser.rest();
+                parser.pos += rest.len() - rest.trim_start().len();
+                day = parser.unsigned(2, "a day")?;
+            }
+            'j' => day_of_year = Some(parser.unsigned(3, "a day of the year")?),
+            'H' => hour = parser.unsigned(2, "an hour")?,
+            'I' => hour = parser.unsigned(2, "an hour")? % 12,
+            'p' => {
+                let rest = parser.rest().to_lowercase();
+                pm = Some(if rest.starts_with("am") {
+                    false
+                } else if rest.starts_with("pm") {
+                    true
+                } else {
+                    return Err(parser.error("AM or PM"));
+                });
+                parser.pos += 2;
+            }
+            'M' => minute = parser.unsigned(2, "a minute")?,
+            'S' => second += parser.unsigned(2, "a second")? as f64,
+            'f' => {
+                let digits = parser
+                    .rest()
+                    .chars()
+                    .take_while(char::is_ascii_digit)
+                    .count();
+                if digits == 0 {
+                    return Err(parser.error("fractional seconds"));
+                }
+                let frac: f64 = format!("0.{}", &parser.rest()[..digits]).parse().unwrap();
+                second += frac;
+                parser.pos += digits;
+            }
+            'B' | 'b' => month = parser.name(&MONTHS, "a month name")? as i64 + 1,
+            'A' | 'a' => _ = parser.name(&WEEKDAYS, "a weekday name")?,
+            'F' => {
+                year = parser.number(9, "a year")?;
+                parse_literal(&mut parser.pos, parser.input, '-')?;
+                month = parser.unsigned(2, "a month")?;
+                parse_literal(&mut parser.pos, parser.input, '-')?;
+                day = parser.unsigned(2, "a day")?;
+            }
+            'T' => {
+                hour = parser.unsigned(2, "an hour")?;
+                parse_literal(&mut parser.pos, parser.input, ':')?;
+                minute = parser.unsigned(2, "a minute")?;
+                parse_literal(&mut parser.pos, parser.input, ':')?;
+                second += parser.unsigned(2, "a second")? as f64;
+            }
+            's' => unix = Some(parser.number(20, "a Unix time")?),
+            'z' => {
+                let rest = parser.rest();
+                if rest.starts_with(['Z', 'z']) {
+                    parser.pos += 1;
+                    continue;
+                }
+                let sign = match rest.chars().next() {
+                    Some('+') => 1,
+                    Some('-') => -1,
+                    _ => return Err(parser.error("a UTC offset like +0100")),
+                };
+                parser.pos += 1;
+                let hours = parser.unsigned(2, "a UTC offset like +0100")?;
+                if parser.rest().starts_with(':') {
+                    parser.pos += 1;
+                }
+                let minutes = parser.unsigned(2, "a UTC offset like +0100")?;
+                offset = sign * (hours * 3600 + minutes * 60);
+            }
+            'Z' => {
+                if !parser.rest().starts_with("UTC") {
+                    return Err(parser.error("UTC"));
+                }
+                parser.pos += 3;
+            }
+            '%' => parse_literal(&mut parser.pos, parser.input, '%')?,
+            c => return Err(format!("Unknown time pattern directive %{c}")),
+        }
+    }
+    if !parser.rest().is_empty() {
+        return Err(format!(
+            "Unexpected {:?} at the end of {input:?}",
+            parser.rest()
+        ));
+    }
+    if let Some(unix) = unix {
+        return Ok(unix as f64 + second.fract());
+    }
+    if pm == Some(true) {
+        hour += 12;
+    }
+    if !(1..=12).contains(&month) {
+        return Err(format!("Invalid month {month} in {input:?}"));
+    }
+    let month_days = [
+        31,
+        28 + is_leap_year(year) as i64,
+        31,
+        30,
+        31,
+        30,
+        31,
+        31,
+        30,
+        31,
+        30,
+        31,
+    ];
+    if day < 1 || day > month_days[month as usize - 1] {
+        return Err(format!("Invalid day {day} in {input:?}"));
+    }
+    if hour > 23 || minute > 59 || second >= 61.0 {
+        return Err(format!("Invalid time of day in {input:?}"));
+    }
+    let days = if let Some(doy) = day_of_year {
+        days_from_civil(year, 1, 1) + doy - 1
+    } else {
+        days_from_civil(year, month, day)
+    };
+    Ok(days as f64 * SECONDS_PER_DAY + (hour * 3600 + minute * 60 - offset) as f64 + second)
+}
+
+fn parse_literal(pos: &mut usize, input: &str, c: char) -> Result<(), String> {
+    if input[*pos..].starts_with(c) {
+        *pos += c.len_utf8();
+        Ok(())
+    } else {
+        Err(format!(
+            "Expected {c:?} at {:?} in {input:?}",
+            &input[*pos..]
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Uiua;
+
+    #[test]
+    fn date_times() {
+        let mut env = Uiua::with_native_sys();
+        env.load_str(
+            r#"
+    &dt 1234567890.5
+    &dts [2009 2 13 23 31 30]
+    &dts [2000 13 1]
+    &tf "%a %e %b %Y %T.%f %Z" 1234567890.5
+    &tp "%FT%T%z" "2009-02-14T01:31:30+02:00"
+    &tp "%I:%M %p, %d %B %y" "11:31 PM, 13 February 09"
+    "#,
+        )
+        .unwrap();
+        let stack = env
+            .take_stack()
+            .into_iter()
+            .map(|val| val.show())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stack,
+            [
+                "[2009 2 13 23 31 30.5]",
+                "1234567890",
+                "978307200",
+                "\"Fri 13 Feb 2009 23:31:30.500 UTC\"",
+                "1234567890",
+                "1234567860",
+            ]
+        );
+        for (pattern, date) in [("%F", "2009-02-30"), ("%Y", "2009 extra"), ("%H", "x")] {
+            let code = format!("&tp {pattern:?} {date:?}");
+            assert!(Uiua::with_native_sys().load_str(&code).is_err(), "{code}");
+        }
+        for seconds in [1e300, -1e17] {
+            assert!(time_to_components(seconds).is_err(), "{seconds}");
+        }
+        for date in [[1e300, 1.0, 1.0], [2000.0, -1e300, 1.0], [1.0, 1.0, 1e10]] {
+            assert!(components_to_time(&date).is_err(), "{date:?}");
+        }
+        for date in [[1e9, 1e9, 1e9], [-1e9, -1e9, -1e9]] {
+            components_to_time(&date).unwrap();
+        }
+        for seconds in [3e16, -3e16] {
+            time_to_components(seconds).unwrap();
+        }
+        for days in [-719468 * 2, -1, 0, 59, 60, 365, 11016, 2932896] {
+            let seconds = days as f64 * 86400.0;
+            let components = time_to_components(seconds).unwrap();
+            assert_eq!(components_to_time(&components).unwrap(), seconds);
+        }
+    }
+}
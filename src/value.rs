@@ -341,6 +341,17 @@ impl Value {
             Self::Func(array) => array.grid_string(),
         }
     }
+    /// Get a plain string representation of the value without box-drawing characters
+    ///
+    /// Scalars and lists are formatted as they are printed by `&p`.
+    /// Each row of a higher-rank array is on its own line.
+    pub fn plain_string(&self) -> String {
+        if self.rank() <= 1 {
+            return self.to_string();
+        }
+        let rows: Vec<String> = self.rows().map(|row| row.plain_string()).collect();
+        rows.join("\n")
+    }
     pub fn as_primitive(&self) -> Option<(Primitive, usize)> {
         if let Value::Func(fs) = self {
             if fs.rank() == 0 {
@@ -730,6 +741,187 @@ impl From<i32> for Value {
     }
 }
 
+impl From<i64> for Value {
+    fn from(i: i64) -> Self {
+        Value::from(i as f64)
+    }
+}
+
+/// An error from converting between a [`Value`] and a Rust type
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError(String);
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+impl From<ConversionError> for String {
+    fn from(error: ConversionError) -> Self {
+        error.0
+    }
+}
+
+impl TryFrom<(Vec<usize>, Vec<f64>)> for Value {
+    type Error = ConversionError;
+    fn try_from((shape, data): (Vec<usize>, Vec<f64>)) -> Result<Self, Self::Error> {
+        let count = (shape.iter()).try_fold(1usize, |count, &n| count.checked_mul(n));
+        let Some(count) = count else {
+            return Err(ConversionError(format!(
+                "Shape {} has too many elements",
+                FormatShape(&shape)
+            )));
+        };
+        if count != data.len() {
+            return Err(ConversionError(format!(
+                "Shape {} has {count} elements, but there are {} numbers",
+                FormatShape(&shape),
+                data.len()
+            )));
+        }
+        Ok(Array::new(shape.as_slice(), data).into())
+    }
+}
+
+impl TryFrom<Vec<Vec<f64>>> for Value {
+    type Error = ConversionError;
+    fn try_from(rows: Vec<Vec<f64>>) -> Result<Self, Self::Error> {
+        let row_len = rows.first().map_or(0, Vec::len);
+        if let Some((i, row)) = (rows.iter().enumerate()).find(|(_, row)| row.len() != row_len) {
+            return Err(ConversionError(format!(
+                "Rows must all have the same length, but row 0 has {row_len} \
+                elements and row {i} has {}",
+                row.len()
+            )));
+        }
+        let shape = vec![rows.len(), row_len];
+        Value::try_from((shape, rows.into_iter().flatten().collect::<Vec<_>>()))
+    }
+}
+
+impl Value {
+    /// Get the shape and data of a number array
+    fn into_shape_and_nums(
+        self,
+        expected: &str,
+    ) -> Result<(Vec<usize>, Vec<f64>), ConversionError> {
+        match self {
+            Value::Num(array) => Ok((array.shape.to_vec(), array.data.into())),
+            Value::Byte(array) => Ok((
+                array.shape.to_vec(),
+                array.data.iter().map(|&b| b as f64).collect(),
+            )),
+            value => Err(ConversionError(format!(
+                "Expected {expected}, but the value is a {} array",
+                value.type_name()
+            ))),
+        }
+    }
+    /// Get the single number of a scalar number array
+    fn into_scalar_num(self, expected: &str) -> Result<f64, ConversionError> {
+        let (shape, data) = self.into_shape_and_nums(expected)?;
+        if !shape.is_empty() {
+            return Err(ConversionError(format!(
+                "Expected {expected}, but the value has shape {}",
+                FormatShape(&shape)
+            )));
+        }
+        Ok(data[0])
+    }
+}
+
+impl TryFrom<Value> for f64 {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.into_scalar_num("a number")
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let n = value.into_scalar_num("an integer")?;
+        if n.fract() != 0.0 || !n.is_finite() {
+            return Err(ConversionError(format!(
+                "Expected an integer, but it is {n}"
+            )));
+        }
+        Ok(n as i64)
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let n = value.into_scalar_num("a boolean")?;
+        if n == 0.0 || n == 1.0 {
+            Ok(n == 1.0)
+        } else {
+            Err(ConversionError(format!(
+                "Expected a boolean, but it is {n}"
+            )))
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Char(chars) if chars.rank() <= 1 => Ok(chars.data.iter().collect()),
+            Value::Char(chars) => Err(ConversionError(format!(
+                "Expected a string, but the value has shape {}",
+                chars.format_shape()
+            ))),
+            value => Err(ConversionError(format!(
+                "Expected a string, but the value is a {} array",
+                value.type_name()
+            ))),
+        }
+    }
+}
+
+impl TryFrom<Value> for Vec<f64> {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let (shape, data) = value.into_shape_and_nums("a list of numbers")?;
+        if shape.len() != 1 {
+            return Err(ConversionError(format!(
+                "Expected a list of numbers, but the value has shape {}",
+                FormatShape(&shape)
+            )));
+        }
+        Ok(data)
+    }
+}
+
+impl TryFrom<Value> for Vec<Vec<f64>> {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let (shape, data) = value.into_shape_and_nums("a table of numbers")?;
+        if shape.len() != 2 {
+            return Err(ConversionError(format!(
+                "Expected a table of numbers, but the value has shape {}",
+                FormatShape(&shape)
+            )));
+        }
+        if shape[1] == 0 {
+            return Ok(vec![Vec::new(); shape[0]]);
+        }
+        Ok(data.chunks_exact(shape[1]).map(<[f64]>::to_vec).collect())
+    }
+}
+
+impl TryFrom<Value> for (Vec<usize>, Vec<f64>) {
+    type Error = ConversionError;
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.into_shape_and_nums("a number array")
+    }
+}
+
 macro_rules! value_un_impl {
     ($name:ident, $(($variant:ident, $f:ident)),* $(,)?) => {
         impl Value {